@@ -0,0 +1,97 @@
+use anyhow::Result;
+use ocrs::OcrEngine;
+use screenshots::Screen;
+use std::io::{self, Write};
+
+use crate::config::{Config, DetectionStrategy, Region};
+use crate::ocr::{capture_screen, find_text_line};
+use crate::pokemon::extract_encounter_name;
+
+/// Extra space kept around the detected text, in screen coordinates
+const REGION_PADDING: u32 = 10;
+/// Extra width to the right of the detected text so longer names still fit
+const NAME_ALLOWANCE: u32 = 250;
+
+/// Locate the capture region by OCR-ing the full screen
+/// 
+/// Searches for the first line matching the configured detection pattern,
+/// proposes a padded region around it and offers to save it to the config.
+/// 
+/// # Arguments
+/// * `engine` - The OCR engine to use
+/// * `screen` - The screen to search
+/// * `config` - Configuration to update with the detected region
+/// 
+/// # Returns
+/// * `Ok(true)` if the detected region was accepted
+/// * `Ok(false)` if nothing was found or the region was rejected
+pub fn autodetect_region(engine: &OcrEngine, screen: &Screen, config: &mut Config) -> Result<bool> {
+    println!("\n=== Region Autodetection ===");
+    println!("Start a wild battle so '{}' is visible on screen.", config.pattern_label());
+    print!("Press Enter to scan the screen...");
+    io::stdout().flush()?;
+    io::stdin().read_line(&mut String::new())?;
+
+    println!("Scanning screen (this can take a few seconds)...");
+    let image = capture_screen(screen)?;
+    let matched = find_text_line(engine, &image, |text| extract_encounter_name(text, config).is_some())?;
+
+    let Some((pixel_rect, text)) = matched else {
+        println!("✗ Could not find '{}' on screen", config.pattern_label());
+        return Ok(false);
+    };
+
+    // The capture may be in physical pixels while regions use screen coordinates
+    let display = screen.display_info;
+    let scale = image.width() as f32 / display.width as f32;
+    let region = to_screen_region(&pixel_rect, scale, display.width, display.height);
+
+    println!("✓ Found \"{}\"", text);
+    println!("  Proposed region: X: {}, Y: {}, Width: {}, Height: {}",
+             region.x, region.y, region.width, region.height);
+
+    print!("Use this region? (y/n): ");
+    io::stdout().flush()?;
+    let mut choice = String::new();
+    io::stdin().read_line(&mut choice)?;
+    if choice.trim().to_lowercase() != "y" {
+        return Ok(false);
+    }
+
+    match config.detection_strategy {
+        DetectionStrategy::Banner => config.region = region,
+        DetectionStrategy::BattleLog => config.battle_log_region = region,
+    }
+
+    print!("Save this configuration for future use? (y/n): ");
+    io::stdout().flush()?;
+    let mut save_choice = String::new();
+    io::stdin().read_line(&mut save_choice)?;
+    if save_choice.trim().to_lowercase() == "y" {
+        config.save()?;
+    }
+
+    Ok(true)
+}
+
+/// Convert a pixel rectangle into a padded region in screen coordinates
+fn to_screen_region(pixel_rect: &Region, scale: f32, screen_width: u32, screen_height: u32) -> Region {
+    let scale = if scale > 0.0 { scale } else { 1.0 };
+    let left = (pixel_rect.x as f32 / scale) as i64 - REGION_PADDING as i64;
+    let top = (pixel_rect.y as f32 / scale) as i64 - REGION_PADDING as i64;
+    let right = ((pixel_rect.x as f32 + pixel_rect.width as f32) / scale) as i64
+        + (REGION_PADDING + NAME_ALLOWANCE) as i64;
+    let bottom = ((pixel_rect.y as f32 + pixel_rect.height as f32) / scale) as i64 + REGION_PADDING as i64;
+
+    let left = left.clamp(0, screen_width as i64);
+    let top = top.clamp(0, screen_height as i64);
+    let right = right.clamp(left, screen_width as i64);
+    let bottom = bottom.clamp(top, screen_height as i64);
+
+    Region {
+        x: left as i32,
+        y: top as i32,
+        width: (right - left) as u32,
+        height: (bottom - top) as u32,
+    }
+}
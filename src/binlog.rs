@@ -0,0 +1,258 @@
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::export::csv_field;
+use crate::pokemon::ShinyStatus;
+use crate::sink::{CountEvent, CountSink};
+use crate::statistics::HuntStats;
+
+/// Written at the start of every binary encounter log
+const LOG_MAGIC: &[u8] = b"PRTNLOG1";
+/// Extension of the species table kept beside the log
+const SPECIES_TABLE_EXTENSION: &str = "species";
+
+/// One encounter read back from a binary log
+#[derive(Debug, Clone)]
+pub struct LoggedEncounter {
+    pub name: String,
+    /// Active hunting time across all sessions in the log
+    pub active_time: Duration,
+}
+
+/// Appends counts to a compact binary encounter log
+///
+/// Each record is two LEB128 varints: the species id and the seconds of
+/// active time since the previous record, so a typical record is 2-3 bytes.
+/// Species ids index the newline-separated species table in the sidecar
+/// `<log>.species` file, which grows as new names are counted. Sessions
+/// appended to an existing log continue its timeline.
+pub struct BinaryLogSink {
+    file: File,
+    species_file: File,
+    species_ids: HashMap<String, u64>,
+    /// Active time the log had reached before this session started
+    session_start_secs: u64,
+    last_secs: u64,
+}
+
+impl BinaryLogSink {
+    pub fn open(path: &Path) -> Result<Self> {
+        let species_path = species_table_path(path);
+        let species = read_species_table(&species_path)?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open encounter log {}", path.display()))?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        let last_secs = if bytes.is_empty() {
+            file.write_all(LOG_MAGIC)?;
+            0
+        } else {
+            let records = log_records(&bytes, path)?;
+            // Drop a record cut short by a crash so new records stay aligned
+            file.set_len((LOG_MAGIC.len() + records.valid_len) as u64)?;
+            records.entries.iter().map(|&(_, delta)| delta).sum()
+        };
+
+        let species_file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&species_path)
+            .with_context(|| format!("Failed to open species table {}", species_path.display()))?;
+
+        Ok(Self {
+            file,
+            species_file,
+            species_ids: species.into_iter().zip(0..).collect(),
+            session_start_secs: last_secs,
+            last_secs,
+        })
+    }
+
+    /// Id of `name`, adding it to the species table first if it's new
+    fn species_id(&mut self, name: &str) -> Result<u64> {
+        if let Some(&id) = self.species_ids.get(name) {
+            return Ok(id);
+        }
+        let id = self.species_ids.len() as u64;
+        writeln!(self.species_file, "{}", name)?;
+        self.species_ids.insert(name.to_string(), id);
+        Ok(id)
+    }
+
+    fn append(&mut self, event: &CountEvent) -> Result<()> {
+        let id = self.species_id(event.name)?;
+        let secs = (self.session_start_secs + event.active_secs).max(self.last_secs);
+
+        let mut record = Vec::new();
+        write_varint(&mut record, id);
+        write_varint(&mut record, secs - self.last_secs);
+        self.file.write_all(&record)?;
+        self.last_secs = secs;
+        Ok(())
+    }
+}
+
+impl CountSink for BinaryLogSink {
+    fn record(&mut self, event: &CountEvent) {
+        if let Err(e) = self.append(event) {
+            eprintln!("Failed to write encounter log: {:#}", e);
+        }
+    }
+}
+
+/// Decoded records of a binary log
+struct LogRecords {
+    /// (species id, seconds since the previous record)
+    entries: Vec<(u64, u64)>,
+    /// Bytes after the header holding complete records
+    valid_len: usize,
+}
+
+/// Decode the records of a binary log, stopping at a truncated record
+fn log_records(bytes: &[u8], path: &Path) -> Result<LogRecords> {
+    let Some(body) = bytes.strip_prefix(LOG_MAGIC) else {
+        bail!("{} is not a binary encounter log", path.display());
+    };
+
+    let mut remaining = body;
+    let mut entries = Vec::new();
+    let mut valid_len = 0;
+    while let Some(id) = read_varint(&mut remaining)
+        && let Some(delta) = read_varint(&mut remaining)
+    {
+        entries.push((id, delta));
+        valid_len = body.len() - remaining.len();
+    }
+    Ok(LogRecords { entries, valid_len })
+}
+
+/// Read every encounter from a binary log and its species table
+///
+/// A record cut short by a crash mid-write is ignored.
+pub fn read_binary_log(path: &Path) -> Result<Vec<LoggedEncounter>> {
+    let bytes = fs::read(path)
+        .with_context(|| format!("Failed to read encounter log {}", path.display()))?;
+    if bytes.is_empty() {
+        return Ok(Vec::new());
+    }
+    let records = log_records(&bytes, path)?;
+    let species = read_species_table(&species_table_path(path))?;
+
+    let mut secs = 0;
+    records
+        .entries
+        .into_iter()
+        .map(|(id, delta)| {
+            let name = species
+                .get(id as usize)
+                .with_context(|| format!("Species id {} missing from the species table of {}", id, path.display()))?;
+            secs += delta;
+            Ok(LoggedEncounter {
+                name: name.clone(),
+                active_time: Duration::from_secs(secs),
+            })
+        })
+        .collect()
+}
+
+/// Rebuild session statistics (counts and encounter log) from logged encounters
+pub fn replay_encounters(encounters: &[LoggedEncounter], config: &Config) -> HuntStats {
+    let mut stats = HuntStats::new(config);
+    for encounter in encounters {
+        stats.record(&encounter.name, encounter.active_time, ShinyStatus::Normal);
+    }
+    stats
+}
+
+/// Print a binary log as CSV, with a short summary on stderr
+///
+/// With `timeline_bucket`, prints one row per bucket of active time instead
+/// of one per encounter: the bucket start, the total and a column per
+/// species, with empty buckets kept so the timeline can be plotted directly.
+pub fn dump_csv(path: &Path, timeline_bucket: Option<Duration>) -> Result<()> {
+    let encounters = read_binary_log(path)?;
+    // The last encounter's second still belongs to the hunt
+    let duration = encounters.last().map_or(Duration::ZERO, |e| e.active_time + Duration::from_secs(1));
+    let stats = replay_encounters(&encounters, &Config::preset());
+
+    match timeline_bucket {
+        Some(bucket) => print_timeline_csv(&stats, bucket, duration),
+        None => {
+            println!("species,active_secs");
+            for encounter in &encounters {
+                println!("{},{}", csv_field(&encounter.name), encounter.active_time.as_secs());
+            }
+        }
+    }
+
+    match stats.encounter_log.session_rate_per_hour(duration) {
+        Some(rate) => eprintln!("{} encounters, {:.1}/hour", encounters.len(), rate),
+        None => eprintln!("{} encounters", encounters.len()),
+    }
+    Ok(())
+}
+
+fn print_timeline_csv(stats: &HuntStats, bucket: Duration, duration: Duration) {
+    let mut species: Vec<&String> = stats.text_counts.keys().collect();
+    species.sort();
+
+    let mut header = vec!["start_secs".to_string(), "total".to_string()];
+    header.extend(species.iter().map(|name| csv_field(name)));
+    println!("{}", header.join(","));
+    for entry in stats.encounter_log.timeline(bucket, duration) {
+        let mut row = vec![entry.start_secs.to_string(), entry.total.to_string()];
+        row.extend(species.iter().map(|name| entry.counts.get(*name).copied().unwrap_or(0).to_string()));
+        println!("{}", row.join(","));
+    }
+}
+
+fn species_table_path(log_path: &Path) -> PathBuf {
+    let mut path = log_path.as_os_str().to_owned();
+    path.push(".");
+    path.push(SPECIES_TABLE_EXTENSION);
+    PathBuf::from(path)
+}
+
+fn read_species_table(path: &Path) -> Result<Vec<String>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read species table {}", path.display()))?;
+    Ok(contents.lines().map(str::to_string).collect())
+}
+
+/// Append `value` as an unsigned LEB128 varint
+fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+/// Read an unsigned LEB128 varint, advancing `bytes` past it
+///
+/// Returns `None` at the end of the input or on a truncated varint.
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for (index, &byte) in bytes.iter().enumerate().take(10) {
+        value |= u64::from(byte & 0x7f) << (7 * index);
+        if byte & 0x80 == 0 {
+            *bytes = &bytes[index + 1..];
+            return Some(value);
+        }
+    }
+    None
+}
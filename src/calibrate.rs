@@ -0,0 +1,108 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::thread;
+
+use crate::capture::CaptureProvider;
+use crate::config::Config;
+use crate::ocr::OcrProvider;
+use crate::pokemon::extract_encounter_name;
+
+const PERCENTAGE_MULTIPLIER: f64 = 100.0;
+
+/// How consistently one preprocessing mode read the same name
+struct Consistency {
+    /// Most common name read, if any frame matched the pattern
+    most_common: Option<(String, usize)>,
+    /// Frames where the pattern matched
+    matched: usize,
+    /// Frames sampled
+    samples: usize,
+}
+
+impl Consistency {
+    fn from_reads(reads: &[Option<String>]) -> Self {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for name in reads.iter().flatten() {
+            *counts.entry(name.as_str()).or_insert(0) += 1;
+        }
+        let most_common = counts
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(name, count)| (name.to_string(), count));
+
+        Self {
+            most_common,
+            matched: reads.iter().flatten().count(),
+            samples: reads.len(),
+        }
+    }
+
+    /// Share of all samples that produced the most common name (0.0-1.0)
+    fn agreement(&self) -> f64 {
+        match &self.most_common {
+            Some((_, count)) if self.samples > 0 => *count as f64 / self.samples as f64,
+            _ => 0.0,
+        }
+    }
+
+    fn print(&self, label: &str) {
+        print!("  {:<18} agreement {:>5.1}%, matched {}/{}",
+               label, self.agreement() * PERCENTAGE_MULTIPLIER, self.matched, self.samples);
+        match &self.most_common {
+            Some((name, _)) => println!(", most common \"{}\"", name),
+            None => println!(", no name read"),
+        }
+    }
+}
+
+/// Measure how consistently the capture region is read with and without preprocessing
+///
+/// Captures the configured region `calibration_samples` times while the
+/// user keeps a battle banner on screen, OCRs every frame both ways and
+/// recommends the mode whose reads agree most often.
+///
+/// # Arguments
+/// * `ocr_provider` - The OCR provider to measure
+/// * `capture` - Captures the configured region
+/// * `config` - Configuration providing the region and sample count
+pub fn calibrate(ocr_provider: &dyn OcrProvider, capture: &dyn CaptureProvider, config: &Config) -> Result<()> {
+    println!("\n=== OCR Calibration ===");
+    println!("Start a wild battle so '{}' stays visible during calibration.", config.pattern_label());
+    print!("Press Enter to start sampling...");
+    io::stdout().flush()?;
+    io::stdin().read_line(&mut String::new())?;
+
+    let mut raw_reads = Vec::with_capacity(config.calibration_samples);
+    let mut preprocessed_reads = Vec::with_capacity(config.calibration_samples);
+    for sample in 1..=config.calibration_samples {
+        print!("\rSampling {}/{}...", sample, config.calibration_samples);
+        io::stdout().flush()?;
+
+        let image = capture.capture_region(config.capture_region())?;
+        let raw_text = ocr_provider.extract_text(&image, false)?;
+        let preprocessed_text = ocr_provider.extract_text(&image, true)?;
+        raw_reads.push(extract_encounter_name(&raw_text, config));
+        preprocessed_reads.push(extract_encounter_name(&preprocessed_text, config));
+
+        thread::sleep(config.refresh_rate);
+    }
+    println!("\n");
+
+    let raw = Consistency::from_reads(&raw_reads);
+    let preprocessed = Consistency::from_reads(&preprocessed_reads);
+    raw.print("Preprocessing off:");
+    preprocessed.print("Preprocessing on:");
+
+    if raw.matched == 0 && preprocessed.matched == 0 {
+        println!("\n✗ The pattern was never read - check the region and that the banner was visible");
+        return Ok(());
+    }
+
+    // Preprocessing costs extra time, so it has to read strictly better
+    let recommended = preprocessed.agreement() > raw.agreement();
+    println!("\nRecommendation: preprocess_images = {}{}",
+             recommended,
+             if recommended == config.preprocess_images { " (current setting)" } else { "" });
+    Ok(())
+}
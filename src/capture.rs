@@ -0,0 +1,289 @@
+use image::DynamicImage;
+use screenshots::Screen;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::{CaptureBackend, Config, Region};
+use crate::error::ProteanError;
+use crate::ocr::capture_region;
+
+/// Replaced with a temporary file path the capture command writes to
+const OUTPUT_PLACEHOLDER: &str = "{output}";
+
+/// Frames buffered between the capture thread and OCR; when full, new
+/// frames are dropped and OCR skips ahead to the newest one it has
+const CAPTURE_QUEUE_CAPACITY: usize = 2;
+
+/// One capture of every monitored region, in target order
+pub type CapturedFrame = Vec<Result<DynamicImage, ProteanError>>;
+
+/// Trait for screen capture to allow for different capture backends
+pub trait CaptureProvider: Send + Sync {
+    /// Capture a region of the screen
+    fn capture_region(&self, region: &Region) -> Result<DynamicImage, ProteanError>;
+}
+
+/// Build the capture provider selected in the config
+pub fn build_capture_provider(screen: Screen, config: &Config) -> Arc<dyn CaptureProvider> {
+    let command = config.capture_command.clone().map(CommandCapture::new);
+    match (config.capture_backend, command) {
+        (CaptureBackend::Command, Some(command)) => Arc::new(command),
+        (CaptureBackend::Screenshots, Some(command)) => Arc::new(FallbackCapture {
+            primary: ScreenCapture { screen },
+            fallback: command,
+            using_fallback: AtomicBool::new(false),
+        }),
+        // validate() rejects the command backend without a command
+        (_, None) => Arc::new(ScreenCapture { screen }),
+    }
+}
+
+/// The screen at `monitor_index` (see `--list-monitors`)
+pub fn select_screen(screens: &[Screen], monitor_index: usize) -> Result<&Screen, ProteanError> {
+    match screens.len() {
+        0 => Err(ProteanError::Config("No screens found".to_string())),
+        count => screens.get(monitor_index).ok_or_else(|| ProteanError::Config(format!(
+            "monitor_index {} is out of range: {} screen(s) found, valid indices are 0-{} (see --list-monitors)",
+            monitor_index, count, count - 1))),
+    }
+}
+
+/// Print the index, resolution and position of each screen
+pub fn list_monitors(screens: &[Screen]) {
+    if screens.is_empty() {
+        println!("No screens found");
+        return;
+    }
+    for (index, screen) in screens.iter().enumerate() {
+        let display = screen.display_info;
+        println!("{}: {}x{} at ({}, {}), scale {}{}",
+                 index, display.width, display.height, display.x, display.y, display.scale_factor,
+                 if display.is_primary { " (primary)" } else { "" });
+    }
+}
+
+/// Clamp the capture regions to the screen they're captured from
+///
+/// Regions are relative to the screen at `monitor_index`. The capture library crops whatever
+/// lies outside it on every frame and fails every frame when nothing is
+/// left, so a region partly off the screen is clamped once with a warning
+/// and one entirely off it is rejected with the layout of all screens.
+pub fn fit_regions_to_screen(screens: &[Screen], config: &mut Config) -> Result<(), ProteanError> {
+    let monitor_index = config.monitor_index;
+    let display = select_screen(screens, monitor_index)?.display_info;
+    let (screen_width, screen_height) = (i64::from(display.width), i64::from(display.height));
+    for (label, region) in config.captured_regions_mut() {
+        let left = i64::from(region.x).max(0);
+        let top = i64::from(region.y).max(0);
+        let right = (i64::from(region.x) + i64::from(region.width)).min(screen_width);
+        let bottom = (i64::from(region.y) + i64::from(region.height)).min(screen_height);
+
+        if left >= right || top >= bottom {
+            // Point out a region meant for another monitor of the virtual desktop
+            let (virtual_x, virtual_y) = (display.x + region.x, display.y + region.y);
+            let elsewhere = screens
+                .iter()
+                .position(|other| {
+                    let other = other.display_info;
+                    other.id != display.id
+                        && (other.x..other.x + other.width as i32).contains(&virtual_x)
+                        && (other.y..other.y + other.height as i32).contains(&virtual_y)
+                })
+                .map(|index| format!(" (it lies on screen {}; set monitor_index = {} and make the region \
+                                      relative to that screen)", index, index))
+                .unwrap_or_default();
+            let layout: Vec<String> = screens
+                .iter()
+                .enumerate()
+                .map(|(index, other)| {
+                    let other = other.display_info;
+                    format!("{}: {}x{} at ({}, {})", index, other.width, other.height, other.x, other.y)
+                })
+                .collect();
+            return Err(ProteanError::Config(format!(
+                "{} {}x{} at ({}, {}) is outside the {}x{} screen {}{}. Screens: {}",
+                label, region.width, region.height, region.x, region.y,
+                screen_width, screen_height, monitor_index, elsewhere, layout.join(", "))));
+        }
+
+        let fitted = Region {
+            x: left as i32,
+            y: top as i32,
+            width: (right - left) as u32,
+            height: (bottom - top) as u32,
+        };
+        if fitted != *region {
+            println!("⚠ {} {}x{} at ({}, {}) extends past the {}x{} screen; capturing {}x{} at ({}, {}) instead",
+                     label, region.width, region.height, region.x, region.y, screen_width, screen_height,
+                     fitted.width, fitted.height, fitted.x, fitted.y);
+            *region = fitted;
+        }
+    }
+    Ok(())
+}
+
+/// Captures with the built-in `screenshots` crate
+pub struct ScreenCapture {
+    screen: Screen,
+}
+
+impl CaptureProvider for ScreenCapture {
+    fn capture_region(&self, region: &Region) -> Result<DynamicImage, ProteanError> {
+        capture_region(&self.screen, region)
+    }
+}
+
+/// Runs a user-configured screenshot command and reads the image it produces
+///
+/// `{x}`, `{y}`, `{width}` and `{height}` in the command are replaced with
+/// the region. With an `{output}` placeholder the command writes the image
+/// to that temporary file; otherwise it must write it to stdout.
+pub struct CommandCapture {
+    command: String,
+    output_path: PathBuf,
+}
+
+impl CommandCapture {
+    pub fn new(command: String) -> Self {
+        let output_path = std::env::temp_dir().join(format!("protean-capture-{}.png", std::process::id()));
+        Self { command, output_path }
+    }
+}
+
+impl CaptureProvider for CommandCapture {
+    fn capture_region(&self, region: &Region) -> Result<DynamicImage, ProteanError> {
+        let failed = |cause: String| ProteanError::Capture {
+            what: format!("region {}x{} at ({}, {}) with capture_command", region.width, region.height, region.x, region.y),
+            cause: cause.into(),
+        };
+
+        let writes_file = self.command.contains(OUTPUT_PLACEHOLDER);
+        let command = self
+            .command
+            .replace("{x}", &region.x.to_string())
+            .replace("{y}", &region.y.to_string())
+            .replace("{width}", &region.width.to_string())
+            .replace("{height}", &region.height.to_string())
+            .replace(OUTPUT_PLACEHOLDER, &self.output_path.display().to_string());
+        let output = Command::new("sh")
+            .args(["-c", &command])
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| failed(e.to_string()))?;
+        if !output.status.success() {
+            return Err(failed(format!("{} ({})", output.status, String::from_utf8_lossy(&output.stderr).trim())));
+        }
+
+        let image = if writes_file {
+            let bytes = fs::read(&self.output_path).map_err(|e| failed(e.to_string()))?;
+            let _ = fs::remove_file(&self.output_path);
+            image::load_from_memory(&bytes)
+        } else {
+            image::load_from_memory(&output.stdout)
+        };
+        image.map_err(|e| failed(e.to_string()))
+    }
+}
+
+/// Uses the built-in capture until it fails or returns an all-black frame,
+/// then switches to the capture command for the rest of the session
+pub struct FallbackCapture {
+    primary: ScreenCapture,
+    fallback: CommandCapture,
+    using_fallback: AtomicBool,
+}
+
+impl CaptureProvider for FallbackCapture {
+    fn capture_region(&self, region: &Region) -> Result<DynamicImage, ProteanError> {
+        if !self.using_fallback.load(Ordering::Relaxed) {
+            let problem = match self.primary.capture_region(region) {
+                Ok(image) if !is_all_black(&image) => return Ok(image),
+                Ok(_) => "returned a black frame".to_string(),
+                Err(e) => e.to_string(),
+            };
+            eprintln!("⚠ Built-in capture {} - switching to capture_command", problem);
+            self.using_fallback.store(true, Ordering::Relaxed);
+        }
+        self.fallback.capture_region(region)
+    }
+}
+
+/// Whether every pixel is black, as some compositors return instead of failing
+fn is_all_black(image: &DynamicImage) -> bool {
+    image.to_luma8().pixels().all(|pixel| pixel.0[0] == 0)
+}
+
+/// Captures the monitored regions on a background thread at a fixed interval
+///
+/// Decouples the capture cadence from OCR: a slow OCR pass no longer delays
+/// the next capture, and the consumer always works on the freshest frame.
+pub struct FrameCapture {
+    receiver: Receiver<CapturedFrame>,
+    paused: Arc<AtomicBool>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl FrameCapture {
+    /// Start capturing `regions` every `interval`
+    pub fn spawn(capture: Arc<dyn CaptureProvider>, regions: Vec<Region>, interval: Duration) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(CAPTURE_QUEUE_CAPACITY);
+        let paused = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let thread_paused = Arc::clone(&paused);
+        let thread_stopped = Arc::clone(&stopped);
+        thread::spawn(move || {
+            let mut next_capture = Instant::now();
+            while !thread_stopped.load(Ordering::Relaxed) {
+                if !thread_paused.load(Ordering::Relaxed) {
+                    let frame: CapturedFrame = regions
+                        .iter()
+                        .map(|region| capture.capture_region(region))
+                        .collect();
+                    if let Err(TrySendError::Disconnected(_)) = sender.try_send(frame) {
+                        return;
+                    }
+                }
+
+                // Keep a steady cadence, skipping ticks missed while capturing
+                next_capture += interval;
+                let now = Instant::now();
+                if next_capture < now {
+                    next_capture = now;
+                }
+                thread::sleep(next_capture - now);
+            }
+        });
+
+        Self { receiver, paused, stopped }
+    }
+
+    /// Stop or resume capturing (e.g. while monitoring is paused)
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    /// Wait up to `timeout` for a frame and return the newest one queued,
+    /// dropping any older frames
+    pub fn latest(&self, timeout: Duration) -> Option<CapturedFrame> {
+        let first = self.receiver.recv_timeout(timeout).ok()?;
+        Some(self.receiver.try_iter().last().unwrap_or(first))
+    }
+
+    /// Drop all queued frames so stale captures aren't processed after a pause
+    pub fn discard(&self) {
+        self.receiver.try_iter().for_each(drop);
+    }
+}
+
+impl Drop for FrameCapture {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
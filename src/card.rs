@@ -0,0 +1,181 @@
+use anyhow::{Context, Result};
+use font8x8::{UnicodeFonts, BASIC_FONTS};
+use image::{Rgb, RgbImage};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::statistics::format_duration;
+
+// Card layout constants
+const CARD_WIDTH: u32 = 640;
+const CARD_HEIGHT: u32 = 400;
+const CARD_PADDING: u32 = 24;
+const GLYPH_SIZE: u32 = 8;
+const TITLE_SCALE: u32 = 3;
+const BODY_SCALE: u32 = 2;
+const LINE_SPACING: u32 = 6;
+const CARD_TOP_N: usize = 8;
+const MAX_NAME_CHARS: usize = 18;
+const PERCENTAGE_MULTIPLIER: f64 = 100.0;
+
+/// Base shiny rate used to compute the "shiny odds" line (1 in N)
+const SHINY_ODDS_DENOMINATOR: f64 = 1000.0;
+
+// Card colors
+const BACKGROUND_COLOR: Rgb<u8> = Rgb([24, 26, 33]);
+const ACCENT_COLOR: Rgb<u8> = Rgb([255, 203, 5]);
+const TEXT_COLOR: Rgb<u8> = Rgb([235, 235, 235]);
+const MUTED_COLOR: Rgb<u8> = Rgb([150, 150, 160]);
+
+/// Render a summary card of the hunt statistics and save it as a PNG
+///
+/// The card has a fixed size so it can be dropped into a stream overlay.
+/// It shows the top species, total encounters, hunt duration, the
+/// chance of having seen at least one shiny at the base rate and, when
+/// targets are configured, the encounters since the last target.
+///
+/// # Arguments
+/// * `text_counts` - HashMap of pokemon names to encounter counts
+/// * `hunt_duration` - Total active hunting time (excluding pauses)
+/// * `since_last_target` - Encounters since the last target, if targets are configured
+/// * `path` - Destination PNG file
+pub fn export_image(
+    text_counts: &HashMap<String, usize>,
+    hunt_duration: Duration,
+    since_last_target: Option<usize>,
+    path: &Path,
+) -> Result<()> {
+    let mut card = RgbImage::from_pixel(CARD_WIDTH, CARD_HEIGHT, BACKGROUND_COLOR);
+    let body_line_height = GLYPH_SIZE * BODY_SCALE + LINE_SPACING;
+
+    let mut y = CARD_PADDING;
+    draw_text(&mut card, "PROTEAN HUNT", CARD_PADDING, y, TITLE_SCALE, ACCENT_COLOR);
+    y += GLYPH_SIZE * TITLE_SCALE + LINE_SPACING * 2;
+
+    let total: usize = text_counts.values().sum();
+    let mut sorted: Vec<_> = text_counts.iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+    if sorted.is_empty() {
+        draw_text(&mut card, "No encounters recorded", CARD_PADDING, y, BODY_SCALE, MUTED_COLOR);
+    }
+
+    for (name, count) in sorted.iter().take(CARD_TOP_N) {
+        let percentage = (**count as f64 / total as f64) * PERCENTAGE_MULTIPLIER;
+        let display_name: String = name.chars().take(MAX_NAME_CHARS).collect();
+        let line = format!("{:<width$} {:>5} {:>5.1}%", display_name, count, percentage, width = MAX_NAME_CHARS);
+        draw_text(&mut card, &line, CARD_PADDING, y, BODY_SCALE, TEXT_COLOR);
+        y += body_line_height;
+    }
+
+    // Footer is anchored to the bottom so the card layout stays stable
+    let shiny_chance = 1.0 - (1.0 - 1.0 / SHINY_ODDS_DENOMINATOR).powf(total as f64);
+    let mut footer = vec![
+        format!("Total: {}", total),
+        format!("Duration: {}", format_duration(hunt_duration, 0)),
+        format!("Shiny odds: {:.1}% (1/{})", shiny_chance * PERCENTAGE_MULTIPLIER, SHINY_ODDS_DENOMINATOR),
+    ];
+    if let Some(since) = since_last_target {
+        footer.push(format!("Since last target: {}", since));
+    }
+    let mut footer_y = CARD_HEIGHT - CARD_PADDING - body_line_height * footer.len() as u32;
+    for line in &footer {
+        draw_text(&mut card, line, CARD_PADDING, footer_y, BODY_SCALE, ACCENT_COLOR);
+        footer_y += body_line_height;
+    }
+
+    card.save(path)
+        .with_context(|| format!("Failed to write summary image to {}", path.display()))?;
+    Ok(())
+}
+
+/// Render dark text on a white background, e.g. as an OCR test image
+pub fn text_image(text: &str, scale: u32, padding: u32) -> RgbImage {
+    let width = text.chars().count() as u32 * GLYPH_SIZE * scale + 2 * padding;
+    let height = GLYPH_SIZE * scale + 2 * padding;
+    let mut image = RgbImage::from_pixel(width, height, Rgb([255, 255, 255]));
+    draw_text(&mut image, text, padding, padding, scale, Rgb([0, 0, 0]));
+    image
+}
+
+/// Overlay a labelled coordinate grid on a screenshot
+///
+/// Lines are drawn every `spacing` screen units and labelled with their
+/// coordinate along the top and left edges, so a region can be read off
+/// the image. `scale` is the number of image pixels per screen unit.
+pub fn coordinate_grid(screenshot: &RgbImage, spacing: u32, scale: f32) -> RgbImage {
+    let mut grid = screenshot.clone();
+    let step = (spacing as f32 * scale).max(1.0);
+    let (width, height) = grid.dimensions();
+
+    // Line number and pixel offset of each grid line before `limit`
+    let lines = |limit: u32| {
+        (1..)
+            .map(move |index| (index, (index as f32 * step) as u32))
+            .take_while(move |(_, pixel)| *pixel < limit)
+    };
+    for (_, x) in lines(width) {
+        for y in 0..height {
+            grid.put_pixel(x, y, ACCENT_COLOR);
+        }
+    }
+    for (_, y) in lines(height) {
+        for x in 0..width {
+            grid.put_pixel(x, y, ACCENT_COLOR);
+        }
+    }
+
+    // Labels on a dark box so they stay readable over the game
+    let mut label = |text: String, x: u32, y: u32| {
+        let box_width = text.chars().count() as u32 * GLYPH_SIZE * BODY_SCALE + 2 * LINE_SPACING;
+        let box_height = GLYPH_SIZE * BODY_SCALE + 2 * LINE_SPACING;
+        for dy in 0..box_height {
+            for dx in 0..box_width {
+                if x + dx < width && y + dy < height {
+                    grid.put_pixel(x + dx, y + dy, BACKGROUND_COLOR);
+                }
+            }
+        }
+        draw_text(&mut grid, &text, x + LINE_SPACING, y + LINE_SPACING, BODY_SCALE, TEXT_COLOR);
+    };
+    for (index, x) in lines(width) {
+        label((index * spacing).to_string(), x + 1, 0);
+    }
+    for (index, y) in lines(height) {
+        label((index * spacing).to_string(), 0, y + 1);
+    }
+    grid
+}
+
+/// Draw a string using the embedded 8x8 bitmap font
+///
+/// Characters without a glyph are skipped but still advance the cursor,
+/// and anything falling outside the card is clipped.
+fn draw_text(card: &mut RgbImage, text: &str, x: u32, y: u32, scale: u32, color: Rgb<u8>) {
+    let advance = GLYPH_SIZE * scale;
+
+    for (index, ch) in text.chars().enumerate() {
+        let Some(glyph) = BASIC_FONTS.get(ch) else {
+            continue;
+        };
+        let origin_x = x + index as u32 * advance;
+
+        for (row, bits) in glyph.iter().enumerate() {
+            for col in 0..GLYPH_SIZE {
+                if bits & (1 << col) == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let px = origin_x + col * scale + dx;
+                        let py = y + row as u32 * scale + dy;
+                        if px < card.width() && py < card.height() {
+                            card.put_pixel(px, py, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
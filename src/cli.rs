@@ -0,0 +1,150 @@
+use anyhow::{bail, Context, Result};
+use clap::{Args, Parser};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::config::{Config, Region};
+
+/// Command line arguments
+#[derive(Debug, Parser)]
+#[command(name = "protean", version, about)]
+pub struct CliArgs {
+    /// Load the config from FILE instead of the default location
+    #[arg(long = "config", value_name = "FILE")]
+    pub config_path: Option<PathBuf>,
+    /// Config values set on the command line, taking precedence over the file
+    #[command(flatten)]
+    pub overrides: ConfigOverrides,
+    /// Find the capture region by searching the screen for the pattern
+    #[arg(long, conflicts_with = "region")]
+    pub autodetect_region: bool,
+    /// Drag out the capture region on screen (or pick it from a screenshot)
+    #[arg(long, conflicts_with_all = ["region", "autodetect_region"])]
+    pub select_region: bool,
+    /// Start monitoring immediately (skip the startup delay)
+    #[arg(long)]
+    pub no_delay: bool,
+    /// Measure OCR consistency with/without preprocessing and exit
+    #[arg(long)]
+    pub calibrate: bool,
+    /// Accept pause/resume/stats/normalize/area/quit on a Unix socket
+    #[arg(long, value_name = "PATH")]
+    pub control_socket: Option<PathBuf>,
+    /// Run headless: log to a file, write JSON snapshots, stop on SIGTERM
+    #[arg(long)]
+    pub daemon: bool,
+    /// Show a live full-screen dashboard instead of scrolling output
+    #[arg(long, conflicts_with = "daemon")]
+    pub tui: bool,
+    /// Start even if another instance appears to be running
+    #[arg(long)]
+    pub force: bool,
+    /// Start from zero instead of resuming the unfinished session
+    #[arg(long)]
+    pub new_session: bool,
+    /// Print all-time statistics across sessions and exit
+    #[arg(long)]
+    pub lifetime: bool,
+    /// Print each screen's index, resolution and position and exit
+    #[arg(long)]
+    pub list_monitors: bool,
+    /// Delete the saved session and snapshot (asks first unless -y) and exit
+    #[arg(long)]
+    pub reset: bool,
+    /// With --reset, also delete the all-time statistics
+    #[arg(long, requires = "reset")]
+    pub reset_lifetime: bool,
+    /// Run detection on scripted OCR text (one frame per line)
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+    /// Print the OCR text and latency of a saved image (e.g. a debug frame) and exit
+    #[arg(long, value_name = "FILE")]
+    pub ocr_image: Option<PathBuf>,
+    /// Re-OCR a recorded session and report frames and counts that differ
+    #[arg(long, value_name = "DIR")]
+    pub verify_session: Option<PathBuf>,
+    /// Print a binary encounter log as CSV and exit
+    #[arg(long, value_name = "FILE")]
+    pub dump_log: Option<PathBuf>,
+    /// With --dump-log, print encounters per SECS bucket (e.g. 60)
+    #[arg(long = "timeline", value_name = "SECS", requires = "dump_log",
+          value_parser = clap::value_parser!(u64).range(1..))]
+    pub timeline_secs: Option<u64>,
+    /// Write stop words, confusables and species list to a file and exit
+    #[arg(long, value_name = "FILE")]
+    pub export_rules: Option<PathBuf>,
+    /// Replace those rules with the ones in a file, save and exit
+    #[arg(long, value_name = "FILE",
+          conflicts_with_all = ["region", "refresh_ms", "empty_threshold", "no_window_detection"])]
+    pub import_rules: Option<PathBuf>,
+    /// Print extra diagnostic output
+    #[arg(short, long)]
+    pub verbose: bool,
+    /// Skip confirmation prompts (quitting saves to lifetime stats)
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+/// Config values given on the command line
+/// 
+/// These apply to this run only; they're never written back to the config file.
+#[derive(Debug, Default, Args)]
+pub struct ConfigOverrides {
+    /// Capture this region instead of the configured one
+    #[arg(long, value_name = "X,Y,W,H", value_parser = parse_region)]
+    pub region: Option<Region>,
+    /// Capture every MS milliseconds instead of the configured rate
+    #[arg(long, value_name = "MS")]
+    pub refresh_ms: Option<u64>,
+    /// Frames without a name before a battle counts as over
+    #[arg(long, value_name = "N")]
+    pub empty_threshold: Option<u32>,
+    /// Capture the configured region without locating the game window
+    #[arg(long)]
+    pub no_window_detection: bool,
+}
+
+impl ConfigOverrides {
+    /// Overwrite the matching config values and check the result is still valid
+    pub fn apply(&self, config: &mut Config) -> Result<()> {
+        if let Some(region) = self.region {
+            config.region = region;
+        }
+        if let Some(refresh_ms) = self.refresh_ms {
+            config.refresh_rate = Duration::from_millis(refresh_ms);
+        }
+        if let Some(empty_threshold) = self.empty_threshold {
+            config.empty_threshold = empty_threshold;
+        }
+        if self.no_window_detection {
+            config.window_detection = false;
+        }
+        config.validate().context("Invalid command line override")?;
+        if !self.is_empty() {
+            println!("✓ Command line overrides applied");
+        }
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.region.is_none()
+            && self.refresh_ms.is_none()
+            && self.empty_threshold.is_none()
+            && !self.no_window_detection
+    }
+}
+
+/// Parse a `--region` value of the form `x,y,width,height`
+fn parse_region(value: &str) -> Result<Region> {
+    let invalid = || format!("Invalid --region {} (expected x,y,width,height)", value);
+    let parts: Vec<&str> = value.split(',').map(str::trim).collect();
+    let [x, y, width, height] = parts[..] else {
+        bail!(invalid());
+    };
+    Ok(Region {
+        x: x.parse().with_context(invalid)?,
+        y: y.parse().with_context(invalid)?,
+        width: width.parse().with_context(invalid)?,
+        height: height.parse().with_context(invalid)?,
+    })
+}
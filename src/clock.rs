@@ -0,0 +1,47 @@
+use std::time::Instant;
+
+/// Source of the current time, so time-based accounting can be driven by
+/// something other than the wall clock
+pub trait Clock {
+    fn now(&self) -> Instant;
+
+    /// Time elapsed since `earlier`, or zero if `earlier` is in the future
+    fn since(&self, earlier: Instant) -> std::time::Duration {
+        self.now().saturating_duration_since(earlier)
+    }
+}
+
+/// The system's monotonic clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Clock that only moves when told to, for testing time-based accounting
+#[cfg(test)]
+pub struct MockClock {
+    now: std::cell::Cell<Instant>,
+}
+
+#[cfg(test)]
+impl MockClock {
+    pub fn new() -> Self {
+        Self { now: std::cell::Cell::new(Instant::now()) }
+    }
+
+    /// Move the clock forward by `duration`
+    pub fn advance(&self, duration: std::time::Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+}
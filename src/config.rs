@@ -1,29 +1,130 @@
-use anyhow::{Context, Result};
-use serde::{Deserialize, Serialize};
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+use crate::error::ProteanError;
+use crate::hotkeys::parse_hotkey;
+use crate::ocr::{ConfidenceFilter, PreprocessOptions, DEFAULT_WORD_SEPARATOR};
+
 /// Configuration presets for the default PROClient window
 pub const PRESET_X: i32 = 2575;
 pub const PRESET_Y: i32 = 70;
 pub const PRESET_WIDTH: u32 = 870;
 pub const PRESET_HEIGHT: u32 = 55;
 pub const PRESET_REFRESH_MS: u64 = 500;
+/// How often a paused monitor wakes to check for input and window focus
+pub const PRESET_PAUSE_POLL_MS: u64 = 100;
 pub const PRESET_EMPTY_THRESHOLD: u32 = 2;
+/// Identical reads of a detected name needed before its battle is active
+pub const PRESET_ACTIVE_CONFIRM_READS: u32 = 2;
+/// Seconds a flickering battle end may last before it's counted anyway (0 disables)
+pub const PRESET_STUCK_ENDING_SECS: u64 = 5;
+/// Frames sampled by the calibration command
+pub const PRESET_CALIBRATION_SAMPLES: usize = 20;
+/// Longest text still considered blank by the blank-text empty detection
+pub const PRESET_BLANK_TEXT_MAX_LENGTH: usize = 3;
+/// Reads shorter than this are treated as noise before pattern matching (0 disables)
+pub const PRESET_MIN_DETECTION_LENGTH: usize = 0;
+/// Shortest unmatched read worth logging as ignored text
+pub const PRESET_MIN_LOG_LENGTH: usize = 10;
 pub const PRESET_WINDOW_DETECTION: bool = true;
+/// Ask before quitting whether to export the session and record it in the lifetime stats
+pub const PRESET_CONFIRM_QUIT: bool = true;
+/// How long the game may stay unfocused before auto-pausing (0 pauses immediately)
+pub const PRESET_WINDOW_PAUSE_GRACE_MS: u64 = 0;
+/// Wait before re-reading a newly detected banner (0 keeps the first read)
+pub const PRESET_DETECTION_SETTLE_MS: u64 = 0;
+/// Longest a region change may precede the first read of a name and still
+/// be taken as the banner's arrival
+pub const PRESET_INTRO_MAX_LEAD_MS: u64 = 1500;
 pub const PRESET_PREPROCESS_IMAGES: bool = false;
-/// The window class to monitor when window detection is enabled
+/// Retry a frame on the raw image when OCR of its preprocessed version fails
+pub const PRESET_PREPROCESS_FALLBACK: bool = true;
+/// Contrast stretch clipping percentiles (0/100 uses the absolute min/max)
+pub const PRESET_CLIP_LOW_PERCENTILE: f32 = 0.0;
+pub const PRESET_CLIP_HIGH_PERCENTILE: f32 = 100.0;
+/// Captures are read at their own size (1.0 leaves them untouched)
+pub const PRESET_UPSCALE_FACTOR: f32 = 1.0;
+/// Largest upscale factor accepted; beyond it OCR time explodes for little gain
+pub const MAX_UPSCALE_FACTOR: f32 = 4.0;
+/// Seconds to wait before monitoring starts (time to focus the game window)
+pub const PRESET_STARTUP_DELAY_SECS: u64 = 3;
+/// Minimum time between two notifications for the same species
+pub const PRESET_NOTIFY_COOLDOWN_SECS: u64 = 10;
+/// Number of recent frames kept in memory for anomaly dumps (0 disables)
+pub const PRESET_ANOMALY_FRAME_HISTORY: usize = 8;
+/// Seconds a battle may spend ending without being counted before frames are dumped
+pub const PRESET_ANOMALY_TIMEOUT_SECS: u64 = 10;
+/// Weight of the newest interval in the smoothed encounter rate
+pub const PRESET_RATE_SMOOTHING: f64 = 0.2;
+/// Most decimal places `duration_decimals` can add to durations (milliseconds)
+pub const MAX_DURATION_DECIMALS: u32 = 3;
+/// Length of the "recent" column in statistics (0 disables)
+pub const PRESET_RECENT_WINDOW_MINS: u64 = 60;
+/// Seconds between JSON snapshots written in daemon mode
+pub const PRESET_SNAPSHOT_INTERVAL_SECS: u64 = 30;
+/// Pick up an unfinished session's counts and time on startup
+pub const PRESET_RESUME_SESSION: bool = true;
+/// Seconds between saves of the running session
+pub const PRESET_SESSION_SAVE_INTERVAL_SECS: u64 = 300;
+/// Approximate battle log location in the default PROClient layout
+pub const PRESET_BATTLE_LOG_X: i32 = 2575;
+pub const PRESET_BATTLE_LOG_Y: i32 = 1240;
+pub const PRESET_BATTLE_LOG_WIDTH: u32 = 870;
+pub const PRESET_BATTLE_LOG_HEIGHT: u32 = 60;
+pub const PRESET_BATTLE_LOG_PATTERN: &str = "A wild {name} appeared";
+pub const PRESET_BATTLE_LOG_END_PHRASES: &[&str] = &["fled", "fainted", "got away", "caught"];
+pub const PRESET_OUTCOME_FLED_PHRASES: &[&str] = &["got away", "fled"];
+pub const PRESET_OUTCOME_CAUGHT_PHRASES: &[&str] = &["caught", "gotcha"];
+pub const PRESET_OUTCOME_DEFEATED_PHRASES: &[&str] = &["fainted"];
+/// Glyph sequences OCR commonly confuses, mapped to the form compared when normalizing
+pub const PRESET_CONFUSABLES: &[(&str, &str)] = &[
+    ("1", "l"),
+    ("I", "l"),
+    ("|", "l"),
+    ("0", "o"),
+    ("5", "s"),
+    ("q", "g"),
+    ("rn", "m"),
+    ("vv", "w"),
+];
+/// Default window class to monitor when window detection is enabled
 pub const TARGET_WINDOW_CLASS: &str = "PROClient.x86_64";
-/// Default minimum OCR confidence threshold (currently unused)
+/// Default minimum OCR confidence of the reads kept
 pub const MIN_OCR_CONFIDENCE: f32 = 0.5;
 
+/// Name of the implicit target when no explicit targets are configured
+const DEFAULT_TARGET_NAME: &str = "main";
+
 const CONFIG_DIR_NAME: &str = "protean";
-const CONFIG_FILE_NAME: &str = "settings.toml";
+/// Config files looked for, in order; the first is created when none exists
+const CONFIG_FILE_NAMES: &[&str] = &["settings.toml", "settings.yaml", "settings.yml", "settings.json"];
+const DEBUG_DIR_NAME: &str = "debug";
+const LIFETIME_FILE_NAME: &str = "lifetime.json";
+const WRITE_PROBE_FILE_NAME: &str = ".write-test";
+/// Where to offer saving the config when the config folder isn't writable
+const FALLBACK_CONFIG_PATH: &str = "settings.toml";
+const SNAPSHOT_FILE_NAME: &str = "snapshot.json";
+/// Counts and time of the running session, kept until it's recorded in the lifetime stats
+const SESSION_FILE_NAME: &str = "session.json";
+const DAEMON_LOG_FILE_NAME: &str = "daemon.log";
+const TUI_LOG_FILE_NAME: &str = "tui.log";
+/// Species list written when a rule set is imported
+const IMPORTED_SPECIES_FILE_NAME: &str = "species.txt";
+/// Held by the running instance so others (and `--reset`) can tell
+const LOCK_FILE_NAME: &str = "protean.lock";
+
+/// Capture group a name regex must define
+const NAME_CAPTURE_GROUP: &str = "name";
 
 /// Structure to hold the selected region coordinates
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Region {
     pub x: i32,
     pub y: i32,
@@ -41,26 +142,724 @@ impl Region {
             height: PRESET_HEIGHT,
         }
     }
+
+    /// Create a region with preset coordinates for the PROClient battle log
+    pub fn battle_log_preset() -> Self {
+        Self {
+            x: PRESET_BATTLE_LOG_X,
+            y: PRESET_BATTLE_LOG_Y,
+            width: PRESET_BATTLE_LOG_WIDTH,
+            height: PRESET_BATTLE_LOG_HEIGHT,
+        }
+    }
+}
+
+/// Which on-screen text is used to detect encounters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectionStrategy {
+    /// The "VS. Wild [Pokemon]" banner at the top of the battle screen
+    #[default]
+    Banner,
+    /// The scrolling battle message ("A wild [Pokemon] appeared!")
+    BattleLog,
+}
+
+/// How screen regions are captured
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureBackend {
+    /// The built-in capture (falls back to `capture_command` when set)
+    #[default]
+    Screenshots,
+    /// Always run `capture_command`
+    Command,
+}
+
+/// How the focused window is looked up for window detection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowBackend {
+    /// Hyprland when it's running, X11 when `XDG_SESSION_TYPE` is x11
+    #[default]
+    Auto,
+    /// `hyprctl`
+    Hyprland,
+    /// `xdotool` (needs an EWMH window manager)
+    X11,
+}
+
+/// Which OCR engine reads the captured regions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OcrBackend {
+    /// The built-in ocrs engine (needs the rten models)
+    #[default]
+    Ocrs,
+    /// The `tesseract` command line program, which must be installed
+    Tesseract,
+}
+
+/// File format of a config (or rule set) file, chosen by its extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Ok(ConfigFormat::Toml),
+            Some("yaml" | "yml") => Ok(ConfigFormat::Yaml),
+            Some("json") => Ok(ConfigFormat::Json),
+            _ => bail!("Unrecognized config file extension in {} (expected .toml, .yaml, .yml or .json)",
+                       path.display()),
+        }
+    }
+
+    pub fn parse<T: DeserializeOwned>(self, contents: &str) -> Result<T> {
+        Ok(match self {
+            ConfigFormat::Toml => toml::from_str(contents)?,
+            ConfigFormat::Yaml => serde_yaml::from_str(contents)?,
+            ConfigFormat::Json => serde_json::from_str(contents)?,
+        })
+    }
+
+    pub fn serialize<T: Serialize>(self, value: &T) -> Result<String> {
+        Ok(match self {
+            ConfigFormat::Toml => toml::to_string_pretty(value)?,
+            ConfigFormat::Yaml => serde_yaml::to_string(value)?,
+            ConfigFormat::Json => serde_json::to_string_pretty(value)?,
+        })
+    }
+}
+
+/// Where in a recognized line the encounter pattern may appear
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternAnchor {
+    /// Anywhere in the text, across line breaks
+    #[default]
+    Anywhere,
+    /// Only at the start of a line, so UI text bleeding into the region
+    /// mid-line can't produce a match
+    LineStart,
+}
+
+/// A destination counted encounters are reported to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkConfig {
+    /// Print each count to the terminal
+    Stdout,
+    /// Append one JSON line per count to a file
+    File { path: PathBuf },
+    /// POST a JSON payload per count to a URL (e.g. a Discord webhook)
+    Webhook {
+        url: String,
+        /// Only post counts of these species (case-insensitive); every count
+        /// is posted when empty
+        #[serde(default)]
+        names: Vec<String>,
+    },
+    /// Append to a compact binary encounter log (dump with `--dump-log`)
+    BinaryLog { path: PathBuf },
+    /// Keep a small file holding a single number, e.g. for an OBS text source
+    Overlay {
+        path: PathBuf,
+        metric: OverlayMetric,
+        #[serde(default)]
+        format: OverlayFormat,
+    },
+}
+
+/// The number an overlay sink shows
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayMetric {
+    /// Encounters of every species
+    Total,
+    /// Encounters of one species, e.g. `metric = { species = "Pidgey" }`
+    Species(String),
+    /// Encounters since the last target species (all encounters without targets)
+    DryStreak,
+}
+
+/// How an overlay sink writes its number
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayFormat {
+    /// Just the number
+    #[default]
+    Text,
+    /// `{"metric": ..., "value": N}`
+    Json,
+}
+
+/// What counts as an "empty" frame when waiting for a battle to end
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmptyDetection {
+    /// Any frame where the pattern isn't matched
+    #[default]
+    MissingPattern,
+    /// Only frames whose text is blank (or nearly), so a briefly garbled
+    /// name on a banner that's still up doesn't end the battle early
+    BlankText,
+}
+
+impl EmptyDetection {
+    /// Whether a frame without a matched pattern counts towards ending the battle
+    pub fn is_empty(&self, text: &str, blank_text_max_length: usize) -> bool {
+        match self {
+            EmptyDetection::MissingPattern => true,
+            EmptyDetection::BlankText => text.trim().chars().count() <= blank_text_max_length,
+        }
+    }
+}
+
+/// Regex extracting the pokemon name through a `(?P<name>...)` capture group
+/// 
+/// Compiled and checked once when the config is loaded; stored in the
+/// config file as the plain pattern string.
+#[derive(Debug, Clone)]
+pub struct NameRegex(Regex);
+
+impl NameRegex {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let regex = Regex::new(pattern)
+            .with_context(|| format!("Invalid name_regex \"{}\"", pattern))?;
+        if !regex.capture_names().flatten().any(|name| name == NAME_CAPTURE_GROUP) {
+            bail!("name_regex \"{}\" must contain a (?P<{}>...) capture group", pattern, NAME_CAPTURE_GROUP);
+        }
+        Ok(Self(regex))
+    }
+
+    /// The regex source as written in the config
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Return the trimmed `name` group of the first match, if non-empty
+    pub fn extract(&self, text: &str) -> Option<String> {
+        let captures = self.0.captures(text)?;
+        let name = captures.name(NAME_CAPTURE_GROUP)?.as_str().trim();
+        (!name.is_empty()).then(|| name.to_string())
+    }
+}
+
+impl Serialize for NameRegex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for NameRegex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        NameRegex::new(&pattern).map_err(|e| serde::de::Error::custom(format!("{:#}", e)))
+    }
+}
+
+/// Keyboard actions available while monitoring
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Pause,
+    Restart,
+    ShowStats,
+    Normalize,
+    ToggleView,
+    ExportImage,
+    ExportStats,
+    CycleArea,
+    ToggleWindowDetection,
+    HeatMap,
+    ManualEntry,
+    Preview,
+    Help,
+    Quit,
+}
+
+impl Action {
+    /// All actions, in the order they're listed in the help menu
+    pub const ALL: [Action; 14] = [
+        Action::Pause,
+        Action::Restart,
+        Action::ShowStats,
+        Action::Normalize,
+        Action::ToggleView,
+        Action::ExportImage,
+        Action::ExportStats,
+        Action::CycleArea,
+        Action::ToggleWindowDetection,
+        Action::HeatMap,
+        Action::ManualEntry,
+        Action::Preview,
+        Action::Help,
+        Action::Quit,
+    ];
+
+    /// Key used when the action isn't remapped in the config
+    pub fn default_key(self) -> char {
+        match self {
+            Action::Pause => 'p',
+            Action::Restart => 'r',
+            Action::ShowStats => 's',
+            Action::Normalize => 'n',
+            Action::ToggleView => 'v',
+            Action::ExportImage => 'i',
+            Action::ExportStats => 'e',
+            Action::CycleArea => 'a',
+            Action::ToggleWindowDetection => 'w',
+            Action::HeatMap => 'h',
+            Action::ManualEntry => 'm',
+            Action::Preview => 'c',
+            Action::Help => '?',
+            Action::Quit => 'q',
+        }
+    }
+
+    /// Help menu description
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Pause => "Pause/Resume monitoring",
+            Action::Restart => "Restart (clear all statistics)",
+            Action::ShowStats => "Show current statistics",
+            Action::Normalize => "Normalize Pokemon names (merge superstrings)",
+            Action::ToggleView => "Toggle raw/normalized names in statistics",
+            Action::ExportImage => "Export summary image (PNG card)",
+            Action::ExportStats => "Export the count table to CSV or JSON",
+            Action::CycleArea => "Cycle the area new encounters are tagged with",
+            Action::ToggleWindowDetection => "Toggle auto-pause when the game window loses focus",
+            Action::HeatMap => "Save an OCR confidence heat map of the next frame",
+            Action::ManualEntry => "Count a Pokemon by name (when OCR can't read it)",
+            Action::Preview => "Toggle a live preview of the captured region",
+            Action::Help => "Show this help menu",
+            Action::Quit => "Quit and show final statistics",
+        }
+    }
+}
+
+/// Which way round text and background are in the captured region
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextPolarity {
+    /// Assume the text is the minority of pixels after thresholding
+    #[default]
+    Auto,
+    /// Dark text on a light background (no inversion needed)
+    DarkOnLight,
+    /// Light text on a dark background (inverted before OCR)
+    LightOnDark,
+}
+
+/// How preprocessing separates text from background
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThresholdMode {
+    /// One threshold for the whole image, chosen with Otsu's method
+    #[default]
+    Otsu,
+    /// A threshold per pixel from the mean of the surrounding window, for
+    /// banners with a gradient background, e.g.
+    /// `threshold_mode = { adaptive = { block_size = 15, c = 5 } }`
+    Adaptive {
+        /// Side of the square window in pixels (odd, at least 3)
+        block_size: u32,
+        /// Subtracted from the window mean; higher values keep fewer
+        /// pixels as text
+        c: i32,
+    },
+}
+
+/// How banners around headings like the final statistics are drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputStyle {
+    /// Double-line Unicode box drawing
+    #[default]
+    Unicode,
+    /// `+---+` borders for terminals and fonts without box drawing
+    Ascii,
+    /// The heading alone, for logs and pipes
+    Plain,
+}
+
+/// Which reads `min_ocr_confidence` removes from OCR output
+/// 
+/// Dropping single words can break a name apart (e.g. "Mr. Mime" losing
+/// "Mr."), while dropping whole lines throws away a good name when only a
+/// neighboring word read poorly. For a one-line banner such as
+/// "VS. Wild X", per line is usually the better choice. "off", or a
+/// `min_ocr_confidence` of 0.0, keeps everything.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfidenceFloor {
+    /// Keep everything that was read
+    Off,
+    /// Drop each word whose confidence is below the minimum
+    PerWord,
+    /// Drop each line whose average word confidence is below the minimum
+    #[default]
+    PerLine,
+}
+
+/// An independently monitored game client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorTarget {
+    /// Label shown in logs and statistics
+    pub name: String,
+    /// Screen region to capture for this client (banner or battle log,
+    /// depending on the detection strategy)
+    pub region: Region,
+    /// Further regions read each frame, their text appended to the text of
+    /// `region` in this order (e.g. a banner and an encounter list)
+    #[serde(default)]
+    pub extra_regions: Vec<Region>,
+    /// Window class of this client, used for window detection
+    #[serde(default = "default_window_class")]
+    pub window_class: String,
+    /// Region showing this client's outcome messages (requires outcome tracking)
+    #[serde(default)]
+    pub outcome_region: Option<Region>,
+}
+
+/// Battle outcome tracking configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutcomeConfig {
+    /// Screen region showing the outcome message (usually the battle log)
+    pub region: Region,
+    /// Phrases indicating the wild pokemon fled or the player ran
+    #[serde(default = "default_outcome_fled_phrases")]
+    pub fled_phrases: Vec<String>,
+    /// Phrases indicating the pokemon was caught
+    #[serde(default = "default_outcome_caught_phrases")]
+    pub caught_phrases: Vec<String>,
+    /// Phrases indicating the wild pokemon fainted
+    #[serde(default = "default_outcome_defeated_phrases")]
+    pub defeated_phrases: Vec<String>,
 }
 
 /// Application configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// File the config was loaded from (`--config`), which saves write back to
+    #[serde(skip)]
+    pub source_path: Option<PathBuf>,
     /// Screen region to capture for OCR
     pub region: Region,
+    /// Further regions read each frame, their text appended to the text of
+    /// the capture region in this order; used when no targets are configured
+    #[serde(default)]
+    pub extra_regions: Vec<Region>,
     /// How frequently to capture and process OCR
     #[serde(with = "duration_ms")]
     pub refresh_rate: Duration,
+    /// Whether regions are captured by the built-in capture or `capture_command`
+    #[serde(default)]
+    pub capture_backend: CaptureBackend,
+    /// Screen the regions are captured from, as numbered by `--list-monitors`
+    #[serde(default)]
+    pub monitor_index: usize,
+    /// Whether text is read by the built-in ocrs engine or `tesseract`
+    /// ("ocrs" or "tesseract"); confidence filtering and heat maps need ocrs
+    #[serde(default)]
+    pub ocr_backend: OcrBackend,
+    /// Screenshot command for setups the built-in capture doesn't support
+    /// (some Wayland compositors, remote sessions), e.g.
+    /// `grim -g "{x},{y} {width}x{height}" {output}`. Without `{output}`
+    /// the image is read from the command's stdout.
+    #[serde(default)]
+    pub capture_command: Option<String>,
+    /// How often to check for input and window focus while paused; longer
+    /// intervals use less CPU but make resuming less responsive
+    #[serde(with = "duration_ms", default = "default_pause_poll_interval")]
+    pub pause_poll_interval: Duration,
     /// Number of empty frames required to confirm battle end
     pub empty_threshold: u32,
+    /// Consecutive identical reads of a detected name (including the first)
+    /// before the battle is confirmed active; a different read in between
+    /// restarts the detection with that name
+    #[serde(default = "default_active_confirm_reads")]
+    pub active_confirm_reads: u32,
+    /// Seconds a battle may keep flickering between empty frames and a stale
+    /// read of its name before it's counted anyway (0 disables)
+    #[serde(default = "default_stuck_ending_secs")]
+    pub stuck_ending_secs: u64,
+    /// Whether a frame is empty when the pattern is missing or only when the text is blank
+    #[serde(default)]
+    pub empty_detection: EmptyDetection,
+    /// Maximum text length treated as blank in `blank_text` empty detection
+    #[serde(default = "default_blank_text_max_length")]
+    pub blank_text_max_length: usize,
+    /// Reads with fewer characters are discarded as noise, as if nothing was read
+    #[serde(default = "default_min_detection_length")]
+    pub min_detection_length: usize,
+    /// Unmatched reads with fewer characters aren't logged as ignored
+    #[serde(default = "default_min_log_length")]
+    pub min_log_length: usize,
+    /// Milliseconds to wait after a new detection before re-capturing and
+    /// re-reading the name, so a banner read mid-animation settles first
+    #[serde(default = "default_detection_settle_ms")]
+    pub detection_settle_ms: u64,
+    /// Mean pixel change between consecutive idle frames (0-1) taken as the
+    /// banner arriving; battle times then start there instead of at the
+    /// first successful read. Unset disables the heuristic
+    #[serde(default)]
+    pub intro_change_threshold: Option<f32>,
+    /// Longest a detected change may precede the first read of the name
+    /// (older changes are ignored, e.g. movement on the overworld)
+    #[serde(default = "default_intro_max_lead_ms")]
+    pub intro_max_lead_ms: u64,
+    /// Mean pixel change (0-1) since the last frame OCR read below which a
+    /// frame isn't read again and keeps that frame's text, saving CPU on
+    /// static screens. Unset reads every frame
+    #[serde(default)]
+    pub ocr_change_threshold: Option<f32>,
     /// Whether to auto-pause when target window loses focus
     pub window_detection: bool,
-    /// Minimum OCR confidence threshold (reserved for future use)
+    /// How the focused window is found: "auto", "hyprland" or "x11"
+    #[serde(default)]
+    pub window_backend: WindowBackend,
+    /// Window class of the game client (used when no targets are configured;
+    /// targets set their own), for launchers or renamed clients
+    #[serde(default = "default_window_class", alias = "window_class")]
+    pub target_window_class: String,
+    /// Ask on quit whether to export the session and add it to the lifetime
+    /// stats (false quits immediately and always adds it)
+    #[serde(default = "default_confirm_quit")]
+    pub confirm_quit: bool,
+    /// Milliseconds the game may stay unfocused before auto-pausing, so quick
+    /// alt-tabs don't pause and resume
+    #[serde(default = "default_window_pause_grace_ms")]
+    pub window_pause_grace_ms: u64,
+    /// Minimum OCR confidence (0-1) of the reads kept, applied per line unless
+    /// `confidence_floor` says otherwise; confidence is the mean probability
+    /// the text detector gave the text pixels under a word. 0.0 disables
+    /// filtering
     #[serde(default = "default_min_confidence")]
     pub min_ocr_confidence: f32,
+    /// Whether `min_ocr_confidence` drops words or whole lines ("off",
+    /// "per_word" or "per_line"); filtering reuses the frame's detection pass,
+    /// so its per-frame cost is one scan of each word's box, and it is skipped
+    /// in single line mode
+    #[serde(default)]
+    pub confidence_floor: ConfidenceFloor,
     /// Whether to apply image preprocessing before OCR
     #[serde(default = "default_preprocess_images")]
     pub preprocess_images: bool,
+    /// When OCR of a preprocessed frame fails, retry it on the raw frame
+    /// (warning once) instead of dropping it; false makes such frames errors
+    #[serde(default = "default_preprocess_fallback")]
+    pub preprocess_fallback: bool,
+    /// Percentile of darkest pixels ignored when stretching contrast (e.g. 2.0)
+    #[serde(default = "default_clip_low_percentile")]
+    pub clip_low_percentile: f32,
+    /// Percentile above which bright pixels are ignored when stretching contrast (e.g. 98.0)
+    #[serde(default = "default_clip_high_percentile")]
+    pub clip_high_percentile: f32,
+    /// Enlarge captures by this factor (Lanczos) before OCR, which helps
+    /// small text like the 55 pixel high preset region. OCR time grows with
+    /// the pixel count, so 2.0 makes each read roughly four times slower;
+    /// the slow OCR warning shows when the refresh rate can't keep up
+    #[serde(default = "default_upscale_factor")]
+    pub upscale_factor: f32,
+    /// Text polarity used to make preprocessed images dark-on-light
+    #[serde(default)]
+    pub text_polarity: TextPolarity,
+    /// Global ("otsu") or local ("adaptive") thresholding in preprocessing
+    #[serde(default)]
+    pub threshold_mode: ThresholdMode,
+    /// Placed between the words OCR recognizes on a line; "" keeps names the
+    /// model splits into several words (e.g. "Pid gey") in one piece
+    #[serde(default = "default_word_separator")]
+    pub word_separator: String,
+    /// Merge adjacent words whose concatenation is a known species ("Pid gey"
+    /// becomes "Pidgey") before detection
+    #[serde(default)]
+    pub rejoin_split_names: bool,
+    /// Snap detected names that aren't in the species list to the closest
+    /// listed name at most this many character edits away ("Pidqey" becomes
+    /// "Pidgey"); names further from every species are kept as read, and
+    /// unset turns snapping off
+    #[serde(default)]
+    pub snap_max_distance: Option<usize>,
+    /// Skip word detection and recognize the whole region as one line of
+    /// text; faster for a tight region around a single-line banner, but
+    /// multi-line or loosely cropped regions read poorly
+    #[serde(default)]
+    pub single_line_mode: bool,
+    /// Whether to detect encounters from the banner or the battle log
+    #[serde(default)]
+    pub detection_strategy: DetectionStrategy,
+    /// Whether the pattern must begin a recognized line (a `name_regex` is
+    /// then matched line by line, so `^` anchors it to the line start)
+    #[serde(default)]
+    pub pattern_anchor: PatternAnchor,
+    /// Screen region of the battle log (used by the battle log strategy)
+    #[serde(default = "Region::battle_log_preset")]
+    pub battle_log_region: Region,
+    /// Battle log message announcing an encounter, with a `{name}` placeholder
+    #[serde(default = "default_battle_log_pattern")]
+    pub battle_log_pattern: String,
+    /// Battle log phrases that end the battle (fled, fainted, ...)
+    #[serde(default = "default_battle_log_end_phrases")]
+    pub battle_log_end_phrases: Vec<String>,
+    /// Regex with a `(?P<name>...)` group used instead of the built-in
+    /// pattern matching, for banner formats the presets don't cover
+    #[serde(default)]
+    pub name_regex: Option<NameRegex>,
+    /// Battle outcome tracking (disabled when not configured)
+    #[serde(default)]
+    pub outcome: Option<OutcomeConfig>,
+    /// Seconds to wait before monitoring starts, giving time to focus the game
+    #[serde(default = "default_startup_delay_secs")]
+    pub startup_delay_secs: u64,
+    /// Game clients to monitor simultaneously; when empty, the single
+    /// `region`/`battle_log_region` is monitored
+    #[serde(default)]
+    pub targets: Vec<MonitorTarget>,
+    /// Species that trigger an alert when encountered
+    #[serde(default)]
+    pub notify_targets: Vec<String>,
+    /// Where counted encounters are reported (terminal, file, webhook)
+    #[serde(default = "default_sinks")]
+    pub sinks: Vec<SinkConfig>,
+    /// Seconds before the same species can trigger another alert
+    #[serde(default = "default_notify_cooldown_secs")]
+    pub notify_cooldown_secs: u64,
+    /// Number of recent frames kept for debugging anomalies (0 disables)
+    #[serde(default = "default_anomaly_frame_history")]
+    pub anomaly_frame_history: usize,
+    /// Seconds a battle may stay in its ending phase without being counted
+    /// before the recent frames are dumped to the debug folder
+    #[serde(default = "default_anomaly_timeout_secs")]
+    pub anomaly_timeout_secs: u64,
+    /// Save every captured frame and its OCR text to the debug folder, so the
+    /// session can be checked afterwards with `--verify-session`
+    #[serde(default)]
+    pub record_session_frames: bool,
+    /// OCR frames containing any of these substrings are discarded before detection
+    #[serde(default)]
+    pub suppress_if_contains: Vec<String>,
+    /// Words never counted as a name, on top of the words of the pattern
+    /// itself (e.g. "VS" or "Wild" read when the name is cut off)
+    #[serde(default)]
+    pub name_stop_words: Vec<String>,
+    /// Area labels cycled with [A]; new encounters are tagged with the current one
+    #[serde(default)]
+    pub areas: Vec<String>,
+    /// Expected encounter rate in percent per species in the hunted area
+    /// (e.g. `Pidgey = 45.0`); statistics then compare observed rates to it
+    #[serde(default)]
+    pub spawn_table: BTreeMap<String, f64>,
+    /// Print extra diagnostic output (e.g. suppressed frames)
+    #[serde(default)]
+    pub verbose: bool,
+    /// Newline-delimited species list replacing the embedded one (fan games,
+    /// renamed forms); used to flag counted names that aren't known species
+    #[serde(default)]
+    pub species_list_path: Option<PathBuf>,
+    /// Key remaps by action (e.g. `quit = "x"`); unlisted actions keep their default key
+    #[serde(default)]
+    pub keybindings: BTreeMap<Action, char>,
+    /// Desktop-wide key combinations by action (e.g. `pause = "ctrl+alt+KeyP"`)
+    /// that work while the game has focus; the game no longer receives them
+    #[serde(default)]
+    pub global_hotkeys: BTreeMap<Action, String>,
+    /// Confusable glyph sequences collapsed before comparing names during
+    /// normalization (e.g. `"0" = "o"`, `"rn" = "m"`)
+    #[serde(default = "default_confusables")]
+    pub confusables: BTreeMap<String, String>,
+    /// Number of frames sampled by `--calibrate`
+    #[serde(default = "default_calibration_samples")]
+    pub calibration_samples: usize,
+    /// List only the N most common species, grouping the rest into an "Other" row
+    #[serde(default)]
+    pub stats_top_n: Option<usize>,
+    /// Read a trailing "Lv.N" from the banner and show a per-species level distribution
+    #[serde(default)]
+    pub track_levels: bool,
+    /// Text on the banner of shiny encounters (e.g. "SHINY"); when set,
+    /// statistics show shiny and normal counts per species
+    #[serde(default)]
+    pub shiny_pattern: Option<String>,
+    /// Ring the terminal bell on every count (target alerts ring twice)
+    #[serde(default)]
+    pub beep_on_count: bool,
+    /// Show the average battle time per species in statistics
+    #[serde(default)]
+    pub show_battle_times: bool,
+    /// Show a 95% confidence interval for each species' encounter rate
+    #[serde(default)]
+    pub show_confidence_intervals: bool,
+    /// Compare the whole session with its last N minutes in statistics,
+    /// once the session is longer than that (0 disables)
+    #[serde(default = "default_recent_window_mins")]
+    pub recent_window_mins: u64,
+    /// Decimal places of seconds shown in durations (0 keeps whole seconds,
+    /// up to 3 for milliseconds); extra precision is truncated, not rounded
+    #[serde(default)]
+    pub duration_decimals: u32,
+    /// Border style of banners: "unicode", "ascii" or "plain"
+    #[serde(default)]
+    pub output_style: OutputStyle,
+    /// Where daemon mode writes its JSON snapshot (defaults to the config folder)
+    #[serde(default)]
+    pub snapshot_path: Option<PathBuf>,
+    /// Add a timeline of encounters per bucket of this many seconds (e.g. 60)
+    /// to daemon snapshots; unset keeps snapshots to the totals
+    #[serde(default)]
+    pub snapshot_timeline_secs: Option<u64>,
+    /// Seconds between daemon mode snapshots
+    #[serde(default = "default_snapshot_interval_secs")]
+    pub snapshot_interval_secs: u64,
+    /// Where daemon mode writes its log (defaults to the config folder)
+    #[serde(default)]
+    pub daemon_log_path: Option<PathBuf>,
+    /// Smoothing factor (0.0-1.0) for the recent encounters/hour estimate;
+    /// higher values react faster to changes in pace
+    #[serde(default = "default_rate_smoothing")]
+    pub rate_smoothing: f64,
+    /// Continue the session saved by a run that didn't record it in the
+    /// lifetime stats (e.g. one that was closed or crashed) instead of
+    /// starting from zero
+    #[serde(default = "default_resume_session")]
+    pub resume_session: bool,
+    /// Seconds between saves of the running session's counts and time
+    #[serde(default = "default_session_save_interval_secs")]
+    pub session_save_interval_secs: u64,
+}
+
+fn default_pause_poll_interval() -> Duration {
+    Duration::from_millis(PRESET_PAUSE_POLL_MS)
+}
+
+fn default_active_confirm_reads() -> u32 {
+    PRESET_ACTIVE_CONFIRM_READS
+}
+
+fn default_confirm_quit() -> bool {
+    PRESET_CONFIRM_QUIT
+}
+
+fn default_stuck_ending_secs() -> u64 {
+    PRESET_STUCK_ENDING_SECS
+}
+
+fn default_detection_settle_ms() -> u64 {
+    PRESET_DETECTION_SETTLE_MS
+}
+
+fn default_intro_max_lead_ms() -> u64 {
+    PRESET_INTRO_MAX_LEAD_MS
+}
+
+fn default_window_pause_grace_ms() -> u64 {
+    PRESET_WINDOW_PAUSE_GRACE_MS
 }
 
 fn default_min_confidence() -> f32 {
@@ -71,6 +870,117 @@ fn default_preprocess_images() -> bool {
     PRESET_PREPROCESS_IMAGES
 }
 
+fn default_preprocess_fallback() -> bool {
+    PRESET_PREPROCESS_FALLBACK
+}
+
+fn default_clip_low_percentile() -> f32 {
+    PRESET_CLIP_LOW_PERCENTILE
+}
+
+fn default_clip_high_percentile() -> f32 {
+    PRESET_CLIP_HIGH_PERCENTILE
+}
+
+fn default_upscale_factor() -> f32 {
+    PRESET_UPSCALE_FACTOR
+}
+
+fn default_startup_delay_secs() -> u64 {
+    PRESET_STARTUP_DELAY_SECS
+}
+
+fn default_notify_cooldown_secs() -> u64 {
+    PRESET_NOTIFY_COOLDOWN_SECS
+}
+
+fn default_anomaly_frame_history() -> usize {
+    PRESET_ANOMALY_FRAME_HISTORY
+}
+
+fn default_anomaly_timeout_secs() -> u64 {
+    PRESET_ANOMALY_TIMEOUT_SECS
+}
+
+fn default_blank_text_max_length() -> usize {
+    PRESET_BLANK_TEXT_MAX_LENGTH
+}
+
+fn default_min_detection_length() -> usize {
+    PRESET_MIN_DETECTION_LENGTH
+}
+
+fn default_min_log_length() -> usize {
+    PRESET_MIN_LOG_LENGTH
+}
+
+fn default_window_class() -> String {
+    TARGET_WINDOW_CLASS.to_string()
+}
+
+fn default_rate_smoothing() -> f64 {
+    PRESET_RATE_SMOOTHING
+}
+
+fn default_battle_log_pattern() -> String {
+    PRESET_BATTLE_LOG_PATTERN.to_string()
+}
+
+fn default_battle_log_end_phrases() -> Vec<String> {
+    to_strings(PRESET_BATTLE_LOG_END_PHRASES)
+}
+
+fn default_outcome_fled_phrases() -> Vec<String> {
+    to_strings(PRESET_OUTCOME_FLED_PHRASES)
+}
+
+fn default_outcome_caught_phrases() -> Vec<String> {
+    to_strings(PRESET_OUTCOME_CAUGHT_PHRASES)
+}
+
+fn default_outcome_defeated_phrases() -> Vec<String> {
+    to_strings(PRESET_OUTCOME_DEFEATED_PHRASES)
+}
+
+fn default_calibration_samples() -> usize {
+    PRESET_CALIBRATION_SAMPLES
+}
+
+fn default_sinks() -> Vec<SinkConfig> {
+    vec![SinkConfig::Stdout]
+}
+
+fn default_word_separator() -> String {
+    DEFAULT_WORD_SEPARATOR.to_string()
+}
+
+fn default_recent_window_mins() -> u64 {
+    PRESET_RECENT_WINDOW_MINS
+}
+
+fn default_snapshot_interval_secs() -> u64 {
+    PRESET_SNAPSHOT_INTERVAL_SECS
+}
+
+fn default_resume_session() -> bool {
+    PRESET_RESUME_SESSION
+}
+
+fn default_session_save_interval_secs() -> u64 {
+    PRESET_SESSION_SAVE_INTERVAL_SECS
+}
+
+pub fn default_confusables() -> BTreeMap<String, String> {
+    PRESET_CONFUSABLES
+        .iter()
+        .map(|(from, to)| (from.to_string(), to.to_string()))
+        .collect()
+}
+
+fn to_strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|s| s.to_string()).collect()
+}
+
 // Custom serde serialization for Duration
 mod duration_ms {
     use serde::{Deserialize, Deserializer, Serializer};
@@ -96,72 +1006,617 @@ impl Config {
     /// Create a config with preset values optimized for PROClient
     pub fn preset() -> Self {
         Self {
+            source_path: None,
             region: Region::preset(),
+            extra_regions: Vec::new(),
             refresh_rate: Duration::from_millis(PRESET_REFRESH_MS),
+            pause_poll_interval: default_pause_poll_interval(),
+            capture_backend: CaptureBackend::default(),
+            monitor_index: 0,
+            ocr_backend: OcrBackend::default(),
+            capture_command: None,
             empty_threshold: PRESET_EMPTY_THRESHOLD,
+            active_confirm_reads: PRESET_ACTIVE_CONFIRM_READS,
+            stuck_ending_secs: PRESET_STUCK_ENDING_SECS,
+            empty_detection: EmptyDetection::default(),
+            blank_text_max_length: PRESET_BLANK_TEXT_MAX_LENGTH,
+            min_detection_length: PRESET_MIN_DETECTION_LENGTH,
+            min_log_length: PRESET_MIN_LOG_LENGTH,
             window_detection: PRESET_WINDOW_DETECTION,
+            window_backend: WindowBackend::default(),
+            target_window_class: default_window_class(),
+            confirm_quit: PRESET_CONFIRM_QUIT,
+            window_pause_grace_ms: PRESET_WINDOW_PAUSE_GRACE_MS,
+            detection_settle_ms: PRESET_DETECTION_SETTLE_MS,
+            intro_change_threshold: None,
+            intro_max_lead_ms: PRESET_INTRO_MAX_LEAD_MS,
+            ocr_change_threshold: None,
             min_ocr_confidence: MIN_OCR_CONFIDENCE,
+            confidence_floor: ConfidenceFloor::default(),
             preprocess_images: PRESET_PREPROCESS_IMAGES,
+            preprocess_fallback: PRESET_PREPROCESS_FALLBACK,
+            clip_low_percentile: PRESET_CLIP_LOW_PERCENTILE,
+            clip_high_percentile: PRESET_CLIP_HIGH_PERCENTILE,
+            upscale_factor: PRESET_UPSCALE_FACTOR,
+            text_polarity: TextPolarity::default(),
+            threshold_mode: ThresholdMode::default(),
+            word_separator: default_word_separator(),
+            single_line_mode: false,
+            rejoin_split_names: false,
+            snap_max_distance: None,
+            detection_strategy: DetectionStrategy::default(),
+            pattern_anchor: PatternAnchor::default(),
+            battle_log_region: Region::battle_log_preset(),
+            battle_log_pattern: default_battle_log_pattern(),
+            battle_log_end_phrases: default_battle_log_end_phrases(),
+            name_regex: None,
+            outcome: None,
+            startup_delay_secs: PRESET_STARTUP_DELAY_SECS,
+            targets: Vec::new(),
+            notify_targets: Vec::new(),
+            sinks: default_sinks(),
+            notify_cooldown_secs: PRESET_NOTIFY_COOLDOWN_SECS,
+            anomaly_frame_history: PRESET_ANOMALY_FRAME_HISTORY,
+            anomaly_timeout_secs: PRESET_ANOMALY_TIMEOUT_SECS,
+            record_session_frames: false,
+            suppress_if_contains: Vec::new(),
+            name_stop_words: Vec::new(),
+            areas: Vec::new(),
+            spawn_table: BTreeMap::new(),
+            verbose: false,
+            species_list_path: None,
+            keybindings: BTreeMap::new(),
+            global_hotkeys: BTreeMap::new(),
+            confusables: default_confusables(),
+            calibration_samples: PRESET_CALIBRATION_SAMPLES,
+            stats_top_n: None,
+            track_levels: false,
+            shiny_pattern: None,
+            beep_on_count: false,
+            show_battle_times: false,
+            show_confidence_intervals: false,
+            recent_window_mins: PRESET_RECENT_WINDOW_MINS,
+            duration_decimals: 0,
+            output_style: OutputStyle::default(),
+            snapshot_path: None,
+            snapshot_timeline_secs: None,
+            snapshot_interval_secs: PRESET_SNAPSHOT_INTERVAL_SECS,
+            daemon_log_path: None,
+            rate_smoothing: PRESET_RATE_SMOOTHING,
+            resume_session: PRESET_RESUME_SESSION,
+            session_save_interval_secs: PRESET_SESSION_SAVE_INTERVAL_SECS,
+        }
+    }
+
+    /// The screen region to capture for the configured detection strategy
+    pub fn capture_region(&self) -> &Region {
+        match self.detection_strategy {
+            DetectionStrategy::Banner => &self.region,
+            DetectionStrategy::BattleLog => &self.battle_log_region,
+        }
+    }
+
+    /// Every region captured while monitoring, labelled for messages
+    pub fn captured_regions_mut(&mut self) -> Vec<(String, &mut Region)> {
+        let mut regions = Vec::new();
+        if self.targets.is_empty() {
+            let (label, region) = match self.detection_strategy {
+                DetectionStrategy::Banner => ("region", &mut self.region),
+                DetectionStrategy::BattleLog => ("battle_log_region", &mut self.battle_log_region),
+            };
+            regions.push((label.to_string(), region));
+            regions.extend(self.extra_regions.iter_mut().map(|region| ("extra_regions".to_string(), region)));
+        }
+        for target in &mut self.targets {
+            let name = target.name.clone();
+            regions.extend(target.extra_regions.iter_mut().map(|region| (format!("{} extra region", name), region)));
+            regions.push((format!("{} region", name), &mut target.region));
+        }
+        if let Some(outcome) = &mut self.outcome {
+            regions.push(("outcome region".to_string(), &mut outcome.region));
+        }
+        regions
+    }
+
+    /// Start building a config from the preset values
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::new()
+    }
+
+    /// Check that the configuration values are usable
+    pub fn validate(&self) -> Result<(), ProteanError> {
+        let regions = std::iter::once(("region", &self.region))
+            .chain(std::iter::once(("battle_log_region", &self.battle_log_region)))
+            .chain(self.extra_regions.iter().map(|region| ("extra_regions", region)))
+            .chain(self.targets.iter().flat_map(|target| {
+                std::iter::once(&target.region)
+                    .chain(&target.extra_regions)
+                    .map(|region| (target.name.as_str(), region))
+            }));
+        for (name, region) in regions {
+            if region.width == 0 || region.height == 0 {
+                return Err(ProteanError::Config(format!("{} must have a non-zero width and height", name)));
+            }
+        }
+        if self.capture_backend == CaptureBackend::Command && self.capture_command.is_none() {
+            return Err(ProteanError::Config("capture_backend = \"command\" requires a capture_command".to_string()));
+        }
+        if self.snapshot_timeline_secs == Some(0) {
+            return Err(ProteanError::Config("snapshot_timeline_secs must be at least 1".to_string()));
+        }
+        if self.snapshot_interval_secs == 0 {
+            return Err(ProteanError::Config("snapshot_interval_secs must be at least 1".to_string()));
+        }
+        if self.target_window_class.trim().is_empty() {
+            return Err(ProteanError::Config("target_window_class must not be empty".to_string()));
+        }
+        if let Some(target) = self.targets.iter().find(|target| target.window_class.trim().is_empty()) {
+            return Err(ProteanError::Config(format!("window_class of target '{}' must not be empty", target.name)));
+        }
+        if let ThresholdMode::Adaptive { block_size, .. } = self.threshold_mode
+            && (block_size < 3 || block_size % 2 == 0)
+        {
+            return Err(ProteanError::Config("adaptive threshold block_size must be odd and at least 3".to_string()));
+        }
+        if self.shiny_pattern.as_ref().is_some_and(|pattern| pattern.trim().is_empty()) {
+            return Err(ProteanError::Config("shiny_pattern must not be empty (or unset)".to_string()));
+        }
+        if self.snap_max_distance == Some(0) {
+            return Err(ProteanError::Config("snap_max_distance must be at least 1 (or unset)".to_string()));
+        }
+        if self.session_save_interval_secs == 0 {
+            return Err(ProteanError::Config("session_save_interval_secs must be at least 1".to_string()));
+        }
+        if self.refresh_rate.is_zero() {
+            return Err(ProteanError::Config("refresh_rate must be greater than 0ms".to_string()));
+        }
+        if self.pause_poll_interval.is_zero() {
+            return Err(ProteanError::Config("pause_poll_interval must be greater than 0ms".to_string()));
+        }
+        if let Some((name, rate)) = self.spawn_table.iter().find(|(_, rate)| !(0.0..=100.0).contains(*rate)) {
+            return Err(ProteanError::Config(format!("spawn_table rate for {} must be between 0 and 100, got {}", name, rate)));
+        }
+        if self.active_confirm_reads < 2 {
+            return Err(ProteanError::Config(
+                "active_confirm_reads must be at least 2 (the detecting read and one confirmation)".to_string()));
+        }
+        if self.empty_threshold == 0 {
+            return Err(ProteanError::Config("empty_threshold must be at least 1".to_string()));
+        }
+        if !(0.0..=1.0).contains(&self.min_ocr_confidence) {
+            return Err(ProteanError::Config("min_ocr_confidence must be between 0.0 and 1.0".to_string()));
+        }
+        if !(0.0..=1.0).contains(&self.rate_smoothing) {
+            return Err(ProteanError::Config("rate_smoothing must be between 0.0 and 1.0".to_string()));
+        }
+        if !(0.0..=100.0).contains(&self.clip_low_percentile)
+            || !(0.0..=100.0).contains(&self.clip_high_percentile)
+            || self.clip_low_percentile >= self.clip_high_percentile
+        {
+            return Err(ProteanError::Config("clip percentiles must satisfy 0 <= low < high <= 100".to_string()));
+        }
+        if !(1.0..=MAX_UPSCALE_FACTOR).contains(&self.upscale_factor) {
+            return Err(ProteanError::Config(format!("upscale_factor must be between 1.0 and {}", MAX_UPSCALE_FACTOR)));
+        }
+        let mut target_names = HashSet::new();
+        for target in &self.targets {
+            if !target_names.insert(target.name.as_str()) {
+                return Err(ProteanError::Config(format!("duplicate target name '{}'", target.name)));
+            }
+        }
+        let change_thresholds = [
+            ("intro_change_threshold", self.intro_change_threshold),
+            ("ocr_change_threshold", self.ocr_change_threshold),
+        ];
+        for (name, threshold) in change_thresholds {
+            if let Some(threshold) = threshold
+                && !(threshold > 0.0 && threshold <= 1.0)
+            {
+                return Err(ProteanError::Config(format!(
+                    "{} must be greater than 0 and at most 1, got {}", name, threshold)));
+            }
+        }
+        if self.duration_decimals > MAX_DURATION_DECIMALS {
+            return Err(ProteanError::Config(format!(
+                "duration_decimals must be at most {}, got {}", MAX_DURATION_DECIMALS, self.duration_decimals)));
+        }
+        if self.stats_top_n == Some(0) {
+            return Err(ProteanError::Config("stats_top_n must be at least 1".to_string()));
+        }
+        if self.calibration_samples == 0 {
+            return Err(ProteanError::Config("calibration_samples must be at least 1".to_string()));
+        }
+        if self.confusables.keys().any(|from| from.is_empty()) {
+            return Err(ProteanError::Config("confusables must not contain an empty sequence".to_string()));
+        }
+        let mut bound_keys: HashMap<char, Action> = HashMap::new();
+        for (action, key) in self.key_bindings() {
+            if let Some(other) = bound_keys.insert(key.to_ascii_lowercase(), action) {
+                return Err(ProteanError::Config(format!("keybindings: {:?} and {:?} are both bound to '{}'", other, action, key)));
+            }
+        }
+        for combination in self.global_hotkeys.values() {
+            parse_hotkey(combination).map_err(|e| ProteanError::Config(format!("global_hotkeys: {:#}", e)))?;
+        }
+        Ok(())
+    }
+
+    /// Effective key for every action, applying any configured remaps
+    pub fn key_bindings(&self) -> Vec<(Action, char)> {
+        Action::ALL
+            .iter()
+            .map(|&action| (action, self.keybindings.get(&action).copied().unwrap_or(action.default_key())))
+            .collect()
+    }
+
+    /// The clients to monitor, falling back to a single target built from
+    /// the top-level region when no explicit targets are configured
+    pub fn monitor_targets(&self) -> Vec<MonitorTarget> {
+        if !self.targets.is_empty() {
+            return self.targets.clone();
+        }
+
+        vec![MonitorTarget {
+            name: DEFAULT_TARGET_NAME.to_string(),
+            region: *self.capture_region(),
+            extra_regions: self.extra_regions.clone(),
+            window_class: self.target_window_class.clone(),
+            outcome_region: self.outcome.as_ref().map(|outcome| outcome.region),
+        }]
+    }
+
+    /// Preprocessing parameters derived from this configuration
+    pub fn preprocess_options(&self) -> PreprocessOptions {
+        PreprocessOptions {
+            clip_low_percentile: self.clip_low_percentile,
+            clip_high_percentile: self.clip_high_percentile,
+            text_polarity: self.text_polarity,
+            threshold_mode: self.threshold_mode,
+        }
+    }
+
+    /// Confidence filtering applied to OCR output
+    pub fn confidence_filter(&self) -> ConfidenceFilter {
+        ConfidenceFilter {
+            floor: self.confidence_floor,
+            min_confidence: self.min_ocr_confidence,
+        }
+    }
+
+    /// Human-readable description of the pattern used to detect encounters
+    pub fn pattern_label(&self) -> &str {
+        if let Some(name_regex) = &self.name_regex {
+            return name_regex.as_str();
+        }
+        match self.detection_strategy {
+            DetectionStrategy::Banner => "VS. Wild [Pokemon]",
+            DetectionStrategy::BattleLog => &self.battle_log_pattern,
         }
     }
 
     /// Get the default config file path
+    /// 
+    /// This is the first of settings.toml, settings.yaml/.yml and
+    /// settings.json that exists, or settings.toml when there's none yet.
     pub fn default_config_path() -> Result<PathBuf> {
+        let config_dir = dirs::config_dir()
+            .context("Could not determine config directory")?
+            .join(CONFIG_DIR_NAME);
+        let existing = CONFIG_FILE_NAMES
+            .iter()
+            .map(|name| config_dir.join(name))
+            .find(|path| path.exists());
+        Ok(existing.unwrap_or_else(|| config_dir.join(CONFIG_FILE_NAMES[0])))
+    }
+
+    /// Get the folder where debug frame dumps are written
+    pub fn debug_dir() -> Result<PathBuf> {
+        let config_dir = dirs::config_dir()
+            .context("Could not determine config directory")?;
+        Ok(config_dir.join(CONFIG_DIR_NAME).join(DEBUG_DIR_NAME))
+    }
+
+    /// File holding the all-time statistics accumulated across sessions
+    pub fn lifetime_path() -> Result<PathBuf> {
+        let config_dir = dirs::config_dir()
+            .context("Could not determine config directory")?;
+        Ok(config_dir.join(CONFIG_DIR_NAME).join(LIFETIME_FILE_NAME))
+    }
+
+    /// File holding the running session, so it can be resumed after a restart
+    pub fn session_path() -> Result<PathBuf> {
+        Self::data_file(SESSION_FILE_NAME)
+    }
+
+    /// Where the species list of an imported rule set is kept
+    pub fn imported_species_path() -> Result<PathBuf> {
+        Self::data_file(IMPORTED_SPECIES_FILE_NAME)
+    }
+
+    /// Lock file marking a running instance
+    pub fn lock_path() -> Result<PathBuf> {
+        Self::data_file(LOCK_FILE_NAME)
+    }
+
+    /// Get the daemon mode snapshot file, falling back to the config folder
+    pub fn snapshot_file(&self) -> Result<PathBuf> {
+        match &self.snapshot_path {
+            Some(path) => Ok(path.clone()),
+            None => Self::data_file(SNAPSHOT_FILE_NAME),
+        }
+    }
+
+    /// Get the daemon mode log file, falling back to the config folder
+    pub fn daemon_log_file(&self) -> Result<PathBuf> {
+        match &self.daemon_log_path {
+            Some(path) => Ok(path.clone()),
+            None => Self::data_file(DAEMON_LOG_FILE_NAME),
+        }
+    }
+
+    /// File the output printed while the dashboard is shown goes to
+    pub fn tui_log_path() -> Result<PathBuf> {
+        Self::data_file(TUI_LOG_FILE_NAME)
+    }
+
+    fn data_file(file_name: &str) -> Result<PathBuf> {
         let config_dir = dirs::config_dir()
             .context("Could not determine config directory")?;
-        Ok(config_dir.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME))
+        Ok(config_dir.join(CONFIG_DIR_NAME).join(file_name))
     }
 
     /// Load config from file, or create via user input if it doesn't exist
     /// This is the preferred way to initialize config in the application
-    pub fn load_or_create() -> Result<Self> {
+    /// 
+    /// # Arguments
+    /// * `path` - Config file to load instead of the default one; it must exist
+    pub fn load_or_create(path: Option<&Path>) -> Result<Self> {
+        if let Some(path) = path {
+            if !path.exists() {
+                bail!("Config file {} not found", path.display());
+            }
+            let mut config = Self::load_from(path)?;
+            config.source_path = Some(path.to_path_buf());
+            return Ok(config);
+        }
+
         let config_path = Self::default_config_path()?;
-        
         if config_path.exists() {
-            println!("Loading configuration from: {}", config_path.display());
-            let contents = fs::read_to_string(&config_path)
-                .context("Failed to read config file")?;
-            let config: Config = toml::from_str(&contents)
-                .context("Failed to parse config file")?;
-            
-            println!("✓ Configuration loaded successfully!");
-            Self::display_config(&config);
-            Ok(config)
+            Self::load_from(&config_path)
         } else {
             println!("No config file found at: {}", config_path.display());
+            Self::check_config_dir_writable(&config_path);
             Self::from_user_input()
         }
     }
 
-    /// Save current config to the default config file location
-    pub fn save(&self) -> Result<()> {
-        let config_path = Self::default_config_path()?;
+    fn load_from(config_path: &Path) -> Result<Self> {
+        println!("Loading configuration from: {}", config_path.display());
+        let contents = fs::read_to_string(config_path)
+            .context("Failed to read config file")?;
+        let config: Config = ConfigFormat::from_path(config_path)?.parse(&contents)
+            .with_context(|| format!("Failed to parse config file {}", config_path.display()))?;
+        config.validate()
+            .with_context(|| format!("Invalid configuration in {}", config_path.display()))?;
         
+        println!("✓ Configuration loaded successfully!");
+        Self::display_config(&config);
+        Ok(config)
+    }
+
+    /// Save current config to the file it was loaded from (`--config`) or
+    /// the default config file location
+    pub fn save(&self) -> Result<()> {
+        match &self.source_path {
+            Some(path) => self.save_to(path),
+            None => self.save_to(&Self::default_config_path()?),
+        }
+    }
+
+    /// Save current config to `config_path`, creating its folder if needed
+    pub fn save_to(&self, config_path: &Path) -> Result<()> {
         // Create parent directory if it doesn't exist
         if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent)?;
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create config folder {}", parent.display()))?;
         }
 
-        let toml_string = toml::to_string_pretty(self)
+        let contents = ConfigFormat::from_path(config_path)?.serialize(self)
             .context("Failed to serialize config")?;
         
-        fs::write(&config_path, toml_string)
-            .context("Failed to write config file")?;
+        fs::write(config_path, contents)
+            .with_context(|| format!("Failed to write config file {}", config_path.display()))?;
         
         println!("✓ Configuration saved to: {}", config_path.display());
         Ok(())
     }
 
+    /// Save to the default location, offering another path (or not saving
+    /// at all) if that fails, so freshly entered settings aren't lost
+    fn save_interactively(&self) -> Result<()> {
+        let mut result = self.save();
+        while let Err(e) = result {
+            println!("✗ {:#}", e);
+            print!("Save to another path [{}], or 'n' to continue without saving: ", FALLBACK_CONFIG_PATH);
+            io::stdout().flush()?;
+            let mut path_input = String::new();
+            io::stdin().read_line(&mut path_input)?;
+
+            let path = match path_input.trim() {
+                "" => FALLBACK_CONFIG_PATH,
+                "n" | "N" => {
+                    println!("Continuing without saving");
+                    return Ok(());
+                }
+                path => path,
+            };
+            result = self.save_to(Path::new(path));
+            if result.is_ok() {
+                println!("  Note: only {} is loaded automatically; copy the file there once it's writable",
+                         Self::default_config_path()?.display());
+            }
+        }
+        Ok(())
+    }
+
+    /// Check up front whether the config folder can be written, so the user
+    /// knows before answering the setup prompts
+    fn check_config_dir_writable(config_path: &Path) {
+        let Some(dir) = config_path.parent() else {
+            return;
+        };
+        let probe = dir.join(WRITE_PROBE_FILE_NAME);
+        let writable = fs::create_dir_all(dir)
+            .and_then(|()| fs::write(&probe, b""))
+            .and_then(|()| fs::remove_file(&probe));
+        if let Err(e) = writable {
+            println!("⚠ Config folder {} is not writable ({})", dir.display(), e);
+            println!("  You'll be offered another location when saving.\n");
+        }
+    }
+
     /// Display the current configuration in a human-readable format
     fn display_config(config: &Config) {
         println!("\nCurrent configuration:");
         println!("  X: {}, Y: {}", config.region.x, config.region.y);
         println!("  Width: {}, Height: {}", config.region.width, config.region.height);
+        for region in &config.extra_regions {
+            println!("  Extra region: X: {}, Y: {}, Width: {}, Height: {}",
+                     region.x, region.y, region.width, region.height);
+        }
         println!("  Refresh rate: {}ms", config.refresh_rate.as_millis());
+        println!("  Pause poll interval: {}ms", config.pause_poll_interval.as_millis());
+        match &config.capture_command {
+            Some(command) => println!("  Capture: {:?} (command: {})", config.capture_backend, command),
+            None => println!("  Capture: {:?}", config.capture_backend),
+        }
+        println!("  Monitor: {}", config.monitor_index);
+        println!("  OCR backend: {:?}", config.ocr_backend);
         println!("  Empty threshold: {}", config.empty_threshold);
+        println!("  Active confirm reads: {}", config.active_confirm_reads);
+        println!("  Stuck ending timeout: {}s", config.stuck_ending_secs);
+        println!("  Empty detection: {:?}", config.empty_detection);
+        println!("  Min detection length: {}", config.min_detection_length);
+        println!("  Min log length: {}", config.min_log_length);
+        println!("  Detection settle time: {}ms", config.detection_settle_ms);
+        if let Some(threshold) = config.intro_change_threshold {
+            println!("  Intro change threshold: {} (max lead {}ms)", threshold, config.intro_max_lead_ms);
+        }
+        if let Some(threshold) = config.ocr_change_threshold {
+            println!("  OCR change threshold: {}", threshold);
+        }
         println!("  Window detection: {}", config.window_detection);
-        println!("  Min OCR confidence: {}", config.min_ocr_confidence);
+        if config.window_detection {
+            println!("  Window backend: {:?}", config.window_backend);
+            println!("  Target window class: {}", config.target_window_class);
+            println!("  Window pause grace: {}ms", config.window_pause_grace_ms);
+        }
+        println!("  Confirm quit: {}", config.confirm_quit);
+        if config.confidence_filter().is_active() {
+            println!("  Min OCR confidence: {} ({:?})", config.min_ocr_confidence, config.confidence_floor);
+        } else {
+            println!("  Min OCR confidence: off");
+        }
         println!("  Preprocess images: {}", config.preprocess_images);
+        if config.preprocess_images {
+            println!("  Contrast clipping: {}% - {}%", config.clip_low_percentile, config.clip_high_percentile);
+            println!("  Preprocess fallback: {}", config.preprocess_fallback);
+        }
+        println!("  Text polarity: {:?}", config.text_polarity);
+        if config.upscale_factor > 1.0 {
+            println!("  Upscale factor: {}x", config.upscale_factor);
+        }
+        println!("  Threshold mode: {:?}", config.threshold_mode);
+        println!("  Word separator: {:?}", config.word_separator);
+        println!("  Rejoin split names: {}", config.rejoin_split_names);
+        if let Some(distance) = config.snap_max_distance {
+            println!("  Snap names to species within: {} edits", distance);
+        }
+        println!("  Single line mode: {}", config.single_line_mode);
+        println!("  Detection strategy: {:?}", config.detection_strategy);
+        println!("  Pattern anchor: {:?}", config.pattern_anchor);
+        if config.detection_strategy == DetectionStrategy::BattleLog {
+            let log = &config.battle_log_region;
+            println!("  Battle log region: X: {}, Y: {}, Width: {}, Height: {}", log.x, log.y, log.width, log.height);
+            println!("  Battle log pattern: \"{}\"", config.battle_log_pattern);
+            println!("  Battle log end phrases: {:?}", config.battle_log_end_phrases);
+        }
+        if let Some(name_regex) = &config.name_regex {
+            println!("  Name regex: {}", name_regex.as_str());
+        }
+        println!("  Startup delay: {}s", config.startup_delay_secs);
+        println!("  Rate smoothing: {}", config.rate_smoothing);
+        println!("  Resume session: {} (saved every {}s)", config.resume_session, config.session_save_interval_secs);
+        println!("  Confusables: {:?}", config.confusables);
+        println!("  Calibration samples: {}", config.calibration_samples);
+        if let Some(top_n) = config.stats_top_n {
+            println!("  Statistics top N: {}", top_n);
+        }
+        println!("  Track levels: {}", config.track_levels);
+        if let Some(pattern) = &config.shiny_pattern {
+            println!("  Shiny pattern: {:?}", pattern);
+        }
+        println!("  Beep on count: {}", config.beep_on_count);
+        println!("  Show battle times: {}", config.show_battle_times);
+        println!("  Show confidence intervals: {}", config.show_confidence_intervals);
+        println!("  Recent window: {}m", config.recent_window_mins);
+        println!("  Duration decimals: {}", config.duration_decimals);
+        println!("  Output style: {:?}", config.output_style);
+        if let Some(path) = &config.snapshot_path {
+            println!("  Snapshot path: {}", path.display());
+        }
+        println!("  Snapshot interval: {}s", config.snapshot_interval_secs);
+        if let Some(secs) = config.snapshot_timeline_secs {
+            println!("  Snapshot timeline: {}s buckets", secs);
+        }
+        if let Some(path) = &config.daemon_log_path {
+            println!("  Daemon log: {}", path.display());
+        }
+        match &config.species_list_path {
+            Some(path) => println!("  Species list: {}", path.display()),
+            None => println!("  Species list: embedded"),
+        }
+        for target in &config.targets {
+            println!("  Target '{}': X: {}, Y: {}, Width: {}, Height: {} (window: {})",
+                     target.name, target.region.x, target.region.y, target.region.width,
+                     target.region.height, target.window_class);
+            for region in &target.extra_regions {
+                println!("    Extra region: X: {}, Y: {}, Width: {}, Height: {}",
+                         region.x, region.y, region.width, region.height);
+            }
+        }
+        if !config.notify_targets.is_empty() {
+            println!("  Notify targets: {:?} (cooldown {}s)", config.notify_targets, config.notify_cooldown_secs);
+        }
+        println!("  Anomaly frame history: {} (timeout {}s)", config.anomaly_frame_history, config.anomaly_timeout_secs);
+        if config.record_session_frames {
+            println!("  Recording session frames to the debug folder");
+        }
+        if !config.suppress_if_contains.is_empty() {
+            println!("  Suppress frames containing: {:?}", config.suppress_if_contains);
+        }
+        if !config.name_stop_words.is_empty() {
+            println!("  Name stop words: {:?}", config.name_stop_words);
+        }
+        println!("  Verbose: {}", config.verbose);
+        println!("  Count sinks: {:?}", config.sinks);
+        if !config.spawn_table.is_empty() {
+            println!("  Spawn table: {:?}", config.spawn_table);
+        }
+        if !config.areas.is_empty() {
+            println!("  Areas: {:?}", config.areas);
+        }
+        if !config.global_hotkeys.is_empty() {
+            println!("  Global hotkeys: {:?}", config.global_hotkeys);
+        }
+        if !config.keybindings.is_empty() {
+            println!("  Keybindings: {:?}", config.keybindings);
+        }
+        match &config.outcome {
+            Some(outcome) => println!("  Outcome region: X: {}, Y: {}, Width: {}, Height: {}",
+                outcome.region.x, outcome.region.y, outcome.region.width, outcome.region.height),
+            None => println!("  Outcome tracking: disabled"),
+        }
     }
 
     /// Create config by prompting user for input
@@ -190,7 +1645,7 @@ impl Config {
         io::stdin().read_line(&mut save_choice)?;
         
         if save_choice.trim().to_lowercase() == "y" {
-            config.save()?;
+            config.save_interactively()?;
         }
 
         Ok(config)
@@ -200,6 +1655,11 @@ impl Config {
     fn from_custom_input() -> Result<Self> {
         println!("\nEnter custom coordinates:");
         
+        print!("Monitor index (see --list-monitors, default 0): ");
+        io::stdout().flush()?;
+        let mut monitor_input = String::new();
+        io::stdin().read_line(&mut monitor_input)?;
+        let monitor_index = monitor_input.trim().parse().unwrap_or(0);
         let x = Self::read_input::<i32>("X coordinate (left): ", "Invalid X")?;
         let y = Self::read_input::<i32>("Y coordinate (top): ", "Invalid Y")?;
         let width = Self::read_input::<u32>("Width: ", "Invalid width")?;
@@ -212,8 +1672,20 @@ impl Config {
         let mut window_input = String::new();
         io::stdin().read_line(&mut window_input)?;
         let window_detection = window_input.trim().to_lowercase() == "y";
+        let target_window_class = if window_detection {
+            print!("Game window class (default {}): ", TARGET_WINDOW_CLASS);
+            io::stdout().flush()?;
+            let mut class_input = String::new();
+            io::stdin().read_line(&mut class_input)?;
+            match class_input.trim() {
+                "" => default_window_class(),
+                class => class.to_string(),
+            }
+        } else {
+            default_window_class()
+        };
 
-        print!("Minimum OCR confidence (0.0-1.0, default 0.5): ");
+        print!("Minimum OCR confidence (0.0-1.0, 0 to disable, default 0.5): ");
         io::stdout().flush()?;
         let mut confidence_input = String::new();
         io::stdin().read_line(&mut confidence_input)?;
@@ -226,14 +1698,34 @@ impl Config {
         io::stdin().read_line(&mut preprocess_input)?;
         let preprocess_images = preprocess_input.trim().to_lowercase() == "y";
 
-        Ok(Self {
-            region: Region { x, y, width, height },
-            refresh_rate: Duration::from_millis(refresh_ms),
-            empty_threshold,
-            window_detection,
-            min_ocr_confidence,
-            preprocess_images,
-        })
+        print!("Detect from battle log instead of banner? (y/n, default n): ");
+        io::stdout().flush()?;
+        let mut strategy_input = String::new();
+        io::stdin().read_line(&mut strategy_input)?;
+        let (detection_strategy, battle_log_region) = if strategy_input.trim().to_lowercase() == "y" {
+            println!("\nEnter battle log coordinates:");
+            let log_x = Self::read_input::<i32>("Battle log X (left): ", "Invalid X")?;
+            let log_y = Self::read_input::<i32>("Battle log Y (top): ", "Invalid Y")?;
+            let log_width = Self::read_input::<u32>("Battle log width: ", "Invalid width")?;
+            let log_height = Self::read_input::<u32>("Battle log height: ", "Invalid height")?;
+            let log_region = Region { x: log_x, y: log_y, width: log_width, height: log_height };
+            (DetectionStrategy::BattleLog, log_region)
+        } else {
+            (DetectionStrategy::Banner, Region::battle_log_preset())
+        };
+
+        Self::builder()
+            .monitor_index(monitor_index)
+            .region(Region { x, y, width, height })
+            .refresh_rate(Duration::from_millis(refresh_ms))
+            .empty_threshold(empty_threshold)
+            .window_detection(window_detection)
+            .target_window_class(target_window_class)
+            .min_ocr_confidence(min_ocr_confidence)
+            .preprocess_images(preprocess_images)
+            .detection_strategy(detection_strategy)
+            .battle_log_region(battle_log_region)
+            .build()
     }
 
     /// Helper function to read and parse user input
@@ -249,3 +1741,80 @@ impl Config {
             .map_err(|e| anyhow::anyhow!("{}: {}", error_msg, e))
     }
 }
+
+/// Fluent builder for `Config`, starting from the preset values
+/// 
+/// Useful when constructing a config in code rather than from the
+/// settings file; `build` validates the result.
+#[derive(Debug, Clone)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl ConfigBuilder {
+    pub fn new() -> Self {
+        Self { config: Config::preset() }
+    }
+
+    pub fn region(mut self, region: Region) -> Self {
+        self.config.region = region;
+        self
+    }
+
+    pub fn refresh_rate(mut self, refresh_rate: Duration) -> Self {
+        self.config.refresh_rate = refresh_rate;
+        self
+    }
+
+    pub fn empty_threshold(mut self, empty_threshold: u32) -> Self {
+        self.config.empty_threshold = empty_threshold;
+        self
+    }
+
+    pub fn window_detection(mut self, window_detection: bool) -> Self {
+        self.config.window_detection = window_detection;
+        self
+    }
+
+    pub fn monitor_index(mut self, monitor_index: usize) -> Self {
+        self.config.monitor_index = monitor_index;
+        self
+    }
+
+    pub fn target_window_class(mut self, target_window_class: String) -> Self {
+        self.config.target_window_class = target_window_class;
+        self
+    }
+
+    pub fn min_ocr_confidence(mut self, min_ocr_confidence: f32) -> Self {
+        self.config.min_ocr_confidence = min_ocr_confidence;
+        self
+    }
+
+    pub fn preprocess_images(mut self, preprocess_images: bool) -> Self {
+        self.config.preprocess_images = preprocess_images;
+        self
+    }
+
+    pub fn detection_strategy(mut self, detection_strategy: DetectionStrategy) -> Self {
+        self.config.detection_strategy = detection_strategy;
+        self
+    }
+
+    pub fn battle_log_region(mut self, battle_log_region: Region) -> Self {
+        self.config.battle_log_region = battle_log_region;
+        self
+    }
+
+    /// Validate and return the finished config
+    pub fn build(self) -> Result<Config> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+impl Default for ConfigBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
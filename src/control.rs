@@ -0,0 +1,120 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config::Action;
+
+/// How long to wait for a connected client to send its commands
+const CLIENT_READ_TIMEOUT_MS: u64 = 100;
+
+/// A command received on the control socket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlCommand {
+    Pause,
+    Resume,
+    /// Any other action, run exactly as if its key had been pressed
+    Run(Action),
+}
+
+impl ControlCommand {
+    fn parse(command: &str) -> Option<Self> {
+        match command.trim().to_lowercase().as_str() {
+            "pause" => Some(ControlCommand::Pause),
+            "resume" => Some(ControlCommand::Resume),
+            "stats" => Some(ControlCommand::Run(Action::ShowStats)),
+            "normalize" => Some(ControlCommand::Run(Action::Normalize)),
+            "area" => Some(ControlCommand::Run(Action::CycleArea)),
+            "quit" => Some(ControlCommand::Run(Action::Quit)),
+            _ => None,
+        }
+    }
+}
+
+/// Unix domain socket accepting newline-separated commands from scripts
+///
+/// e.g. `echo pause | socat - UNIX-CONNECT:/tmp/protean.sock`. Every command
+/// is answered with `ok` or an error line. The socket file is removed when
+/// the listener is dropped.
+pub struct ControlSocket {
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl ControlSocket {
+    /// Listen on `path`, replacing a stale socket left by a previous run
+    pub fn bind(path: &Path) -> Result<Self> {
+        if let Ok(metadata) = fs::symlink_metadata(path) {
+            if !metadata.file_type().is_socket() {
+                bail!("{} exists and is not a socket", path.display());
+            }
+            fs::remove_file(path)
+                .with_context(|| format!("Failed to remove stale socket {}", path.display()))?;
+        }
+
+        let listener = UnixListener::bind(path)
+            .with_context(|| format!("Failed to bind control socket {}", path.display()))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self { listener, path: path.to_path_buf() })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Accept pending connections and return the commands they sent
+    pub fn poll(&self) -> Vec<ControlCommand> {
+        let mut commands = Vec::new();
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    if let Err(e) = read_commands(stream, &mut commands) {
+                        eprintln!("Control socket error: {}", e);
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    eprintln!("Control socket error: {}", e);
+                    break;
+                }
+            }
+        }
+        commands
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Read commands from one client until it disconnects or goes quiet
+fn read_commands(stream: UnixStream, commands: &mut Vec<ControlCommand>) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_millis(CLIENT_READ_TIMEOUT_MS)))?;
+    let mut writer = stream.try_clone()?;
+
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => break,
+            Err(e) => return Err(e),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match ControlCommand::parse(&line) {
+            Some(command) => {
+                commands.push(command);
+                writeln!(writer, "ok")?;
+            }
+            None => writeln!(writer, "error: unknown command \"{}\"", line.trim())?,
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use signal_hook::consts::{SIGINT, SIGTERM};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+use crate::pokedex::Pokedex;
+use crate::statistics::{HuntStats, TimelineBucket};
+
+/// Current counts as written to the snapshot file
+#[derive(Debug, Serialize)]
+struct Snapshot {
+    /// Unix time the snapshot was written
+    written_at: u64,
+    /// Active hunting time in seconds (excluding pauses)
+    hunt_duration_secs: u64,
+    paused: bool,
+    total: usize,
+    encounters_per_hour: Option<f64>,
+    /// Encounters since the last target species (absent without targets)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    since_last_target: Option<usize>,
+    counts: BTreeMap<String, usize>,
+    /// Counts keyed by national dex number (names not in the dex are omitted)
+    dex_counts: BTreeMap<u16, usize>,
+    /// Encounters per `snapshot_timeline_secs` bucket of active time, oldest
+    /// first (absent unless configured)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeline: Option<Vec<TimelineBucket>>,
+}
+
+/// Headless mode state: snapshot schedule and the termination flag
+///
+/// Keyboard handling and interactive output are skipped; stdout and stderr
+/// go to the log file and the current counts are written as JSON every
+/// `snapshot_interval_secs` and once more on SIGTERM/SIGINT.
+pub struct Daemon {
+    terminate: Arc<AtomicBool>,
+    snapshot_path: PathBuf,
+    interval: Duration,
+    timeline_bucket: Option<Duration>,
+    last_snapshot: Instant,
+}
+
+impl Daemon {
+    /// Redirect output to the log file and install the signal handlers
+    pub fn start(config: &Config) -> Result<Self> {
+        let snapshot_path = config.snapshot_file()?;
+        let log_path = config.daemon_log_file()?;
+        println!("Daemon mode: logging to {}, snapshots to {}",
+                 log_path.display(), snapshot_path.display());
+        redirect_output(&log_path)?;
+
+        let terminate = Arc::new(AtomicBool::new(false));
+        for signal in [SIGTERM, SIGINT] {
+            signal_hook::flag::register(signal, Arc::clone(&terminate))
+                .context("Failed to install signal handler")?;
+        }
+
+        Ok(Self {
+            terminate,
+            snapshot_path,
+            interval: Duration::from_secs(config.snapshot_interval_secs),
+            timeline_bucket: config.snapshot_timeline_secs.map(Duration::from_secs),
+            last_snapshot: Instant::now(),
+        })
+    }
+
+    /// Whether SIGTERM or SIGINT has been received
+    pub fn terminated(&self) -> bool {
+        self.terminate.load(Ordering::Relaxed)
+    }
+
+    /// Whether the snapshot interval has elapsed since the last snapshot
+    pub fn snapshot_due(&self) -> bool {
+        self.last_snapshot.elapsed() >= self.interval
+    }
+
+    /// Write the current counts to the snapshot file
+    pub fn write_snapshot(&mut self, stats: &HuntStats, active_duration: Duration, paused: bool) -> Result<()> {
+        self.last_snapshot = Instant::now();
+
+        let view = stats.view();
+        let snapshot = Snapshot {
+            written_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            hunt_duration_secs: active_duration.as_secs(),
+            paused,
+            total: view.text_counts.values().sum(),
+            encounters_per_hour: view.encounter_log.session_rate_per_hour(active_duration),
+            since_last_target: view.since_last_target(),
+            dex_counts: dex_counts(&view.text_counts),
+            timeline: self.timeline_bucket
+                .map(|bucket| view.encounter_log.timeline(bucket, active_duration)),
+            counts: view.text_counts.into_iter().collect(),
+        };
+        let json = serde_json::to_string_pretty(&snapshot)
+            .context("Failed to serialize snapshot")?;
+
+        // Write beside the target and rename so readers never see a partial file
+        if let Some(parent) = self.snapshot_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let temp_path = self.snapshot_path.with_extension("json.tmp");
+        fs::write(&temp_path, json)
+            .with_context(|| format!("Failed to write snapshot {}", temp_path.display()))?;
+        fs::rename(&temp_path, &self.snapshot_path)
+            .with_context(|| format!("Failed to replace snapshot {}", self.snapshot_path.display()))
+    }
+}
+
+/// Sum counts by dex number, skipping names the dex doesn't know
+fn dex_counts(text_counts: &HashMap<String, usize>) -> BTreeMap<u16, usize> {
+    let pokedex = Pokedex::bundled();
+    let mut counts = BTreeMap::new();
+    for (name, count) in text_counts {
+        if let Some(number) = pokedex.number(name) {
+            *counts.entry(number).or_insert(0) += count;
+        }
+    }
+    counts
+}
+
+/// Point stdout and stderr at the end of the log file
+pub fn redirect_output(log_path: &Path) -> Result<()> {
+    if let Some(parent) = log_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)
+        .with_context(|| format!("Failed to open log {}", log_path.display()))?;
+
+    for fd in [libc::STDOUT_FILENO, libc::STDERR_FILENO] {
+        // SAFETY: both descriptors are valid for the duration of the call
+        if unsafe { libc::dup2(log.as_raw_fd(), fd) } < 0 {
+            return Err(std::io::Error::last_os_error())
+                .with_context(|| format!("Failed to redirect output to {}", log_path.display()));
+        }
+    }
+    Ok(())
+}
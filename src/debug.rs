@@ -0,0 +1,219 @@
+use anyhow::{Context, Result};
+use image::{DynamicImage, Rgb, RgbImage};
+use ocrs::{ImageSource, OcrEngine};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::ocr::{detect_words_with_probabilities, mean_text_probability};
+
+const FRAME_TEXT_FILE_NAME: &str = "frames.txt";
+/// OCR text of every recorded session frame, in `--replay` script format
+pub const SESSION_SCRIPT_FILE_NAME: &str = "replay.txt";
+/// Counts per species at the end of a recorded session
+const SESSION_COUNTS_FILE_NAME: &str = "counts.json";
+
+// Heat map colors
+const HEAT_MAP_LOW: Rgb<u8> = Rgb([255, 0, 0]);
+const HEAT_MAP_HIGH: Rgb<u8> = Rgb([0, 200, 0]);
+/// Lines the recognizer returned nothing for
+const HEAT_MAP_UNREAD: Rgb<u8> = Rgb([255, 0, 255]);
+/// Share of the word color blended into the pixels inside each word
+const HEAT_MAP_FILL_OPACITY: f32 = 0.3;
+
+/// Ring buffer of the most recently captured frames and their OCR text
+/// 
+/// Kept in memory so the context around a detection anomaly can be written
+/// to disk after the fact, without constantly saving every frame.
+pub struct FrameHistory {
+    frames: VecDeque<(DynamicImage, String)>,
+    capacity: usize,
+}
+
+impl FrameHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            frames: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Whether frames are being kept at all
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Remember a frame, dropping the oldest one when full
+    pub fn push(&mut self, image: DynamicImage, text: String) {
+        if !self.is_enabled() {
+            return;
+        }
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back((image, text));
+    }
+
+    /// The most recently remembered frame
+    pub fn latest(&self) -> Option<&DynamicImage> {
+        self.frames.back().map(|(image, _)| image)
+    }
+
+    /// Write all remembered frames into a new timestamped folder under `base_dir`
+    /// 
+    /// # Returns
+    /// * `Ok(PathBuf)` of the folder containing `frame_NN.png` files and `frames.txt`
+    /// * `Err` if the folder or any file could not be written
+    pub fn dump(&self, base_dir: &Path, label: &str) -> Result<PathBuf> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let dump_dir = base_dir.join(format!("{}-{}", label, timestamp));
+        fs::create_dir_all(&dump_dir)
+            .with_context(|| format!("Failed to create debug folder {}", dump_dir.display()))?;
+
+        let mut texts = String::new();
+        for (index, (image, text)) in self.frames.iter().enumerate() {
+            let file_name = format!("frame_{:02}.png", index);
+            image.save(dump_dir.join(&file_name))
+                .with_context(|| format!("Failed to save {}", file_name))?;
+            texts.push_str(&format!("{}: {}\n", file_name, text));
+        }
+
+        fs::write(dump_dir.join(FRAME_TEXT_FILE_NAME), texts)
+            .context("Failed to write frame text")?;
+        Ok(dump_dir)
+    }
+}
+
+/// Saves every frame of a session with the text OCR read from it, so the
+/// session can be verified (`--verify-session`) or replayed later
+/// 
+/// Frames are written as `frame_NNNNN.png` and their texts, one line per
+/// frame with line breaks written as `\n`, as a replay script.
+pub struct SessionRecorder {
+    dir: PathBuf,
+    script: File,
+    frames: usize,
+}
+
+impl SessionRecorder {
+    /// Start recording into a new timestamped folder under `base_dir`
+    pub fn create(base_dir: &Path, label: &str) -> Result<Self> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let dir = base_dir.join(format!("session-{}-{}", label, timestamp));
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create session folder {}", dir.display()))?;
+        let script = File::create(dir.join(SESSION_SCRIPT_FILE_NAME))
+            .context("Failed to create session replay script")?;
+        Ok(Self { dir, script, frames: 0 })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Save one frame and the text read from it
+    pub fn record(&mut self, image: &DynamicImage, text: &str) -> Result<()> {
+        let file_name = session_frame_name(self.frames);
+        image.save(self.dir.join(&file_name))
+            .with_context(|| format!("Failed to save {}", file_name))?;
+        writeln!(self.script, "{}", text.replace('\n', "\\n"))?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Save the live counts the session ended with, for verification
+    pub fn write_counts(&self, counts: &HashMap<String, usize>) -> Result<()> {
+        let counts: BTreeMap<_, _> = counts.iter().collect();
+        fs::write(self.dir.join(SESSION_COUNTS_FILE_NAME), serde_json::to_string_pretty(&counts)?)
+            .context("Failed to save session counts")
+    }
+}
+
+/// Load the live counts saved with a recorded session, if it ended cleanly
+pub fn read_session_counts(dir: &Path) -> Result<Option<BTreeMap<String, usize>>> {
+    let path = dir.join(SESSION_COUNTS_FILE_NAME);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(Some(serde_json::from_str(&contents)
+        .with_context(|| format!("Invalid session counts in {}", path.display()))?))
+}
+
+/// File name of the recorded frame at `index`
+pub fn session_frame_name(index: usize) -> String {
+    format!("frame_{:05}.png", index)
+}
+
+/// Save a confidence heat map as a timestamped PNG under `base_dir`
+pub fn save_heat_map(heat_map: &RgbImage, base_dir: &Path, label: &str) -> Result<PathBuf> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+    fs::create_dir_all(base_dir)
+        .with_context(|| format!("Failed to create debug folder {}", base_dir.display()))?;
+    let path = base_dir.join(format!("heatmap-{}-{}.png", label, timestamp));
+    heat_map.save(&path)
+        .with_context(|| format!("Failed to save {}", path.display()))?;
+    Ok(path)
+}
+
+/// Overlay the detected word rectangles on `image`, colored by confidence
+/// 
+/// ocrs doesn't expose recognition scores, so each word is colored by the
+/// mean text probability the detection model gave its pixels: green words
+/// were found confidently, red ones barely passed the detection threshold.
+/// Words on lines the recognizer read nothing from are outlined in magenta.
+/// 
+/// # Arguments
+/// * `engine` - The OCR engine to use
+/// * `image` - The image exactly as it is passed to OCR
+/// 
+/// # Returns
+/// * `Ok(RgbImage)` with the overlay drawn
+/// * `Err` if OCR processing fails
+pub fn confidence_heat_map(engine: &OcrEngine, image: &DynamicImage) -> Result<RgbImage> {
+    let mut heat_map = image.to_rgb8();
+
+    let ocr_input = engine.prepare_input(ImageSource::from_bytes(heat_map.as_raw(), heat_map.dimensions())?)?;
+    let (word_rects, probabilities) = detect_words_with_probabilities(engine, &ocr_input)?;
+    let line_rects = engine.find_text_lines(&ocr_input, &word_rects);
+    let line_texts = engine.recognize_text(&ocr_input, &line_rects)?;
+
+    for (line, text) in line_rects.iter().zip(&line_texts) {
+        for word in line {
+            let corners = word.corners();
+            let clamp_x = |x: f32| (x.max(0.0) as u32).min(heat_map.width().saturating_sub(1));
+            let clamp_y = |y: f32| (y.max(0.0) as u32).min(heat_map.height().saturating_sub(1));
+            let left = clamp_x(corners.iter().map(|p| p.x).fold(f32::MAX, f32::min));
+            let right = clamp_x(corners.iter().map(|p| p.x).fold(f32::MIN, f32::max));
+            let top = clamp_y(corners.iter().map(|p| p.y).fold(f32::MAX, f32::min));
+            let bottom = clamp_y(corners.iter().map(|p| p.y).fold(f32::MIN, f32::max));
+
+            let confidence = mean_text_probability(&probabilities, engine.detection_threshold(), left, top, right, bottom);
+            let color = blend(HEAT_MAP_LOW, HEAT_MAP_HIGH, confidence);
+
+            for y in top..=bottom {
+                for x in left..=right {
+                    let pixel = heat_map.get_pixel_mut(x, y);
+                    *pixel = blend(*pixel, color, HEAT_MAP_FILL_OPACITY);
+                    let on_edge = x == left || x == right || y == top || y == bottom;
+                    if on_edge {
+                        *pixel = if text.is_some() { color } else { HEAT_MAP_UNREAD };
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(heat_map)
+}
+
+/// Linear blend from `from` (amount 0.0) to `to` (amount 1.0)
+fn blend(from: Rgb<u8>, to: Rgb<u8>, amount: f32) -> Rgb<u8> {
+    let amount = amount.clamp(0.0, 1.0);
+    Rgb(std::array::from_fn(|channel| {
+        (from.0[channel] as f32 * (1.0 - amount) + to.0[channel] as f32 * amount).round() as u8
+    }))
+}
@@ -0,0 +1,25 @@
+use thiserror::Error;
+
+/// Underlying error reported by a dependency (screenshots, ocrs, hyprctl)
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Errors from the capture, OCR, configuration and window layers
+///
+/// Lets callers tell failures apart, e.g. skip a frame when a capture
+/// fails but stop on an invalid configuration. The binary wraps these
+/// in `anyhow` at the top level.
+#[derive(Debug, Error)]
+pub enum ProteanError {
+    /// Taking a screenshot failed
+    #[error("failed to capture {what}: {cause}")]
+    Capture { what: String, cause: BoxError },
+    /// The OCR engine could not process an image
+    #[error("OCR failed: {0}")]
+    Ocr(BoxError),
+    /// A configuration value is unusable
+    #[error("{0}")]
+    Config(String),
+    /// Querying the compositor for the active window failed
+    #[error("{message}: {cause}")]
+    Window { message: String, cause: BoxError },
+}
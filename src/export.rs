@@ -0,0 +1,84 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::statistics::HuntStats;
+
+/// Session summary written to the JSON export
+#[derive(Debug, Serialize)]
+struct SessionExport {
+    /// Active hunting time in seconds (excluding pauses)
+    hunt_duration_secs: u64,
+    total: usize,
+    encounters_per_hour: Option<f64>,
+    counts: BTreeMap<String, usize>,
+    encounters: Vec<ExportedEncounter>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedEncounter {
+    name: String,
+    /// Active time at which the encounter was counted
+    active_secs: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    area: Option<String>,
+}
+
+/// Write the session's encounters as `<base>.csv` and a summary with the
+/// same encounters as `<base>.json`
+/// 
+/// # Returns
+/// * `Ok((csv_path, json_path))` with the files written
+/// * `Err` if either file can't be written
+pub fn export_session(stats: &HuntStats, active_duration: Duration, base: &Path) -> Result<(PathBuf, PathBuf)> {
+    let view = stats.view();
+    let encounters: Vec<ExportedEncounter> = view
+        .encounter_log
+        .encounters()
+        .iter()
+        .map(|encounter| ExportedEncounter {
+            name: encounter.name.clone(),
+            active_secs: encounter.active_time.as_secs(),
+            area: encounter.area.clone(),
+        })
+        .collect();
+
+    let mut csv = String::from("species,active_secs,area\n");
+    for encounter in &encounters {
+        csv.push_str(&format!(
+            "{},{},{}\n",
+            csv_field(&encounter.name),
+            encounter.active_secs,
+            csv_field(encounter.area.as_deref().unwrap_or_default()),
+        ));
+    }
+    let csv_path = base.with_extension("csv");
+    fs::write(&csv_path, csv)
+        .with_context(|| format!("Failed to write {}", csv_path.display()))?;
+
+    let export = SessionExport {
+        hunt_duration_secs: active_duration.as_secs(),
+        total: view.text_counts.values().sum(),
+        encounters_per_hour: view.encounter_log.session_rate_per_hour(active_duration),
+        counts: view.text_counts.into_iter().collect(),
+        encounters,
+    };
+    let json = serde_json::to_string_pretty(&export).context("Failed to serialize session")?;
+    let json_path = base.with_extension("json");
+    fs::write(&json_path, json)
+        .with_context(|| format!("Failed to write {}", json_path.display()))?;
+
+    Ok((csv_path, json_path))
+}
+
+/// Quote a CSV field if it contains a separator or quote
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,68 @@
+use anyhow::{Context, Result};
+use global_hotkey::hotkey::HotKey;
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+use std::collections::BTreeMap;
+
+use crate::config::Action;
+
+/// Key combinations registered with the desktop that trigger actions while
+/// another window (e.g. the game) has focus
+/// 
+/// Uses X11 key grabs on Linux, so under Wayland the combinations only
+/// reach protean while an XWayland window is focused. A grabbed combination
+/// is swallowed and never reaches the focused window. The combinations are
+/// released when this is dropped.
+pub struct GlobalHotkeys {
+    manager: GlobalHotKeyManager,
+    hotkeys: Vec<(HotKey, Action)>,
+}
+
+impl GlobalHotkeys {
+    /// Register the configured combinations, or return `None` when none are configured
+    pub fn register(bindings: &BTreeMap<Action, String>) -> Result<Option<Self>> {
+        if bindings.is_empty() {
+            return Ok(None);
+        }
+
+        let hotkeys = bindings
+            .iter()
+            .map(|(action, combination)| Ok((parse_hotkey(combination)?, *action)))
+            .collect::<Result<Vec<_>>>()?;
+        let manager = GlobalHotKeyManager::new().context("Failed to start global hotkey handling")?;
+        for (hotkey, action) in &hotkeys {
+            manager
+                .register(*hotkey)
+                .with_context(|| format!("Failed to register global hotkey {} for {:?}", hotkey, action))?;
+        }
+        Ok(Some(Self { manager, hotkeys }))
+    }
+
+    /// The action of a combination pressed since the last poll, if any
+    pub fn poll(&self) -> Option<Action> {
+        while let Ok(event) = GlobalHotKeyEvent::receiver().try_recv() {
+            if event.state() != HotKeyState::Pressed {
+                continue;
+            }
+            if let Some((_, action)) = self.hotkeys.iter().find(|(hotkey, _)| hotkey.id() == event.id()) {
+                return Some(*action);
+            }
+        }
+        None
+    }
+}
+
+impl Drop for GlobalHotkeys {
+    fn drop(&mut self) {
+        let hotkeys: Vec<HotKey> = self.hotkeys.iter().map(|(hotkey, _)| *hotkey).collect();
+        if let Err(e) = self.manager.unregister_all(&hotkeys) {
+            eprintln!("Failed to release global hotkeys: {}", e);
+        }
+    }
+}
+
+/// Parse a combination such as "ctrl+alt+KeyP"
+pub fn parse_hotkey(combination: &str) -> Result<HotKey> {
+    combination
+        .parse()
+        .with_context(|| format!("Invalid global hotkey \"{}\" (expected e.g. \"ctrl+alt+KeyP\")", combination))
+}
@@ -0,0 +1,59 @@
+use image::GrayImage;
+use std::time::{Duration, Instant};
+
+use crate::motion::mean_change;
+
+/// Spots the battle banner arriving from how much the region changes
+/// between frames, before OCR can read the name on it
+/// 
+/// The first change above the threshold while idle is remembered; when a
+/// name is then detected within `max_lead`, that change is taken as the
+/// moment the battle started.
+pub struct IntroDetector {
+    threshold: f32,
+    max_lead: Duration,
+    previous: Option<GrayImage>,
+    arrived_at: Option<Instant>,
+}
+
+impl IntroDetector {
+    pub fn new(threshold: f32, max_lead: Duration) -> Self {
+        Self {
+            threshold,
+            max_lead,
+            previous: None,
+            arrived_at: None,
+        }
+    }
+
+    /// Compare a frame with the previous one
+    /// 
+    /// # Arguments
+    /// * `sample` - The captured region, downscaled by `change_sample`
+    /// * `idle` - Whether no battle is being tracked (changes during a battle are ignored)
+    pub fn observe(&mut self, sample: &GrayImage, idle: bool) {
+        let changed = self.previous.as_ref().is_some_and(|previous| {
+            mean_change(previous, sample).is_some_and(|change| change >= self.threshold)
+        });
+        self.previous = Some(sample.clone());
+
+        if !idle {
+            self.arrived_at = None;
+        } else if changed && self.arrival().is_none() {
+            self.arrived_at = Some(Instant::now());
+        }
+    }
+
+    /// Take the arrival of the banner whose name was just read, if a change
+    /// was seen recently enough to belong to it
+    pub fn take_arrival(&mut self) -> Option<Instant> {
+        let arrival = self.arrival();
+        self.arrived_at = None;
+        arrival
+    }
+
+    /// The pending arrival, unless it's too old to belong to the next detection
+    fn arrival(&self) -> Option<Instant> {
+        self.arrived_at.filter(|arrived_at| arrived_at.elapsed() <= self.max_lead)
+    }
+}
@@ -0,0 +1,85 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::pokemon::normalized_name_map;
+use crate::statistics::{print_count_table, remap_species, HuntStats};
+use crate::ui::print_banner;
+
+/// All-time statistics accumulated across hunt sessions
+///
+/// Counts are stored with the names as each session recorded them (after
+/// any normalization committed during that session) and normalized when
+/// displayed, so a misread that only shows up in a later session still
+/// merges into its species without losing the raw data.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LifetimeStats {
+    /// Encounter counts keyed by recorded name
+    pub text_counts: HashMap<String, usize>,
+    /// Total active hunting time in seconds (excluding pauses)
+    pub hunt_duration_secs: u64,
+    /// Number of sessions merged in
+    pub sessions: u64,
+}
+
+impl LifetimeStats {
+    /// Load lifetime statistics, starting fresh if the file doesn't exist yet
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read lifetime stats {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse lifetime stats {}", path.display()))
+    }
+
+    /// Write lifetime statistics, creating the parent directory if needed
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let json = serde_json::to_string_pretty(self)
+            .context("Failed to serialize lifetime stats")?;
+        fs::write(path, json)
+            .with_context(|| format!("Failed to write lifetime stats {}", path.display()))
+    }
+
+    /// Add a finished session's counts and duration
+    pub fn merge_session(&mut self, stats: &HuntStats, hunt_duration: Duration) {
+        for (name, count) in &stats.text_counts {
+            *self.text_counts.entry(name.clone()).or_insert(0) += count;
+        }
+        self.hunt_duration_secs += hunt_duration.as_secs();
+        self.sessions += 1;
+    }
+
+    /// Print the all-time table with normalized names
+    pub fn print(&self, config: &Config) {
+        print_banner("LIFETIME STATISTICS", config.output_style);
+        println!();
+
+        if self.text_counts.is_empty() {
+            println!("No encounters recorded yet.");
+            return;
+        }
+
+        let name_map = normalized_name_map(&self.text_counts, &config.confusables);
+        let counts = remap_species(&self.text_counts, &name_map);
+        print_count_table(&counts, Duration::from_secs(self.hunt_duration_secs), config.duration_decimals, config.stats_top_n);
+        println!("Sessions: {}", self.sessions);
+    }
+}
+
+/// Merge the session into the lifetime file
+pub fn record_session(stats: &HuntStats, hunt_duration: Duration, path: &Path) -> Result<()> {
+    let mut lifetime = LifetimeStats::load(path)?;
+    lifetime.merge_session(stats, hunt_duration);
+    lifetime.save(path)
+}
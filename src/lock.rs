@@ -0,0 +1,103 @@
+use anyhow::{bail, Context, Result};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+
+const PROC_DIR: &str = "/proc";
+
+/// Lock file held for as long as an instance is monitoring, so a second
+/// instance can't write the same snapshot and lifetime files concurrently
+/// 
+/// The file holds the owner's PID. A lock whose process is gone (e.g. after
+/// a crash) is stale and taken over; it's removed again when dropped.
+pub struct InstanceLock {
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    /// Take the lock in the config folder
+    /// 
+    /// # Arguments
+    /// * `force` - Take over the lock even if another instance holds it
+    pub fn acquire(force: bool) -> Result<Self> {
+        let path = Config::lock_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    write!(file, "{}", std::process::id())
+                        .with_context(|| format!("Failed to write lock file {}", path.display()))?;
+                    return Ok(Self { path });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e).with_context(|| format!("Failed to create lock file {}", path.display())),
+            }
+
+            match running_instance_at(&path)? {
+                Some(pid) if !force => bail!(
+                    "Another protean instance (PID {}) is already running. Close it first, or \
+                     start with --force to take over its lock file {}",
+                    pid, path.display()),
+                Some(pid) => println!("⚠ Taking over the lock held by protean PID {}", pid),
+                None => println!("Removing stale lock file left by a previous run"),
+            }
+            match fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(e).with_context(|| format!("Failed to remove lock file {}", path.display())),
+            }
+        }
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // Leave the file alone if another instance took it over with --force
+        let owned = fs::read_to_string(&self.path)
+            .is_ok_and(|contents| contents.trim() == std::process::id().to_string());
+        if owned && let Err(e) = fs::remove_file(&self.path) {
+            eprintln!("Failed to remove lock file {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// PID of the live instance holding the lock file, if any
+pub fn running_instance() -> Result<Option<u32>> {
+    running_instance_at(&Config::lock_path()?)
+}
+
+fn running_instance_at(path: &Path) -> Result<Option<u32>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read lock file {}", path.display())),
+    };
+    // A lock that can't be parsed was cut short by a crash mid-write
+    let Ok(pid) = contents.trim().parse::<u32>() else {
+        return Ok(None);
+    };
+    Ok(is_protean_process(pid).then_some(pid))
+}
+
+/// Whether `pid` is a live process running this same program
+/// 
+/// Comparing the process name guards against the PID having been reused
+/// since a crash. Without `/proc` the lock is assumed to be live.
+fn is_protean_process(pid: u32) -> bool {
+    let proc_dir = Path::new(PROC_DIR);
+    if !proc_dir.exists() {
+        return true;
+    }
+    let name = |process: &str| fs::read_to_string(proc_dir.join(process).join("comm")).ok();
+    match (name(&pid.to_string()), name("self")) {
+        (Some(other), Some(own)) => other == own,
+        (None, _) => false,
+        (Some(_), None) => true,
+    }
+}
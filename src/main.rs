@@ -1,31 +1,114 @@
 use anyhow::{Context, Result};
-use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use clap::Parser;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use image::{DynamicImage, RgbImage};
 use ocrs::{OcrEngine, OcrEngineParams};
 use rten::Model;
 use screenshots::Screen;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+mod autodetect;
+mod binlog;
+mod calibrate;
+mod capture;
+mod card;
+mod cli;
+mod clock;
 mod config;
+mod control;
+mod daemon;
+mod debug;
+mod error;
+mod hotkeys;
+mod export;
+mod intro;
+mod lifetime;
+mod lock;
+mod motion;
+mod notify;
 mod ocr;
+mod pokedex;
 mod pokemon;
+mod reset;
+mod rules;
+mod select;
+mod sink;
+mod species;
 mod statistics;
+mod tui;
 mod ui;
 mod window;
 
-use config::Config;
-use ocr::{capture_region, OcrProvider, StandardOcrProvider};
-use pokemon::{extract_pokemon_name, normalize_pokemon_names};
-use statistics::print_statistics;
-use ui::show_help;
-use window::check_active_window;
+use autodetect::autodetect_region;
+use binlog::dump_csv;
+use calibrate::calibrate;
+use capture::{build_capture_provider, fit_regions_to_screen, list_monitors, select_screen, CaptureProvider, FrameCapture};
+use card::{export_image, text_image};
+use cli::CliArgs;
+use clock::{Clock, SystemClock};
+use config::{Action, CaptureBackend, Config, DetectionStrategy, MonitorTarget, OcrBackend};
+use control::{ControlCommand, ControlSocket};
+use daemon::Daemon;
+use debug::{read_session_counts, save_heat_map, session_frame_name, FrameHistory, SessionRecorder, SESSION_SCRIPT_FILE_NAME};
+use export::export_session;
+use hotkeys::GlobalHotkeys;
+use intro::IntroDetector;
+use lifetime::{record_session, LifetimeStats};
+use lock::InstanceLock;
+use motion::{change_sample, OcrGate};
+use notify::{beep_count, Notifier};
+use ocr::{
+    extract_text_from_image, read_replay_script, upscale, MockOcrProvider, OcrProvider, StandardOcrProvider,
+    TesseractOcrProvider,
+};
+use pokedex::{snap_to_pokedex, Pokedex};
+use pokemon::{
+    classify_outcome, compose_name, contains_any_phrase, detect_shiny, extract_encounter_name, extract_level, BattleOutcome,
+    ShinyStatus,
+};
+use reset::reset;
+use rules::{export_rules, import_rules};
+use select::select_region;
+use sink::{build_sinks, CountEvent, CountSink};
+use species::SpeciesList;
+use statistics::{export_statistics, format_duration, load_session, save_session, HuntStats, StatsFormat};
+use tui::{Dashboard, DashboardView, RegionPreview, PREVIEW_COLUMNS};
+use ui::{ask_yes_no, print_banner, print_region_preview, region_thumbnail, show_help};
+use window::WindowDetector;
 
 // Constants for timing and thresholds
-const PAUSE_POLL_INTERVAL_MS: u64 = 100;
-const MIN_TEXT_LENGTH_TO_LOG: usize = 10;
-const STARTUP_DELAY_SECONDS: u64 = 3;
+/// Longest wait for a captured frame before checking input again
+const FRAME_WAIT_TIMEOUT_MS: u64 = 100;
+
+/// Species listed when a manually entered prefix matches several
+const MANUAL_ENTRY_MAX_SUGGESTIONS: usize = 8;
+
+// OCR model locations
+const MODEL_CACHE_DIR: &str = ".cache/ocrs";
+const DETECTION_MODEL_FILE: &str = "text-detection.rten";
+const RECOGNITION_MODEL_FILE: &str = "text-recognition.rten";
+const MODEL_DOWNLOAD_URL: &str = "https://ocrs-models.s3-accelerate.amazonaws.com";
+
+// Text rendered to warm up the OCR engine, and the scale and padding in
+// pixels it is rendered at
+const WARMUP_TEXT: &str = "VS. Wild Pidgey";
+const WARMUP_TEXT_SCALE: u32 = 4;
+const WARMUP_TEXT_PADDING: u32 = 16;
+
+// The live region preview is refreshed every this many frames
+const PREVIEW_EVERY_FRAMES: u32 = 10;
+
+// A battle end interrupted this many times by stale reads is considered stuck
+const MIN_STUCK_ENDING_OSCILLATIONS: u32 = 3;
+
+// Constants for OCR throughput tracking
+const OCR_TIMING_SMOOTHING: f64 = 0.1;
+const OCR_TIMING_MIN_SAMPLES: u32 = 20;
 
 /// Battle detection states
 #[derive(Debug, Clone, PartialEq)]
@@ -33,31 +116,87 @@ enum BattlePhase {
     /// Not in battle, waiting for pokemon detection
     Idle,
     /// Pokemon detected via "VS. Wild \[name\]" pattern
-    PokemonDetected { name: String },
+    PokemonDetected {
+        name: String,
+        /// Consecutive reads of this name so far, including the first
+        reads: u32,
+    },
     /// Battle is active, monitoring for end
     BattleActive { name: String },
     /// Battle ending, waiting to count
-    BattleEnding { name: String, empty_count: u32 },
+    BattleEnding {
+        name: String,
+        empty_count: u32,
+        /// When the battle entered the ending phase
+        since: Instant,
+        /// Times a stale read of the name interrupted the empty frames
+        oscillations: u32,
+    },
+}
+
+impl BattlePhase {
+    /// Whether this phase is a detection of a different pokemon than `previous`
+    /// was tracking (a first read, not a repeat of an ongoing detection)
+    fn is_new_detection(&self, previous: &BattlePhase) -> bool {
+        match (self, previous) {
+            (BattlePhase::PokemonDetected { name, .. }, BattlePhase::PokemonDetected { name: previous, .. }) => {
+                name != previous
+            }
+            (BattlePhase::PokemonDetected { .. }, _) => true,
+            _ => false,
+        }
+    }
+
+    /// Short description of the phase for the dashboard
+    fn label(&self) -> String {
+        match self {
+            BattlePhase::Idle => "Waiting for a battle".to_string(),
+            BattlePhase::PokemonDetected { name, reads } => format!("Detected {} ({} reads)", name, reads),
+            BattlePhase::BattleActive { name } => format!("In battle with {}", name),
+            BattlePhase::BattleEnding { name, empty_count, .. } => {
+                format!("Battle with {} ending ({} empty frames)", name, empty_count)
+            }
+        }
+    }
 }
 
 /// Manages pause state and duration tracking
-struct PauseManager {
+/// 
+/// Manual and window pauses may overlap; paused time is counted once, from
+/// when the first pause started until neither is active.
+struct PauseManager<C: Clock = SystemClock> {
+    clock: C,
     manual_pause: bool,
     window_pause: bool,
     total_paused_duration: Duration,
     pause_start: Option<Instant>,
+    /// When the monitored windows lost focus, while still within the grace period
+    unfocused_since: Option<Instant>,
+    /// How long focus may be lost before the window pause starts
+    window_pause_grace: Duration,
+    /// Active time carried over from a resumed session
+    resumed_duration: Duration,
 }
 
-impl PauseManager {
-    fn new() -> Self {
+impl<C: Clock> PauseManager<C> {
+    fn new(window_pause_grace: Duration, clock: C) -> Self {
         Self {
+            clock,
             manual_pause: false,
             window_pause: false,
             total_paused_duration: Duration::ZERO,
             pause_start: None,
+            unfocused_since: None,
+            window_pause_grace,
+            resumed_duration: Duration::ZERO,
         }
     }
 
+    /// Count `duration` of earlier active time, from a resumed session
+    fn resume_from(&mut self, duration: Duration) {
+        self.resumed_duration = duration;
+    }
+
     fn is_paused(&self) -> bool {
         self.manual_pause || self.window_pause
     }
@@ -73,10 +212,31 @@ impl PauseManager {
         }
     }
 
+    fn set_manual_pause(&mut self, paused: bool) {
+        if paused != self.manual_pause {
+            self.toggle_manual_pause();
+        }
+    }
+
+    /// Update the window pause from the focus state
+    /// 
+    /// Focus has to stay lost for the grace period before pausing; the pause
+    /// is then backdated to when focus was lost so the time isn't counted.
     fn set_window_pause(&mut self, paused: bool) {
+        if !paused {
+            self.unfocused_since = None;
+        }
+
         if paused && !self.window_pause {
+            let now = self.clock.now();
+            let unfocused_since = *self.unfocused_since.get_or_insert(now);
+            if self.clock.since(unfocused_since) < self.window_pause_grace {
+                return;
+            }
             self.window_pause = true;
-            self.start_pause();
+            if self.pause_start.is_none() {
+                self.pause_start = Some(unfocused_since);
+            }
             println!("\n⏸  Auto-paused (window not focused)");
         } else if !paused && self.window_pause {
             self.window_pause = false;
@@ -85,269 +245,1994 @@ impl PauseManager {
         }
     }
 
+    /// Drop any window pause without announcing a resume (window detection turned off)
+    fn clear_window_pause(&mut self) {
+        self.unfocused_since = None;
+        if self.window_pause {
+            self.window_pause = false;
+            self.end_pause();
+        }
+    }
+
     fn start_pause(&mut self) {
         if self.pause_start.is_none() {
-            self.pause_start = Some(Instant::now());
+            self.pause_start = Some(self.clock.now());
         }
     }
 
+    /// Close the current pause unless the other kind of pause still holds it open
     fn end_pause(&mut self) {
+        if self.is_paused() {
+            return;
+        }
         if let Some(pause_time) = self.pause_start.take() {
-            self.total_paused_duration += pause_time.elapsed();
+            self.total_paused_duration += self.clock.since(pause_time);
         }
     }
 
     fn active_duration(&self, start_time: Instant) -> Duration {
-        let mut duration = start_time.elapsed() - self.total_paused_duration;
+        let mut duration = self.clock.since(start_time).saturating_sub(self.total_paused_duration);
         // Account for currently active pause
         if let Some(pause_time) = self.pause_start {
-            duration -= pause_time.elapsed();
+            duration = duration.saturating_sub(self.clock.since(pause_time));
         }
-        duration
+        self.resumed_duration + duration
     }
 }
 
+/// Receives battle state changes, keeping presentation out of the state machine
+trait BattleObserver {
+    /// Called when the phase changes or a battle is counted
+    /// 
+    /// # Arguments
+    /// * `from` - Phase before the frame was processed
+    /// * `to` - Phase after the frame was processed
+    /// * `text` - The OCR text that caused the transition
+    /// * `counted` - The battle counted by this transition, if any
+    fn on_transition(&mut self, from: &BattlePhase, to: &BattlePhase, text: &str, counted: Option<&CountedBattle>);
+
+    /// Called for new readable text that didn't match the pattern while idle
+    fn on_ignored(&mut self, _text: &str, _pattern_label: &str) {}
+}
+
+/// Prints log lines, collapsing consecutive repeats of the same line
+/// 
+/// Long battles re-read the same banner every frame; repeats are dropped
+/// and, in verbose mode, summarized once a different line is printed.
+struct DedupLog {
+    last_line: Option<String>,
+    repeats: u32,
+    verbose: bool,
+}
+
+impl DedupLog {
+    fn new(verbose: bool) -> Self {
+        Self {
+            last_line: None,
+            repeats: 0,
+            verbose,
+        }
+    }
+
+    fn print(&mut self, line: String) {
+        if self.last_line.as_deref() == Some(line.as_str()) {
+            self.repeats += 1;
+            return;
+        }
+
+        if self.repeats > 0 && self.verbose {
+            println!("  (repeated {} more times)", self.repeats);
+        }
+        self.repeats = 0;
+        println!("{}", line);
+        self.last_line = Some(line);
+    }
+}
+
+/// Observer printing battle progress to the console
+struct ConsoleObserver {
+    log: DedupLog,
+}
+
+impl BattleObserver for ConsoleObserver {
+    fn on_transition(&mut self, from: &BattlePhase, to: &BattlePhase, text: &str, counted: Option<&CountedBattle>) {
+        if let BattlePhase::PokemonDetected { name, .. } = to
+            && to.is_new_detection(from)
+        {
+            self.log.print(format!("⏳ Detected: \"{}\" from \"{}\"", name, text));
+        }
+        if let Some(battle) = counted {
+            match &battle.end_message {
+                Some(message) => self.log.print(format!("[Battle ended: \"{}\" - ready for next encounter]", message)),
+                None => self.log.print("[Battle ended - ready for next encounter]".to_string()),
+            }
+        }
+    }
+
+    fn on_ignored(&mut self, text: &str, pattern_label: &str) {
+        self.log.print(format!("✗ Ignored (no '{}' pattern): \"{}\"", pattern_label, text));
+    }
+}
+
+/// Observer ignoring every transition, for re-deriving counts quietly
+struct SilentObserver;
+
+impl BattleObserver for SilentObserver {
+    fn on_transition(&mut self, _from: &BattlePhase, _to: &BattlePhase, _text: &str, _counted: Option<&CountedBattle>) {}
+}
+
 /// Tracks the state of battle detection using explicit state machine
 struct BattleState {
     phase: BattlePhase,
     last_text: String,
+    /// Most significant outcome read during the current battle
+    outcome: Option<BattleOutcome>,
+    /// When the current pokemon was first detected
+    detected_at: Option<Instant>,
+    /// When the current battle last entered its ending phase
+    ending_at: Option<Instant>,
+    /// Level read for the current pokemon (level tracking)
+    level: Option<u32>,
+    /// Whether the shiny pattern was read since the pokemon was detected;
+    /// kept when later frames lose it
+    shiny: ShinyStatus,
+    /// Notified of every phase transition
+    observer: Box<dyn BattleObserver>,
+}
+
+/// A completed battle ready to be counted
+struct CountedBattle {
+    name: String,
+    outcome: Option<BattleOutcome>,
+    /// Time from detection until the banner disappeared (or an end message);
+    /// `None` for manually entered encounters
+    duration: Option<Duration>,
+    /// Battle log message that ended the battle (battle log strategy)
+    end_message: Option<String>,
+    /// Level read from the banner, when level tracking is enabled
+    level: Option<u32>,
+    shiny: ShinyStatus,
 }
 
 impl BattleState {
-    fn new() -> Self {
+    fn new(observer: Box<dyn BattleObserver>) -> Self {
         Self {
             phase: BattlePhase::Idle,
             last_text: String::new(),
+            outcome: None,
+            detected_at: None,
+            ending_at: None,
+            level: None,
+            shiny: ShinyStatus::Normal,
+            observer,
         }
     }
 
     fn reset(&mut self) {
         self.phase = BattlePhase::Idle;
         self.last_text.clear();
+        self.outcome = None;
+        self.detected_at = None;
+        self.ending_at = None;
+        self.level = None;
+        self.shiny = ShinyStatus::Normal;
+    }
+
+    /// Enter the detected phase for a newly seen pokemon
+    fn start_detection(&mut self, name: String, text: &str) {
+        self.phase = BattlePhase::PokemonDetected { name, reads: 1 };
+        self.last_text = text.to_string();
+        self.outcome = None;
+        self.detected_at = Some(Instant::now());
+        self.ending_at = None;
+        self.level = None;
+        self.shiny = ShinyStatus::Normal;
+    }
+
+    /// Replace the name of the pokemon being detected with a better read
+    fn rename_detection(&mut self, settled: String) {
+        if let BattlePhase::PokemonDetected { name, .. } = &mut self.phase
+            && *name != settled
+        {
+            println!("  ↻ Settled read: \"{}\" -> \"{}\"", name, settled);
+            *name = settled;
+        }
+    }
+
+    /// Enter the ending phase, remembering when the banner disappeared
+    fn start_ending(&mut self, name: &str) {
+        let now = Instant::now();
+        self.phase = BattlePhase::BattleEnding { name: name.to_string(), empty_count: 1, since: now, oscillations: 0 };
+        self.ending_at = Some(now);
+    }
+
+    /// Return to idle and produce the battle to count
+    fn finish_battle(&mut self, name: String) -> CountedBattle {
+        let ended_at = self.ending_at.take().unwrap_or_else(Instant::now);
+        let duration = self.detected_at.take()
+            .map_or(Duration::ZERO, |detected_at| ended_at.saturating_duration_since(detected_at));
+        self.phase = BattlePhase::Idle;
+        CountedBattle {
+            name,
+            outcome: self.outcome.take(),
+            duration: Some(duration),
+            end_message: None,
+            level: self.level.take(),
+            shiny: std::mem::take(&mut self.shiny),
+        }
+    }
+
+    /// Whether a battle is currently being tracked
+    fn in_battle(&self) -> bool {
+        self.phase != BattlePhase::Idle
+    }
+
+    /// Remember the outcome read for the current battle
+    /// 
+    /// A caught or defeated result is never downgraded by a later "fled"
+    /// read, since the battle log keeps scrolling after the decisive message.
+    fn record_outcome(&mut self, outcome: BattleOutcome) {
+        if self.outcome.is_none() || outcome != BattleOutcome::Fled {
+            self.outcome = Some(outcome);
+        }
     }
     
-    /// Update state based on OCR text and return whether to count the pokemon
-    fn update(&mut self, text: &str, config: &Config) -> Option<String> {
-        let pokemon_in_text = extract_pokemon_name(text);
+    /// Update state based on OCR text and return the battle to count, if any
+    /// 
+    /// The observer is notified when the phase changes or a battle is counted.
+    /// With `snap_max_distance` set, misread names are snapped to `species`
+    /// before they're compared with the battle's name.
+    fn update(&mut self, text: &str, species: &SpeciesList, config: &Config) -> Option<CountedBattle> {
+        // Very short reads are garbled noise; treat them as if nothing was read
+        let text = if text.trim().chars().count() < config.min_detection_length { "" } else { text };
+        // Detection may depend on line breaks (pattern_anchor); everything else reads one line
+        let pokemon_in_text = extract_encounter_name(text, config).map(|name| match config.snap_max_distance {
+            Some(max_distance) if !species.contains(&name) => {
+                snap_to_pokedex(&name, species.names(), max_distance).unwrap_or(name)
+            }
+            _ => name,
+        });
+        let text = &text.replace('\n', " ");
+
+        let previous = self.phase.clone();
+        let counted = self.advance(text, pokemon_in_text, species, config);
+        if config.track_levels
+            && self.level.is_none()
+            && matches!(self.phase, BattlePhase::PokemonDetected { .. } | BattlePhase::BattleActive { .. })
+        {
+            self.level = extract_level(text);
+        }
+        if let Some(pattern) = &config.shiny_pattern
+            && matches!(self.phase, BattlePhase::PokemonDetected { .. } | BattlePhase::BattleActive { .. })
+            && detect_shiny(text, pattern) == ShinyStatus::Shiny
+        {
+            self.shiny = ShinyStatus::Shiny;
+        }
+        if counted.is_some() || self.phase != previous {
+            self.observer.on_transition(&previous, &self.phase, text, counted.as_ref());
+        }
+        counted
+    }
+
+    /// Apply one frame of OCR text to the state machine
+    fn advance(&mut self, text: &str, pokemon_in_text: Option<String>, species: &SpeciesList, config: &Config) -> Option<CountedBattle> {
+        let frame_empty = pokemon_in_text.is_none()
+            && config.empty_detection.is_empty(text, config.blank_text_max_length);
+        let end_event = config.detection_strategy == DetectionStrategy::BattleLog
+            && contains_any_phrase(text, &config.battle_log_end_phrases);
+
+        // An explicit end message (battle log strategy) counts immediately
+        if end_event
+            && let BattlePhase::PokemonDetected { name, .. }
+            | BattlePhase::BattleActive { name }
+            | BattlePhase::BattleEnding { name, .. } = &self.phase
+        {
+            let counted_name = name.clone();
+            self.last_text = text.to_string();
+            let mut battle = self.finish_battle(counted_name);
+            battle.end_message = Some(text.to_string());
+            return Some(battle);
+        }
         
         match &self.phase {
             BattlePhase::Idle => {
                 if let Some(pokemon_name) = pokemon_in_text {
-                    println!("⏳ Detected: \"{}\" from \"{}\"", pokemon_name, text);
-                    self.phase = BattlePhase::PokemonDetected { name: pokemon_name };
-                    self.last_text = text.to_string();
-                } else if text != self.last_text && text.len() >= MIN_TEXT_LENGTH_TO_LOG {
-                    println!("✗ Ignored (no 'VS. Wild' pattern): \"{}\"", text);
+                    self.start_detection(pokemon_name, text);
+                } else if text != self.last_text && text.chars().count() >= config.min_log_length {
+                    self.observer.on_ignored(text, config.pattern_label());
                     self.last_text = text.to_string();
                 }
                 None
             }
             
-            BattlePhase::PokemonDetected { name } => {
+            BattlePhase::PokemonDetected { name, reads } => {
                 if let Some(new_name) = pokemon_in_text {
                     if &new_name != name {
                         // Different pokemon detected, transition to new detection
-                        println!("⏳ Detected: \"{}\" from \"{}\"", new_name, text);
-                        self.phase = BattlePhase::PokemonDetected { name: new_name };
-                    } else {
-                        // Same pokemon, transition to active battle
+                        self.start_detection(new_name, text);
+                    } else if reads + 1 >= config.active_confirm_reads {
+                        // Same pokemon read enough times, transition to active battle
                         self.phase = BattlePhase::BattleActive { name: name.clone() };
+                        self.last_text = text.to_string();
+                    } else {
+                        self.phase = BattlePhase::PokemonDetected { name: name.clone(), reads: reads + 1 };
+                        self.last_text = text.to_string();
                     }
-                    self.last_text = text.to_string();
-                } else {
+                } else if frame_empty {
                     // No pokemon detected, start counting empties
-                    self.phase = BattlePhase::BattleEnding { name: name.clone(), empty_count: 1 };
+                    let name = name.clone();
+                    self.start_ending(&name);
                 }
                 None
             }
             
             BattlePhase::BattleActive { name } => {
-                if pokemon_in_text.is_none() {
+                if frame_empty {
                     // Battle ending, start counting
-                    self.phase = BattlePhase::BattleEnding { name: name.clone(), empty_count: 1 };
-                } else {
+                    let name = name.clone();
+                    self.start_ending(&name);
+                } else if pokemon_in_text.is_some() {
                     self.last_text = text.to_string();
                 }
                 None
             }
             
-            BattlePhase::BattleEnding { name, empty_count } => {
-                if let Some(new_name) = pokemon_in_text {
-                    // New pokemon detected during ending phase
-                    println!("⏳ Detected: \"{}\" from \"{}\"", new_name, text);
-                    self.phase = BattlePhase::PokemonDetected { name: new_name };
-                    self.last_text = text.to_string();
-                    None
-                } else {
-                    let new_count = empty_count + 1;
-                    if new_count >= config.empty_threshold {
-                        // Battle confirmed ended, count the pokemon
-                        let counted_name = name.clone();
-                        println!("[Battle ended - ready for next encounter]");
-                        self.phase = BattlePhase::Idle;
-                        self.last_text.clear();
-                        Some(counted_name)
-                    } else {
-                        // Keep counting
-                        self.phase = BattlePhase::BattleEnding { name: name.clone(), empty_count: new_count };
-                        None
+            BattlePhase::BattleEnding { name, empty_count, since, oscillations } => {
+                let (name, since) = (name.clone(), *since);
+                let (mut empty_count, mut oscillations) = (*empty_count, *oscillations);
+
+                match pokemon_in_text {
+                    Some(read) if is_stale_read(&read, &name, species) => {
+                        if empty_count == 0 {
+                            if read == name {
+                                // The banner is back for a second frame; the battle goes on
+                                self.phase = BattlePhase::BattleActive { name };
+                                return None;
+                            }
+                        } else {
+                            // A leftover read between empty frames; keep ending
+                            oscillations += 1;
+                            empty_count = 0;
+                        }
+                    }
+                    Some(new_name) => {
+                        // New pokemon detected during ending phase; the last
+                        // battle's banner is gone, so it's over
+                        let battle = self.finish_battle(name);
+                        self.start_detection(new_name, text);
+                        return Some(battle);
                     }
+                    // Text is present but unreadable, hold until it clears
+                    None if !frame_empty => {}
+                    None => {
+                        empty_count += 1;
+                        if empty_count >= config.empty_threshold {
+                            // Battle confirmed ended, count the pokemon
+                            self.last_text.clear();
+                            return Some(self.finish_battle(name));
+                        }
+                    }
+                }
+
+                if config.stuck_ending_secs > 0
+                    && oscillations >= MIN_STUCK_ENDING_OSCILLATIONS
+                    && since.elapsed() >= Duration::from_secs(config.stuck_ending_secs)
+                {
+                    println!("⚠ Battle end flickered {} times over {}s without settling - counting \"{}\"",
+                             oscillations, since.elapsed().as_secs(), name);
+                    self.last_text.clear();
+                    return Some(self.finish_battle(name));
                 }
+                self.phase = BattlePhase::BattleEnding { name, empty_count, since, oscillations };
+                None
             }
         }
     }
 }
 
+/// Whether a read during a battle's end is the fading banner of `name`: the
+/// name itself or a cut-off start of it
+/// 
+/// A start that is a species of its own (Pidgeot after Pidgeotto, Mew after
+/// Mewtwo) is a new encounter, not a fading one.
+fn is_stale_read(read: &str, name: &str, species: &SpeciesList) -> bool {
+    let (read, name) = (read.to_lowercase(), name.to_lowercase());
+    read == name || (!read.is_empty() && !species.contains(&read) && name.starts_with(&read))
+}
+
+/// Monitoring state for a single game client
+struct TargetMonitor {
+    target: MonitorTarget,
+    battle_state: BattleState,
+    stats: HuntStats,
+    focused: bool,
+    /// Recent frames kept for anomaly dumps
+    frame_history: FrameHistory,
+    /// Whether the current ending phase's anomaly has already been dumped
+    anomaly_dumped: bool,
+    /// Size of this client's window when it was last focused
+    window_size: Option<(i32, i32)>,
+    /// Verbose log of suppressed frames
+    suppressed_log: DedupLog,
+    /// Save a confidence heat map of the next frame
+    heat_map_requested: bool,
+    /// Frames until the region preview is refreshed again (`None` when off)
+    preview_in: Option<u32>,
+    /// Latest region thumbnail shown on the dashboard
+    preview_thumbnail: Option<RgbImage>,
+    /// Saves every frame for later verification (`record_session_frames`)
+    recorder: Option<SessionRecorder>,
+    /// Dates battles from the banner's arrival (`intro_change_threshold`)
+    intro: Option<IntroDetector>,
+    /// Skips OCR on frames that barely changed (`ocr_change_threshold`)
+    ocr_gate: Option<OcrGate>,
+}
+
+impl TargetMonitor {
+    fn new(target: MonitorTarget, config: &Config) -> Self {
+        Self {
+            target,
+            battle_state: BattleState::new(Box::new(ConsoleObserver { log: DedupLog::new(config.verbose) })),
+            stats: HuntStats::new(config),
+            focused: true,
+            frame_history: FrameHistory::new(config.anomaly_frame_history),
+            anomaly_dumped: false,
+            window_size: None,
+            suppressed_log: DedupLog::new(config.verbose),
+            heat_map_requested: false,
+            preview_in: None,
+            preview_thumbnail: None,
+            recorder: None,
+            intro: config.intro_change_threshold.map(|threshold| {
+                IntroDetector::new(threshold, Duration::from_millis(config.intro_max_lead_ms))
+            }),
+            ocr_gate: config.ocr_change_threshold.map(OcrGate::new),
+        }
+    }
+
+    /// Dump recent frames if a battle started ending but never got counted
+    /// within the anomaly timeout (e.g. OCR flickering between empty and a name),
+    /// along with a confidence heat map of the latest frame
+    /// 
+    /// The timeout runs from when the battle last entered its ending phase;
+    /// a banner that comes back mid-battle ends that phase and the timeout.
+    fn check_anomaly(&mut self, ocr_provider: &dyn OcrProvider, config: &Config) {
+        let BattlePhase::BattleEnding { since: ending_since, .. } = self.battle_state.phase else {
+            self.anomaly_dumped = false;
+            return;
+        };
+        if self.anomaly_dumped
+            || !self.frame_history.is_enabled()
+            || ending_since.elapsed() < Duration::from_secs(config.anomaly_timeout_secs)
+        {
+            return;
+        }
+
+        self.anomaly_dumped = true;
+        let dumped = Config::debug_dir().and_then(|dir| self.frame_history.dump(&dir, &self.target.name));
+        let dump_dir = match dumped {
+            Ok(path) => {
+                println!("⚠ Battle did not end cleanly - recent frames saved to: {}", path.display());
+                path
+            }
+            Err(e) => {
+                eprintln!("Failed to dump debug frames: {:#}", e);
+                return;
+            }
+        };
+
+        if let Some(frame) = self.frame_history.latest() {
+            let saved = ocr_provider
+                .confidence_heat_map(frame, config.preprocess_images)
+                .map_err(anyhow::Error::from)
+                .and_then(|heat_map| save_heat_map(&heat_map, &dump_dir, &self.target.name));
+            if let Err(e) = saved {
+                eprintln!("Failed to save heat map: {:#}", e);
+            }
+        }
+    }
+
+    /// Save a confidence heat map of `image` to the debug folder
+    fn export_heat_map(&self, ocr_provider: &dyn OcrProvider, image: &DynamicImage, config: &Config) {
+        let saved = ocr_provider
+            .confidence_heat_map(image, config.preprocess_images)
+            .map_err(anyhow::Error::from)
+            .and_then(|heat_map| save_heat_map(&heat_map, &Config::debug_dir()?, &self.target.name));
+        match saved {
+            Ok(path) => println!("✓ Heat map saved to: {}", path.display()),
+            Err(e) => eprintln!("Failed to save heat map: {:#}", e),
+        }
+    }
+}
+
+/// Combined statistics across all monitored targets
+fn combined_stats(monitors: &[TargetMonitor]) -> HuntStats {
+    let all_stats: Vec<&HuntStats> = monitors.iter().map(|m| &m.stats).collect();
+    HuntStats::combined(&all_stats)
+}
+
+/// Continue a saved session in the monitors, returning its active time
+/// 
+/// Targets are matched by name; counts saved for a target that's no longer
+/// configured are left out.
+fn resume_session(path: &Path, monitors: &mut [TargetMonitor]) -> Result<Option<Duration>> {
+    let Some(mut session) = load_session(path)? else {
+        return Ok(None);
+    };
+    for monitor in monitors.iter_mut() {
+        if let Some(saved) = session.targets.remove(&monitor.target.name) {
+            monitor.stats.restore(saved);
+        }
+    }
+    for name in session.targets.keys() {
+        eprintln!("⚠ Saved session has counts for target '{}', which is no longer configured", name);
+    }
+    Ok(Some(Duration::from_millis(session.active_ms)))
+}
+
+/// Save every target's statistics so the session can be resumed
+fn save_monitors_session(path: &Path, monitors: &[TargetMonitor], active_duration: Duration) {
+    let targets: Vec<(&str, &HuntStats)> = monitors
+        .iter()
+        .map(|monitor| (monitor.target.name.as_str(), &monitor.stats))
+        .collect();
+    if let Err(e) = save_session(path, &targets, active_duration) {
+        eprintln!("Failed to save session: {:#}", e);
+    }
+}
+
+/// Print combined statistics, followed by a breakdown per target when
+/// more than one client is monitored
+fn print_all_statistics(monitors: &[TargetMonitor], active_duration: Duration) {
+    if let [monitor] = monitors {
+        monitor.stats.print(active_duration);
+        return;
+    }
+
+    println!("\n=== All targets ===");
+    combined_stats(monitors).print(active_duration);
+    for monitor in monitors {
+        println!("\n=== Target: {} ===", monitor.target.name);
+        monitor.stats.print(active_duration);
+    }
+}
+
 enum KeyAction {
     Continue,
+    /// Prompt for an encounter name and count it
+    ManualEntry,
+    /// Ask whether to quit, export the session and record lifetime stats
+    ConfirmQuit,
     Quit,
 }
 
 /// Handle keyboard input and return action
 fn handle_keyboard_input(
+    dashboard: Option<&mut Dashboard>,
     pause_manager: &mut PauseManager,
-    battle_state: &mut BattleState,
-    text_counts: &mut HashMap<String, usize>,
+    monitors: &mut [TargetMonitor],
+    bindings: &[(Action, char)],
+    config: &mut Config,
     start_time: Instant,
 ) -> Result<KeyAction> {
     if !event::poll(Duration::from_millis(0))? {
         return Ok(KeyAction::Continue);
     }
 
-    if let Event::Key(KeyEvent { code, .. }) = event::read()? {
-        match code {
-            KeyCode::Char('p') | KeyCode::Char('P') => {
-                pause_manager.toggle_manual_pause();
+    let Event::Key(KeyEvent { code: KeyCode::Char(pressed), modifiers, .. }) = event::read()? else {
+        return Ok(KeyAction::Continue);
+    };
+    // The dashboard's raw mode turns Ctrl-C into a key press
+    if modifiers.contains(KeyModifiers::CONTROL) && pressed.eq_ignore_ascii_case(&'c') {
+        return perform_key_action(Action::Quit, dashboard, pause_manager, monitors, bindings, config, start_time);
+    }
+    let action = bindings
+        .iter()
+        .find(|(_, key)| key.eq_ignore_ascii_case(&pressed))
+        .map(|(action, _)| *action);
+
+    match action {
+        Some(action) => perform_key_action(action, dashboard, pause_manager, monitors, bindings, config, start_time),
+        None => Ok(KeyAction::Continue),
+    }
+}
+
+/// Run an action triggered by a key (terminal or global hotkey)
+/// 
+/// Actions that prompt get the plain terminal back from the dashboard while
+/// they ask.
+fn perform_key_action(
+    action: Action,
+    mut dashboard: Option<&mut Dashboard>,
+    pause_manager: &mut PauseManager,
+    monitors: &mut [TargetMonitor],
+    bindings: &[(Action, char)],
+    config: &mut Config,
+    start_time: Instant,
+) -> Result<KeyAction> {
+    let prompts = action == Action::ExportStats;
+    if prompts && let Some(dashboard) = dashboard.as_deref_mut() {
+        dashboard.suspend()?;
+    }
+    let key_action = perform_action(action, pause_manager, monitors, bindings, config, start_time);
+    if prompts && let Some(dashboard) = dashboard {
+        dashboard.resume()?;
+    }
+    match key_action? {
+        // Quitting over the control socket is scripted and never asks
+        KeyAction::Quit if config.confirm_quit => Ok(KeyAction::ConfirmQuit),
+        key_action => Ok(key_action),
+    }
+}
+
+/// Confirm quitting, offering to export the session first
+/// 
+/// # Returns
+/// * `Ok(None)` if the hunt should go on
+/// * `Ok(Some(record_lifetime))` to quit, with whether to add the session
+///   to the lifetime statistics
+fn prompt_quit(stats: &HuntStats, active_duration: Duration) -> Result<Option<bool>> {
+    if !ask_yes_no("\nQuit and end the session?")? {
+        return Ok(None);
+    }
+
+    if ask_yes_no("Export the session to CSV and JSON?")? {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let base = PathBuf::from(format!("protean-session-{}", timestamp));
+        match export_session(stats, active_duration, &base) {
+            Ok((csv_path, json_path)) => {
+                println!("✓ Session saved to: {} and {}", csv_path.display(), json_path.display());
             }
-            KeyCode::Char('r') | KeyCode::Char('R') => {
-                text_counts.clear();
-                battle_state.reset();
-                println!("\n=> RESTARTED - All statistics cleared");
+            Err(e) => eprintln!("Session export error: {:#}", e),
+        }
+    }
+
+    ask_yes_no("Add the session to the lifetime statistics?").map(Some)
+}
+
+/// Handle commands received on the control socket
+fn handle_control_commands(
+    control: &ControlSocket,
+    pause_manager: &mut PauseManager,
+    monitors: &mut [TargetMonitor],
+    bindings: &[(Action, char)],
+    config: &mut Config,
+    start_time: Instant,
+) -> Result<KeyAction> {
+    for command in control.poll() {
+        match command {
+            ControlCommand::Pause => pause_manager.set_manual_pause(true),
+            ControlCommand::Resume => pause_manager.set_manual_pause(false),
+            ControlCommand::Run(action) => {
+                if let KeyAction::Quit = perform_action(action, pause_manager, monitors, bindings, config, start_time)? {
+                    return Ok(KeyAction::Quit);
+                }
             }
-            KeyCode::Char('s') | KeyCode::Char('S') => {
-                let active_duration = pause_manager.active_duration(start_time);
-                println!("\n");
-                print_statistics(text_counts, active_duration);
-                println!();
+        }
+    }
+
+    Ok(KeyAction::Continue)
+}
+
+/// Run a keyboard action
+fn perform_action(
+    action: Action,
+    pause_manager: &mut PauseManager,
+    monitors: &mut [TargetMonitor],
+    bindings: &[(Action, char)],
+    config: &mut Config,
+    start_time: Instant,
+) -> Result<KeyAction> {
+    match action {
+        Action::Pause => {
+            pause_manager.toggle_manual_pause();
+        }
+        Action::Restart => {
+            for monitor in monitors.iter_mut() {
+                monitor.stats.clear();
+                monitor.battle_state.reset();
             }
-            KeyCode::Char('n') | KeyCode::Char('N') => {
-                println!("\n=> Normalizing Pokemon names...");
-                *text_counts = normalize_pokemon_names(text_counts);
-                println!("✓ Normalization complete\n");
+            println!("\n=> RESTARTED - All statistics cleared");
+        }
+        Action::ShowStats => {
+            let active_duration = pause_manager.active_duration(start_time);
+            println!("\n");
+            print_all_statistics(monitors, active_duration);
+            println!();
+        }
+        Action::Normalize => {
+            println!("\n=> Normalizing Pokemon names...");
+            for monitor in monitors.iter_mut() {
+                monitor.stats.commit_normalization();
+            }
+            println!("✓ Normalization complete\n");
+        }
+        Action::ToggleView => {
+            let normalized_view = !monitors.first().is_some_and(|m| m.stats.normalized_view);
+            for monitor in monitors.iter_mut() {
+                monitor.stats.normalized_view = normalized_view;
             }
-            KeyCode::Char('?') => {
-                show_help();
+            let view = if normalized_view { "normalized" } else { "raw" };
+            println!("\n=> Statistics view: {} names", view);
+        }
+        Action::ExportImage => {
+            let active_duration = pause_manager.active_duration(start_time);
+            // A clock set before 1970 only affects the file name; it must not end the session
+            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+            let path = PathBuf::from(format!("protean-summary-{}.png", timestamp));
+            let view = combined_stats(monitors).view();
+            match export_image(&view.text_counts, active_duration, view.since_last_target(), &path) {
+                Ok(()) => println!("\n✓ Summary image saved to: {}", path.display()),
+                Err(e) => eprintln!("\nImage export error: {:#}", e),
             }
-            KeyCode::Char('q') | KeyCode::Char('Q') => {
-                return Ok(KeyAction::Quit);
+        }
+        Action::ExportStats => {
+            let active_duration = pause_manager.active_duration(start_time);
+            let view = combined_stats(monitors).view();
+            let exported = prompt_export_path()
+                .and_then(|path| {
+                    export_statistics(&view.text_counts, active_duration, StatsFormat::from_path(&path)?, &path)?;
+                    Ok(path)
+                });
+            match exported {
+                Ok(path) => println!("✓ Statistics saved to: {}", path.display()),
+                Err(e) => eprintln!("Statistics export error: {:#}", e),
             }
-            _ => {}
+        }
+        Action::CycleArea => {
+            let mut area = None;
+            for monitor in monitors.iter_mut() {
+                area = monitor.stats.cycle_area().map(str::to_string);
+            }
+            match area {
+                Some(area) => println!("\n=> Area: {} (new encounters are tagged with it)", area),
+                None if monitors.first().is_some_and(|m| m.stats.areas.is_empty()) => {
+                    println!("\n=> No areas configured - add e.g. areas = [\"Route 1\"] to the config");
+                }
+                None => println!("\n=> Area cleared (new encounters are untagged)"),
+            }
+        }
+        Action::ToggleWindowDetection => {
+            config.window_detection = !config.window_detection;
+            if config.window_detection {
+                println!("\n=> Window detection enabled (auto-pause when the game isn't focused)");
+            } else {
+                pause_manager.clear_window_pause();
+                println!("\n=> Window detection disabled");
+            }
+        }
+        Action::HeatMap => {
+            for monitor in monitors.iter_mut() {
+                monitor.heat_map_requested = true;
+            }
+            println!("\n=> Saving a confidence heat map of the next frame...");
+        }
+        Action::Preview => {
+            let enabled = monitors.first().is_some_and(|m| m.preview_in.is_none());
+            for monitor in monitors.iter_mut() {
+                monitor.preview_in = enabled.then_some(0);
+                monitor.preview_thumbnail = None;
+            }
+            if !enabled {
+                println!("\n=> Region preview off");
+            }
+        }
+        Action::ManualEntry => {
+            return Ok(KeyAction::ManualEntry);
+        }
+        Action::Help => {
+            show_help(bindings, config.output_style);
+        }
+        Action::Quit => {
+            return Ok(KeyAction::Quit);
         }
     }
 
     Ok(KeyAction::Continue)
 }
 
+/// Tracks how long OCR takes per polling pass
+/// 
+/// When the rolling average exceeds the refresh interval the effective
+/// poll rate is slower than configured, so a one-time warning is printed.
+/// The average is smoothed and needs a minimum number of samples so a
+/// single slow frame (or model warm-up) doesn't trigger it.
+struct OcrThroughput {
+    average_secs: f64,
+    samples: u32,
+    warned: bool,
+}
+
+impl OcrThroughput {
+    fn new() -> Self {
+        Self {
+            average_secs: 0.0,
+            samples: 0,
+            warned: false,
+        }
+    }
+
+    /// Record the OCR time of one pass and warn if OCR can't keep up
+    fn record(&mut self, elapsed: Duration, refresh_rate: Duration) {
+        let elapsed_secs = elapsed.as_secs_f64();
+        self.average_secs = if self.samples == 0 {
+            elapsed_secs
+        } else {
+            OCR_TIMING_SMOOTHING * elapsed_secs + (1.0 - OCR_TIMING_SMOOTHING) * self.average_secs
+        };
+        self.samples = self.samples.saturating_add(1);
+
+        if self.warned || self.samples < OCR_TIMING_MIN_SAMPLES {
+            return;
+        }
+        if self.average_secs > refresh_rate.as_secs_f64() {
+            self.warned = true;
+            println!("⚠ OCR is taking {:.0}ms per pass, longer than the {}ms refresh rate",
+                     self.average_secs * 1000.0, refresh_rate.as_millis());
+            println!("  Fast battles may be missed. Try a larger refresh_rate, a smaller region,");
+            println!("  or setting preprocess_images = false");
+        }
+    }
+}
+
 /// Process OCR text and update battle state using state machine
 fn process_ocr_text(
     text: &str,
-    battle_state: &mut BattleState,
-    text_counts: &mut HashMap<String, usize>,
+    monitor: &mut TargetMonitor,
+    notifier: &mut Notifier,
+    sinks: &mut [Box<dyn CountSink>],
+    species: &SpeciesList,
+    active_duration: Duration,
+    config: &Config,
+) {
+    let single_line = text.replace('\n', " ");
+    if contains_any_phrase(&single_line, &config.suppress_if_contains) {
+        monitor.stats.diagnostics.suppressed_frames += 1;
+        if config.verbose {
+            monitor.suppressed_log.print(format!("⊘ Suppressed: \"{}\"", single_line));
+        }
+        return;
+    }
+
+    let rejoined;
+    let text = if config.rejoin_split_names {
+        rejoined = species.rejoin_split_names(text);
+        &rejoined
+    } else {
+        text
+    };
+
+    let battle_state = &mut monitor.battle_state;
+    let previous = battle_state.phase.clone();
+    let counted = battle_state.update(text, species, config);
+
+    let diagnostics = &mut monitor.stats.diagnostics;
+    match &battle_state.phase {
+        BattlePhase::PokemonDetected { name, .. } => {
+            if battle_state.phase.is_new_detection(&previous) {
+                diagnostics.detections += 1;
+            }
+            notifier.notify(name);
+        }
+        BattlePhase::Idle if counted.is_none() && !text.trim().is_empty() => diagnostics.ignored_frames += 1,
+        _ => {}
+    }
+
+    if let Some(battle) = counted {
+        monitor.stats.diagnostics.confirmed += 1;
+        record_count(battle, monitor, notifier, sinks, species, active_duration, config);
+    }
+}
+
+/// Add a finished battle to the statistics and report it to every sink
+fn record_count(
+    battle: CountedBattle,
+    monitor: &mut TargetMonitor,
+    notifier: &Notifier,
+    sinks: &mut [Box<dyn CountSink>],
+    species: &SpeciesList,
+    active_duration: Duration,
+    config: &Config,
+) {
+    let since_last_target = monitor.stats.since_last_target();
+    let count = monitor.stats.record(&battle.name, active_duration, battle.shiny);
+    if let Some(duration) = battle.duration {
+        monitor.stats.record_battle_time(&battle.name, duration);
+    }
+    if let Some(level) = battle.level {
+        monitor.stats.record_level(&battle.name, level);
+    }
+    if config.outcome.is_some() {
+        monitor.stats.record_outcome(&battle.name, battle.outcome);
+    }
+    if config.shiny_pattern.is_some() {
+        monitor.stats.record_shiny(&battle.name, battle.shiny);
+    }
+    if config.beep_on_count {
+        beep_count();
+    }
+
+    let event = CountEvent {
+        name: &battle.name,
+        dex: Pokedex::bundled().number(&battle.name),
+        count,
+        total: monitor.stats.text_counts.values().sum(),
+        target: &monitor.target.name,
+        outcome: battle.outcome,
+        level: battle.level,
+        shiny: battle.shiny,
+        area: monitor.stats.current_area.as_deref(),
+        is_target: notifier.is_target(&battle.name),
+        since_last_target: since_last_target.map(|since| since + 1),
+        known_species: species.contains(&battle.name),
+        active_secs: active_duration.as_secs(),
+    };
+    for sink in sinks.iter_mut() {
+        sink.record(&event);
+    }
+}
+
+/// Ask where to export the statistics, offering a timestamped CSV file
+fn prompt_export_path() -> Result<PathBuf> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let default_path = PathBuf::from(format!("protean-stats-{}.csv", timestamp));
+    print!("\nExport path (.csv or .json, blank for {}): ", default_path.display());
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let entered = input.trim();
+    Ok(if entered.is_empty() { default_path } else { PathBuf::from(entered) })
+}
+
+/// Ask for the name of an encounter OCR couldn't read
+/// 
+/// Names are completed and cased from the species list so they merge with
+/// automatic detections. A prefix matching several species lists them and
+/// asks again; a name matching none must be confirmed.
+/// 
+/// # Returns
+/// * `Some(String)` with the name to count
+/// * `None` if the entry was cancelled with a blank line
+fn prompt_manual_entry(species: &SpeciesList) -> Result<Option<String>> {
+    loop {
+        print!("\nPokemon name (blank to cancel): ");
+        io::stdout().flush()?;
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        let entered = input.trim();
+        if entered.is_empty() {
+            println!("=> Manual entry cancelled");
+            return Ok(None);
+        }
+
+        if let Some(name) = species.canonical(entered) {
+            return Ok(Some(name.to_string()));
+        }
+        match species.completions(entered).as_slice() {
+            [name] => {
+                println!("=> {}", name);
+                return Ok(Some(name.to_string()));
+            }
+            [] => {
+                print!("\"{}\" is not a known species. Count it anyway? (y/n): ", entered);
+                io::stdout().flush()?;
+                let mut confirm = String::new();
+                io::stdin().read_line(&mut confirm)?;
+                if confirm.trim().eq_ignore_ascii_case("y") {
+                    return Ok(Some(compose_name(entered)));
+                }
+            }
+            names => {
+                let shown = &names[..names.len().min(MANUAL_ENTRY_MAX_SUGGESTIONS)];
+                let more = names.len() - shown.len();
+                print!("Did you mean: {}", shown.join(", "));
+                if more > 0 {
+                    print!(" (+{} more)", more);
+                }
+                println!();
+            }
+        }
+    }
+}
+
+/// Read the outcome region during a battle and remember any outcome message
+fn track_outcome(
+    ocr_provider: &dyn OcrProvider,
+    capture: &dyn CaptureProvider,
+    monitor: &mut TargetMonitor,
     config: &Config,
 ) {
-    if let Some(pokemon_name) = battle_state.update(text, config) {
-        let count = text_counts.entry(pokemon_name.clone()).and_modify(|c| *c += 1).or_insert(1);
-        println!("✓ Counted: \"{}\" (Total: {})", pokemon_name, count);
+    let (Some(outcome_config), Some(outcome_region)) = (&config.outcome, &monitor.target.outcome_region) else {
+        return;
+    };
+    if !monitor.battle_state.in_battle() {
+        return;
+    }
+
+    let text = match capture.capture_region(outcome_region)
+        .and_then(|image| ocr_provider.extract_text(&image, config.preprocess_images))
+    {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("Outcome OCR error: {}", e);
+            return;
+        }
+    };
+
+    if let Some(outcome) = classify_outcome(&text.replace('\n', " "), outcome_config) {
+        monitor.battle_state.record_outcome(outcome);
     }
 }
 
-fn monitor_text(ocr_provider: &dyn OcrProvider, screen: &Screen, config: &Config) -> Result<()> {
-    let mut text_counts: HashMap<String, usize> = HashMap::new();
-    let mut pause_manager = PauseManager::new();
-    let mut battle_state = BattleState::new();
-    let start_time = Instant::now();
+/// Append the text of a target's extra regions, in config order, to the
+/// text read from its main region
+/// 
+/// A region that fails to capture or read is left out of this frame.
+fn with_extra_regions(
+    text: String,
+    ocr_provider: &dyn OcrProvider,
+    capture: &dyn CaptureProvider,
+    target: &MonitorTarget,
+    config: &Config,
+) -> String {
+    let mut texts = vec![text];
+    for region in &target.extra_regions {
+        match capture.capture_region(region)
+            .and_then(|image| ocr_provider.extract_text(&image, config.preprocess_images))
+        {
+            Ok(extra) => texts.push(extra),
+            Err(e) => eprintln!("Extra region OCR error ({}): {}", target.name, e),
+        }
+    }
+    texts.retain(|text| !text.is_empty());
+    texts.join("\n")
+}
+
+/// Wait for a newly detected banner to settle, then read it again and keep
+/// the settled name
+/// 
+/// Banners read mid-animation can give a garbled name; the re-read
+/// replaces it when it still finds an encounter.
+fn reread_settled_name(
+    ocr_provider: &dyn OcrProvider,
+    capture: &dyn CaptureProvider,
+    monitor: &mut TargetMonitor,
+    config: &Config,
+) {
+    thread::sleep(Duration::from_millis(config.detection_settle_ms));
+    let text = match capture.capture_region(&monitor.target.region)
+        .and_then(|image| ocr_provider.extract_text(&image, config.preprocess_images))
+    {
+        Ok(text) => with_extra_regions(text, ocr_provider, capture, &monitor.target, config),
+        Err(e) => {
+            eprintln!("Settle re-read error: {}", e);
+            return;
+        }
+    };
 
-    println!("\n╔══════════════════════════════════════════════════════╗");
-    println!("║                  MONITORING STARTED                  ║");
-    println!("╚══════════════════════════════════════════════════════╝");
+    if let Some(settled) = extract_encounter_name(&text, config) {
+        monitor.battle_state.rename_detection(settled);
+    }
+}
+
+/// Update each target's focus state from the active window
+/// 
+/// Returns whether any monitored client is focused, or the error querying
+/// the active window failed with. Focus changes are
+/// reported per target when several clients are monitored, and a warning
+/// is printed when a focused client's window size changes since the fixed
+/// capture region then likely points at the wrong area.
+fn update_window_focus(detector: WindowDetector, monitors: &mut [TargetMonitor]) -> Result<bool> {
+    let target_window_classes: Vec<&str> = monitors.iter().map(|m| m.target.window_class.as_str()).collect();
+    let active = detector.check_active_window(&target_window_classes)?;
+    let multiple = monitors.len() > 1;
+
+    for monitor in monitors.iter_mut() {
+        let focused_window = active.as_ref().filter(|window| window.class == monitor.target.window_class);
+        let focused = focused_window.is_some();
+        if multiple && focused != monitor.focused {
+            let state = if focused { "focused" } else { "unfocused" };
+            println!("[{}] window {}", monitor.target.name, state);
+        }
+        monitor.focused = focused;
+
+        if let Some(window) = focused_window {
+            if let Some((width, height)) = monitor.window_size
+                && (width, height) != window.size
+            {
+                println!("\n⚠ [{}] window resized from {}x{} to {}x{} - the capture region may need",
+                         monitor.target.name, width, height, window.size.0, window.size.1);
+                println!("  re-calibrating (restart with --autodetect-region)");
+            }
+            monitor.window_size = Some(window.size);
+        }
+    }
+
+    Ok(monitors.iter().any(|m| m.focused))
+}
+
+fn monitor_text(
+    ocr_provider: &dyn OcrProvider,
+    capture_provider: Arc<dyn CaptureProvider>,
+    species: &SpeciesList,
+    control: Option<&ControlSocket>,
+    mut daemon: Option<&mut Daemon>,
+    tui: bool,
+    config: &mut Config,
+) -> Result<()> {
+    let mut monitors: Vec<TargetMonitor> = config
+        .monitor_targets()
+        .into_iter()
+        .map(|target| TargetMonitor::new(target, config))
+        .collect();
+    let mut notifier = Notifier::new(&config.notify_targets, Duration::from_secs(config.notify_cooldown_secs));
+    let mut sinks = build_sinks(config, monitors.len())?;
+    let mut pause_manager = PauseManager::new(Duration::from_millis(config.window_pause_grace_ms), SystemClock);
+    let mut throughput = OcrThroughput::new();
+    let bindings = config.key_bindings();
+    let regions = monitors.iter().map(|monitor| monitor.target.region).collect();
+    let capture = FrameCapture::spawn(Arc::clone(&capture_provider), regions, config.refresh_rate);
+    let hotkeys = match GlobalHotkeys::register(&config.global_hotkeys) {
+        Ok(hotkeys) => hotkeys,
+        Err(e) => {
+            eprintln!("Global hotkeys disabled: {:#}", e);
+            None
+        }
+    };
+    if config.record_session_frames {
+        for monitor in &mut monitors {
+            match Config::debug_dir().and_then(|dir| SessionRecorder::create(&dir, &monitor.target.name)) {
+                Ok(recorder) => {
+                    println!("Recording session frames to {}", recorder.dir().display());
+                    monitor.recorder = Some(recorder);
+                }
+                Err(e) => eprintln!("Session recording disabled ({}): {:#}", monitor.target.name, e),
+            }
+        }
+    }
+    let session_path = Config::session_path()?;
+    let session_save_interval = Duration::from_secs(config.session_save_interval_secs);
+    let mut session_saved_at = Instant::now();
+    if config.resume_session && let Some(resumed) = resume_session(&session_path, &mut monitors)? {
+        pause_manager.resume_from(resumed);
+        let total: usize = monitors.iter().map(|m| m.stats.text_counts.values().sum::<usize>()).sum();
+        println!("✓ Resumed the previous session: {} encounters in {} (start fresh with --new-session)",
+                 total, format_duration(resumed, config.duration_decimals));
+    }
+    let start_time = pause_manager.clock.now();
+
+    let window_detector = WindowDetector::new(config.window_backend);
+    let mut window_error_reported = false;
+    if config.window_detection && window_detector.is_none() {
+        eprintln!("⚠ Window detection disabled: not a Hyprland or X11 session (set window_backend \
+                   to \"hyprland\" or \"x11\" if it is)");
+        config.window_detection = false;
+    }
+
+    print_banner("MONITORING STARTED", config.output_style);
     if config.window_detection {
-        println!("Window detection enabled: {} ", config::TARGET_WINDOW_CLASS);
+        for monitor in &monitors {
+            println!("Window detection enabled: {} ", monitor.target.window_class);
+        }
     }
-    show_help();
-    println!("Tracking encounters with 'VS. Wild [Pokemon]' pattern");
+    if monitors.len() > 1 {
+        let names: Vec<&str> = monitors.iter().map(|m| m.target.name.as_str()).collect();
+        println!("Monitoring {} targets: {}", monitors.len(), names.join(", "));
+    }
+    if daemon.is_none() {
+        show_help(&bindings, config.output_style);
+    }
+    if let Some(control) = control {
+        println!("Control socket: {}", control.path().display());
+    }
+    if hotkeys.is_some() {
+        for (action, combination) in &config.global_hotkeys {
+            println!("Global hotkey: {} - {}", combination, action.description());
+        }
+    }
+    println!("Tracking encounters with '{}' pattern", config.pattern_label());
     println!("Counts registered AFTER battle ends\n");
+    let mut dashboard = if tui { Some(Dashboard::start(&Config::tui_log_path()?)?) } else { None };
 
     loop {
-        // Window detection check
-        if config.window_detection && let Ok(is_target) = check_active_window() {
-            pause_manager.set_window_pause(!is_target);
+        // Window detection check (paused only when no monitored client is focused)
+        if config.window_detection && let Some(detector) = window_detector {
+            match update_window_focus(detector, &mut monitors) {
+                Ok(any_focused) => pause_manager.set_window_pause(!any_focused),
+                // Reported once; a missing tool would otherwise print every frame
+                Err(e) if !window_error_reported => {
+                    eprintln!("⚠ Window detection failed, focus won't be tracked until it works: {:#}", e);
+                    window_error_reported = true;
+                }
+                Err(_) => {}
+            }
         }
 
-        // Check for keyboard input
-        match handle_keyboard_input(&mut pause_manager, &mut battle_state, &mut text_counts, start_time)? {
-            KeyAction::Quit => {
+        // Check for keyboard input (or a termination signal when headless)
+        // and control socket commands
+        let mut key_action = match daemon.as_deref() {
+            Some(daemon) if daemon.terminated() => KeyAction::Quit,
+            Some(_) => KeyAction::Continue,
+            None => handle_keyboard_input(dashboard.as_mut(), &mut pause_manager, &mut monitors, &bindings, config, start_time)?,
+        };
+        if let Some(hotkeys) = &hotkeys
+            && let KeyAction::Continue = key_action
+            && let Some(action) = hotkeys.poll()
+        {
+            key_action = perform_key_action(action, dashboard.as_mut(), &mut pause_manager, &mut monitors, &bindings, config, start_time)?;
+        }
+        if let Some(control) = control
+            && let KeyAction::Continue = key_action
+        {
+            key_action = handle_control_commands(control, &mut pause_manager, &mut monitors, &bindings, config, start_time)?;
+        }
+        match key_action {
+            KeyAction::Quit | KeyAction::ConfirmQuit => {
+                // Measured before prompting so time spent answering isn't counted
                 let active_duration = pause_manager.active_duration(start_time);
-                println!("\n\n=> Monitoring stopped by user.");
-                print_statistics(&text_counts, active_duration);
+                // The final statistics are printed to the terminal, not the log
+                if let Some(dashboard) = &mut dashboard {
+                    dashboard.suspend()?;
+                }
+                let mut record_lifetime = true;
+                if let KeyAction::ConfirmQuit = key_action {
+                    match prompt_quit(&combined_stats(&monitors), active_duration)? {
+                        Some(record) => record_lifetime = record,
+                        None => {
+                            println!("=> Continuing the hunt");
+                            if let Some(dashboard) = &mut dashboard {
+                                dashboard.resume()?;
+                            }
+                            continue;
+                        }
+                    }
+                }
+                match daemon {
+                    Some(daemon) => {
+                        println!("\n\n=> Monitoring stopped by signal.");
+                        let snapshot = daemon.write_snapshot(
+                            &combined_stats(&monitors), active_duration, pause_manager.is_paused());
+                        if let Err(e) = snapshot {
+                            eprintln!("Failed to write final snapshot: {:#}", e);
+                        }
+                    }
+                    None => println!("\n\n=> Monitoring stopped by user."),
+                }
+                print_all_statistics(&monitors, active_duration);
+                for monitor in &monitors {
+                    if let Some(recorder) = &monitor.recorder
+                        && let Err(e) = recorder.write_counts(&monitor.stats.text_counts)
+                    {
+                        eprintln!("{:#} ({})", e, monitor.target.name);
+                    }
+                }
+                let recorded = record_lifetime && match Config::lifetime_path()
+                    .and_then(|path| record_session(&combined_stats(&monitors), active_duration, &path))
+                {
+                    Ok(()) => true,
+                    Err(e) => {
+                        eprintln!("Failed to update lifetime statistics: {:#}", e);
+                        false
+                    }
+                };
+                // A session recorded in the lifetime stats is finished; any
+                // other is kept so the next start can resume it
+                if !recorded {
+                    save_monitors_session(&session_path, &monitors, active_duration);
+                } else if session_path.exists() && let Err(e) = std::fs::remove_file(&session_path) {
+                    eprintln!("Failed to remove saved session {}: {}", session_path.display(), e);
+                }
                 return Ok(());
             }
+            KeyAction::ManualEntry => {
+                if let Some(dashboard) = &mut dashboard {
+                    dashboard.suspend()?;
+                }
+                let entered = prompt_manual_entry(species)?;
+                if let Some(dashboard) = &mut dashboard {
+                    dashboard.resume()?;
+                }
+                if let Some(name) = entered
+                    && let Some(monitor) = monitors.first_mut()
+                {
+                    let battle = CountedBattle {
+                        name,
+                        outcome: None,
+                        duration: None,
+                        end_message: None,
+                        level: None,
+                        shiny: ShinyStatus::Normal,
+                    };
+                    let active_duration = pause_manager.active_duration(start_time);
+                    record_count(battle, monitor, &notifier, &mut sinks, species, active_duration, config);
+                }
+            }
             KeyAction::Continue => {}
         }
 
+        if session_saved_at.elapsed() >= session_save_interval {
+            session_saved_at = Instant::now();
+            save_monitors_session(&session_path, &monitors, pause_manager.active_duration(start_time));
+        }
+
+        if let Some(daemon) = daemon.as_deref_mut()
+            && daemon.snapshot_due()
+        {
+            let active_duration = pause_manager.active_duration(start_time);
+            if let Err(e) = daemon.write_snapshot(&combined_stats(&monitors), active_duration, pause_manager.is_paused()) {
+                eprintln!("Failed to write snapshot: {:#}", e);
+            }
+        }
+
+        if let Some(dashboard) = &mut dashboard {
+            let stats = combined_stats(&monitors).view();
+            let phases = monitors
+                .iter()
+                .map(|monitor| (monitor.target.name.as_str(), monitor.battle_state.phase.label()))
+                .collect();
+            dashboard.draw(&DashboardView {
+                stats: &stats,
+                active_duration: pause_manager.active_duration(start_time),
+                paused: pause_manager.is_paused(),
+                phases,
+                bindings: &bindings,
+                previews: monitors
+                    .iter()
+                    .filter(|monitor| monitor.preview_in.is_some())
+                    .map(|monitor| RegionPreview {
+                        name: &monitor.target.name,
+                        region: &monitor.target.region,
+                        thumbnail: monitor.preview_thumbnail.as_ref(),
+                    })
+                    .collect(),
+            })?;
+        }
+
+        capture.set_paused(pause_manager.is_paused());
         if pause_manager.is_paused() {
-            thread::sleep(Duration::from_millis(PAUSE_POLL_INTERVAL_MS));
+            capture.discard();
+            thread::sleep(config.pause_poll_interval);
             continue;
         }
 
-        let image =         match capture_region(screen, &config.region) {
-            Ok(img) => img,
-            Err(e) => {
-                eprintln!("Capture error: {}", e);
-                thread::sleep(config.refresh_rate);
-                continue;
-            }
+        // Wait briefly for the next frame so keyboard input stays responsive
+        let Some(frame) = capture.latest(Duration::from_millis(FRAME_WAIT_TIMEOUT_MS)) else {
+            continue;
         };
 
-        match ocr_provider.extract_text(&image, config.preprocess_images) {
-            Ok(text) => process_ocr_text(&text, &mut battle_state, &mut text_counts, config),
-            Err(e) => eprintln!("OCR Error: {}", e),
+        let mut ocr_time = Duration::ZERO;
+        for (monitor, captured) in monitors.iter_mut().zip(frame) {
+            let image = match captured {
+                Ok(img) => img,
+                Err(e) => {
+                    eprintln!("Capture error ({}): {}", monitor.target.name, e);
+                    continue;
+                }
+            };
+
+            track_outcome(ocr_provider, capture_provider.as_ref(), monitor, config);
+
+            if monitor.heat_map_requested {
+                monitor.heat_map_requested = false;
+                monitor.export_heat_map(ocr_provider, &image, config);
+            }
+            if let Some(frames) = monitor.preview_in {
+                if frames == 0 {
+                    if dashboard.is_some() {
+                        monitor.preview_thumbnail = Some(region_thumbnail(&image, PREVIEW_COLUMNS));
+                    } else {
+                        print_region_preview(&image, &monitor.target.region, &monitor.target.name);
+                    }
+                }
+                monitor.preview_in = Some(frames.checked_sub(1).unwrap_or(PREVIEW_EVERY_FRAMES - 1));
+            }
+
+            let sample = (monitor.intro.is_some() || monitor.ocr_gate.is_some()).then(|| change_sample(&image));
+            if let Some(intro) = &mut monitor.intro
+                && let Some(sample) = &sample
+            {
+                intro.observe(sample, !monitor.battle_state.in_battle());
+            }
+
+            let unchanged = monitor.ocr_gate.as_ref()
+                .zip(sample.as_ref())
+                .and_then(|(gate, sample)| gate.unchanged_text(sample))
+                .map(str::to_string);
+            let result = match unchanged {
+                Some(text) => {
+                    monitor.stats.diagnostics.unchanged_frames += 1;
+                    Ok(text)
+                }
+                None => {
+                    let ocr_started = Instant::now();
+                    let result = ocr_provider.extract_text(&image, config.preprocess_images);
+                    ocr_time += ocr_started.elapsed();
+                    if let Ok(text) = &result
+                        && let Some(gate) = &mut monitor.ocr_gate
+                        && let Some(sample) = sample
+                    {
+                        gate.remember(sample, text);
+                    }
+                    result
+                }
+            };
+
+            let result = result.map(|text| {
+                if monitor.target.extra_regions.is_empty() {
+                    return text;
+                }
+                let ocr_started = Instant::now();
+                let text = with_extra_regions(text, ocr_provider, capture_provider.as_ref(), &monitor.target, config);
+                ocr_time += ocr_started.elapsed();
+                text
+            });
+
+            match result {
+                Ok(text) => {
+                    let phase_before = monitor.battle_state.phase.clone();
+                    process_ocr_text(
+                        &text,
+                        monitor,
+                        &mut notifier,
+                        &mut sinks,
+                        species,
+                        pause_manager.active_duration(start_time),
+                        config,
+                    );
+                    if monitor.battle_state.phase.is_new_detection(&phase_before) {
+                        if let Some(arrived_at) = monitor.intro.as_mut().and_then(IntroDetector::take_arrival) {
+                            monitor.battle_state.detected_at = Some(arrived_at);
+                        }
+                        if config.detection_settle_ms > 0 {
+                            reread_settled_name(ocr_provider, capture_provider.as_ref(), monitor, config);
+                            // The settled read replaced this frame's text
+                            if let Some(gate) = &mut monitor.ocr_gate {
+                                gate.forget();
+                            }
+                        }
+                    }
+                    if let Some(recorder) = &mut monitor.recorder
+                        && let Err(e) = recorder.record(&image, &text)
+                    {
+                        eprintln!("Session recording stopped ({}): {:#}", monitor.target.name, e);
+                        monitor.recorder = None;
+                    }
+                    monitor.frame_history.push(image, text);
+                    monitor.check_anomaly(ocr_provider, config);
+                }
+                Err(e) => eprintln!("{} ({})", e, monitor.target.name),
+            }
         }
+        throughput.record(ocr_time, config.refresh_rate);
+    }
+}
+
+/// Run the detection state machine over scripted OCR text
+fn replay_ocr(ocr_provider: &MockOcrProvider, species: &SpeciesList, config: &Config) -> Result<()> {
+    let mut monitor = TargetMonitor::new(config.monitor_targets().remove(0), config);
+    let mut notifier = Notifier::new(&config.notify_targets, Duration::from_secs(config.notify_cooldown_secs));
+    let mut sinks = build_sinks(config, 1)?;
+    let blank_frame = DynamicImage::new_rgb8(1, 1);
+    let texts = (0..ocr_provider.remaining())
+        .map(|_| ocr_provider.extract_text(&blank_frame, config.preprocess_images))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    println!("Replaying {} frames with '{}' pattern\n", texts.len(), config.pattern_label());
+    let active_duration = replay_texts(texts, &mut monitor, &mut notifier, &mut sinks, species, config);
+
+    println!();
+    print_all_statistics(std::slice::from_ref(&monitor), active_duration);
+    Ok(())
+}
 
-        thread::sleep(config.refresh_rate);
+/// Feed OCR text to a monitor frame by frame, returning the active time
+/// 
+/// Each frame advances the active time by the refresh rate so rates and
+/// battle times come out as they would have live.
+fn replay_texts(
+    texts: Vec<String>,
+    monitor: &mut TargetMonitor,
+    notifier: &mut Notifier,
+    sinks: &mut [Box<dyn CountSink>],
+    species: &SpeciesList,
+    config: &Config,
+) -> Duration {
+    let mut active_duration = Duration::ZERO;
+    for text in texts {
+        active_duration += config.refresh_rate;
+        process_ocr_text(&text, monitor, notifier, sinks, species, active_duration, config);
     }
+    active_duration
 }
 
-fn main() -> Result<()> {
+/// Counts per species the detection state machine derives from OCR text,
+/// without printing, notifying or reporting to any sink
+fn derive_counts(texts: Vec<String>, species: &SpeciesList, config: &Config) -> BTreeMap<String, usize> {
+    let mut monitor = TargetMonitor::new(config.monitor_targets().remove(0), config);
+    monitor.battle_state = BattleState::new(Box::new(SilentObserver));
+    let mut notifier = Notifier::new(&[], Duration::ZERO);
+    replay_texts(texts, &mut monitor, &mut notifier, &mut [], species, config);
+    monitor.stats.text_counts.into_iter().collect()
+}
+
+/// Re-OCR the frames of a recorded session with the current settings and
+/// report every frame that reads differently, then whether the counts
+/// derived from the new reads match the live ones
+/// 
+/// Sessions that didn't end cleanly have no saved counts; theirs are
+/// re-derived from the text read live instead.
+fn verify_session(engine: &OcrEngine, mut config: Config, dir: &Path) -> Result<()> {
+    config.verbose = false;
+    config.beep_on_count = false;
+    let species = SpeciesList::load(config.species_list_path.as_deref())?;
+    let ocr_provider = StandardOcrProvider::new(engine)
+        .with_preprocess_options(config.preprocess_options())
+        .with_word_separator(&config.word_separator)
+        .with_single_line(config.single_line_mode)
+        .with_confidence_filter(config.confidence_filter())
+        .with_preprocess_fallback(config.preprocess_fallback)
+        .with_upscale_factor(config.upscale_factor);
+
+    let live_texts = read_replay_script(&dir.join(SESSION_SCRIPT_FILE_NAME))?;
+    println!("Verifying {} frames from {}\n", live_texts.len(), dir.display());
+    let mut reread_texts = Vec::with_capacity(live_texts.len());
+    let mut mismatches = 0;
+    for (index, live) in live_texts.iter().enumerate() {
+        let file_name = session_frame_name(index);
+        let image = image::open(dir.join(&file_name))
+            .with_context(|| format!("Failed to open session frame {}", file_name))?;
+        let text = ocr_provider.extract_text(&image, config.preprocess_images)?.trim().to_string();
+        if text != *live {
+            mismatches += 1;
+            println!("✗ {}: live \"{}\", now \"{}\"", file_name, live.replace('\n', "\\n"), text.replace('\n', "\\n"));
+        }
+        reread_texts.push(text);
+    }
+    println!("\n{} of {} frames read differently", mismatches, live_texts.len());
+
+    let live_counts = match read_session_counts(dir)? {
+        Some(counts) => counts,
+        None => {
+            println!("No saved live counts (session didn't end cleanly); re-deriving them from the live reads");
+            derive_counts(live_texts, &species, &config)
+        }
+    };
+    let reread_counts = derive_counts(reread_texts, &species, &config);
+
+    let names: BTreeSet<&String> = live_counts.keys().chain(reread_counts.keys()).collect();
+    let mut discrepancies = 0;
+    for name in names {
+        let live = live_counts.get(name).copied().unwrap_or(0);
+        let reread = reread_counts.get(name).copied().unwrap_or(0);
+        if live != reread {
+            discrepancies += 1;
+            println!("⚠ {}: {} live, {} re-derived", name, live, reread);
+        }
+    }
+    if discrepancies == 0 {
+        println!("✓ Re-derived counts match the live counts");
+    } else {
+        println!("{} species counted differently (manual entries are only in the live counts)", discrepancies);
+    }
+    Ok(())
+}
+
+/// Load the OCR models from the cache directory and initialize the engine
+/// 
+/// Each step gets its own error so it's clear which model is missing or
+/// corrupt, with a hint on where to download a fresh copy.
+fn load_ocr_engine() -> Result<OcrEngine> {
     println!("Loading OCR models...");
-    
+    let engine = load_ocr_models()?;
+    println!("✓ Models loaded successfully!\n");
+    Ok(engine)
+}
+
+fn load_ocr_models() -> Result<OcrEngine> {
     let home = std::env::var("HOME").context("HOME not set")?;
-    let cache_dir = PathBuf::from(home).join(".cache/ocrs");
-    
-    let detection_path = cache_dir.join("text-detection.rten");
-    let recognition_path = cache_dir.join("text-recognition.rten");
-    
-    let detection_model = Model::load_file(&detection_path)
-        .context("Failed to load detection model. Download to ~/.cache/ocrs/")?;
-    let recognition_model = Model::load_file(&recognition_path)
-        .context("Failed to load recognition model. Download to ~/.cache/ocrs/")?;
-    
-    let engine = OcrEngine::new(OcrEngineParams {
+    let cache_dir = PathBuf::from(home).join(MODEL_CACHE_DIR);
+
+    let load_model = |kind: &str, file_name: &str| {
+        let path = cache_dir.join(file_name);
+        Model::load_file(&path).with_context(|| format!(
+            "Failed to load {} model from {}. If the file is missing or corrupt, \
+             re-download it from {}/{}",
+            kind, path.display(), MODEL_DOWNLOAD_URL, file_name,
+        ))
+    };
+    let detection_model = load_model("detection", DETECTION_MODEL_FILE)?;
+    let recognition_model = load_model("recognition", RECOGNITION_MODEL_FILE)?;
+
+    OcrEngine::new(OcrEngineParams {
         detection_model: Some(detection_model),
         recognition_model: Some(recognition_model),
         ..Default::default()
-    })?;
+    })
+    .with_context(|| format!(
+        "OCR engine initialization failed. The models in {} may be incompatible \
+         with this version; try re-downloading them from {}",
+        cache_dir.display(), MODEL_DOWNLOAD_URL,
+    ))
+}
 
-    println!("✓ Models loaded successfully!\n");
+/// OCR a saved image with and without preprocessing, both with word
+/// detection and in single line mode, and print each read with its latency
+/// 
+/// The image is enlarged by the configured `upscale_factor` first, as
+/// live frames are.
+fn print_image_text(engine: &OcrEngine, config: &Config, path: &Path) -> Result<()> {
+    let image = image::open(path)
+        .with_context(|| format!("Failed to open image {}", path.display()))?;
+    let image = upscale(&image, config.upscale_factor);
+    if config.upscale_factor > 1.0 {
+        println!("Upscaled {}x to {}x{}", config.upscale_factor, image.width(), image.height());
+    }
+    let preprocess_options = config.preprocess_options();
+    let confidence_filter = config.confidence_filter();
+
+    // The first pass pays the model warmup; keep it out of the timings
+    extract_text_from_image(engine, &image, None, &config.word_separator, false, &confidence_filter)?;
+    for (preprocess_label, preprocess) in [("off", None), ("on", Some(&preprocess_options))] {
+        for (layout_label, single_line) in [("word detection", false), ("single line", true)] {
+            let started = Instant::now();
+            let text = extract_text_from_image(
+                engine, &image, preprocess, &config.word_separator, single_line, &confidence_filter)?;
+            println!("Preprocessing {:<3} {:<15} {:>5}ms  \"{}\"",
+                     format!("{},", preprocess_label), layout_label, started.elapsed().as_millis(), text);
+        }
+    }
+    Ok(())
+}
+
+/// Run a throwaway OCR pass so the first real frame doesn't pay the
+/// model warmup and allocation cost
+/// 
+/// The pass reads a rendered sample of known text, so models that load
+/// but can't recognize anything (wrong version, truncated download) are
+/// reported up front instead of showing up as OCR silently reading nothing.
+fn warm_up_ocr(ocr_provider: &dyn OcrProvider, preprocess: bool) {
+    let sample = DynamicImage::ImageRgb8(text_image(WARMUP_TEXT, WARMUP_TEXT_SCALE, WARMUP_TEXT_PADDING));
+    let started = Instant::now();
+    let text = match ocr_provider.extract_text(&sample, preprocess) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("OCR warmup failed (continuing): {}", e);
+            return;
+        }
+    };
+    println!("✓ OCR warmed up in {}ms", started.elapsed().as_millis());
+
+    // Misreading a letter is fine; recognizing none of the words is not
+    let read = text.to_lowercase();
+    let recognized = WARMUP_TEXT
+        .split_whitespace()
+        .any(|word| read.contains(&word.to_lowercase()));
+    if !recognized {
+        eprintln!("⚠ OCR self-check read \"{}\" from a test image of \"{}\". The models in ~/{} \
+                   may be the wrong version or corrupt; try re-downloading them from {}",
+                  text, WARMUP_TEXT, MODEL_CACHE_DIR, MODEL_DOWNLOAD_URL);
+    }
+}
+
+/// Count down before monitoring starts so the user can focus the game window
+fn startup_countdown(delay_secs: u64) -> Result<()> {
+    if delay_secs == 0 {
+        return Ok(());
+    }
+
+    println!("\nFocus the game window - monitoring starts soon");
+    for remaining in (1..=delay_secs).rev() {
+        print!("\rStarting in {}... ", remaining);
+        io::stdout().flush()?;
+        thread::sleep(Duration::from_secs(1));
+    }
+    println!("\rStarting now!    ");
+    Ok(())
+}
+
+/// Load the config chosen with `--config` (or the default one) and apply
+/// the command line overrides on top
+fn load_config(cli: &CliArgs) -> Result<Config> {
+    let mut config = Config::load_or_create(cli.config_path.as_deref())?;
+    cli.overrides.apply(&mut config)?;
+    Ok(config)
+}
+
+fn main() -> Result<()> {
+    let cli = CliArgs::parse();
+
+    if cli.lifetime {
+        let config = load_config(&cli)?;
+        LifetimeStats::load(&Config::lifetime_path()?)?.print(&config);
+        return Ok(());
+    }
+
+    if cli.list_monitors {
+        list_monitors(&Screen::all()?);
+        return Ok(());
+    }
+
+    if let Some(path) = &cli.export_rules {
+        return export_rules(&load_config(&cli)?, path);
+    }
+    if let Some(path) = &cli.import_rules {
+        return import_rules(&mut Config::load_or_create(cli.config_path.as_deref())?, path);
+    }
+
+    if cli.reset {
+        return reset(&load_config(&cli)?, cli.reset_lifetime, !cli.yes);
+    }
+
+    if let Some(path) = &cli.dump_log {
+        return dump_csv(path, cli.timeline_secs.map(Duration::from_secs));
+    }
+
+    if let Some(script) = &cli.replay {
+        let mut config = load_config(&cli)?;
+        config.verbose |= cli.verbose;
+        let species = SpeciesList::load(config.species_list_path.as_deref())?;
+        return replay_ocr(&MockOcrProvider::from_file(script)?, &species, &config);
+    }
+
+    if let Some(path) = &cli.ocr_image {
+        return print_image_text(&load_ocr_engine()?, &load_config(&cli)?, path);
+    }
+    if let Some(dir) = &cli.verify_session {
+        return verify_session(&load_ocr_engine()?, load_config(&cli)?, dir);
+    }
 
+    let _lock = InstanceLock::acquire(cli.force)?;
+
+    let mut config = Config::load_or_create(cli.config_path.as_deref())?;
     let screens = Screen::all()?;
-    let screen = screens.first().context("No screens found")?;
-    let config = Config::load_or_create()?;
+    let screen = select_screen(&screens, config.monitor_index)?;
+    if cli.verbose {
+        config.verbose = true;
+    }
+    if cli.yes {
+        config.confirm_quit = false;
+    }
+    if cli.new_session {
+        config.resume_session = false;
+    }
 
-    let ocr_provider = StandardOcrProvider::new(&engine);
+    let species = SpeciesList::load(config.species_list_path.as_deref())?;
 
-    println!("\nStarting in {} seconds...", STARTUP_DELAY_SECONDS);
-    thread::sleep(Duration::from_secs(STARTUP_DELAY_SECONDS));
+    // A missing model or tesseract install is reported before monitoring starts
+    let (engine, tesseract) = match config.ocr_backend {
+        OcrBackend::Ocrs => (Some(load_ocr_engine()?), None),
+        OcrBackend::Tesseract => (None, Some(TesseractOcrProvider::new()?)),
+    };
 
-    monitor_text(&ocr_provider, screen, &config)?;
+    if cli.autodetect_region {
+        let engine = engine.as_ref().context("--autodetect-region needs ocr_backend = \"ocrs\"")?;
+        if !autodetect_region(engine, screen, &mut config)? {
+            println!("Keeping configured region");
+        }
+    }
+    if cli.select_region && !select_region(screen, &mut config)? {
+        println!("Keeping configured region");
+    }
+    // Applied after autodetection or selection, which save the config, so they aren't persisted
+    cli.overrides.apply(&mut config)?;
+    // A capture command handles the coordinates itself
+    if config.capture_backend != CaptureBackend::Command {
+        fit_regions_to_screen(&screens, &mut config)?;
+    }
+
+    let ocr_provider: Box<dyn OcrProvider + '_> = match (&engine, tesseract) {
+        (Some(engine), _) => {
+            let provider = StandardOcrProvider::new(engine)
+                .with_preprocess_options(config.preprocess_options())
+                .with_word_separator(&config.word_separator)
+                .with_single_line(config.single_line_mode)
+                .with_confidence_filter(config.confidence_filter())
+                .with_preprocess_fallback(config.preprocess_fallback)
+                .with_upscale_factor(config.upscale_factor);
+            warm_up_ocr(&provider, config.preprocess_images);
+            Box::new(provider)
+        }
+        (None, Some(tesseract)) => {
+            println!("✓ Using tesseract for OCR");
+            Box::new(tesseract
+                .with_preprocess_options(config.preprocess_options())
+                .with_word_separator(&config.word_separator)
+                .with_single_line(config.single_line_mode)
+                .with_upscale_factor(config.upscale_factor))
+        }
+        (None, None) => unreachable!("one OCR backend is always set up"),
+    };
+
+    let capture_provider = build_capture_provider(*screen, &config);
+    if cli.calibrate {
+        return calibrate(ocr_provider.as_ref(), capture_provider.as_ref(), &config);
+    }
+
+    if cli.no_delay || cli.daemon {
+        config.startup_delay_secs = 0;
+    }
+    startup_countdown(config.startup_delay_secs)?;
+
+    let control = cli.control_socket.as_deref().map(ControlSocket::bind).transpose()?;
+    let mut daemon = if cli.daemon { Some(Daemon::start(&config)?) } else { None };
+    monitor_text(ocr_provider.as_ref(), capture_provider, &species, control.as_ref(), daemon.as_mut(), cli.tui, &mut config)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run every frame of a script through a quiet state machine, returning the counted battles
+    fn run_script(script: &[&str], config: &Config) -> Vec<CountedBattle> {
+        let ocr_provider = MockOcrProvider::new(script.iter().map(|text| text.to_string()));
+        let species = SpeciesList::load(None).unwrap();
+        let mut state = BattleState::new(Box::new(SilentObserver));
+        let frame = DynamicImage::new_rgb8(1, 1);
+        let mut counted = Vec::new();
+        while ocr_provider.remaining() > 0 {
+            let text = ocr_provider.extract_text(&frame, false).unwrap();
+            counted.extend(state.update(&text, &species, config));
+        }
+        counted
+    }
+
+    #[test]
+    fn detect_active_then_two_empty_frames_counts_once() {
+        let config = Config::preset();
+        let counted = run_script(&["VS. Wild Pidgey", "VS. Wild Pidgey", "", ""], &config);
+        let names: Vec<&str> = counted.iter().map(|battle| battle.name.as_str()).collect();
+        assert_eq!(names, ["Pidgey"]);
+    }
+
+    #[test]
+    fn one_empty_frame_does_not_count() {
+        let config = Config::preset();
+        assert!(run_script(&["VS. Wild Pidgey", "VS. Wild Pidgey", ""], &config).is_empty());
+    }
+
+    /// Frames of a battle end flickering between empty and a cut-off banner
+    const FLICKERING_END: [&str; 6] = ["", "VS. Wild Pid", "", "VS. Wild Pid", "", "VS. Wild Pid"];
+
+    #[test]
+    fn flickering_end_never_counts_without_the_stuck_ending_timeout() {
+        let config = Config { stuck_ending_secs: 0, ..Config::preset() };
+        let mut script = vec!["VS. Wild Pidgey", "VS. Wild Pidgey"];
+        script.extend(FLICKERING_END);
+        assert!(run_script(&script, &config).is_empty());
+    }
+
+    #[test]
+    fn stuck_ending_is_counted_after_the_timeout() {
+        let config = Config { stuck_ending_secs: 5, ..Config::preset() };
+        let species = SpeciesList::load(None).unwrap();
+        let mut state = BattleState::new(Box::new(SilentObserver));
+        for text in ["VS. Wild Pidgey", "VS. Wild Pidgey", ""] {
+            assert!(state.update(text, &species, &config).is_none());
+        }
+
+        // Pretend the battle started ending long enough ago
+        let BattlePhase::BattleEnding { since, .. } = &mut state.phase else {
+            panic!("expected the battle to be ending, got {:?}", state.phase);
+        };
+        *since -= Duration::from_secs(config.stuck_ending_secs);
+
+        let mut counted = Vec::new();
+        for text in &FLICKERING_END[1..] {
+            counted.extend(state.update(text, &species, &config));
+        }
+        let names: Vec<&str> = counted.iter().map(|battle| battle.name.as_str()).collect();
+        assert_eq!(names, ["Pidgey"]);
+        assert_eq!(state.phase, BattlePhase::Idle);
+    }
+
+    #[test]
+    fn later_read_corrects_an_early_misread_before_confirmation() {
+        let config = Config { active_confirm_reads: 3, ..Config::preset() };
+        let species = SpeciesList::load(None).unwrap();
+        let mut state = BattleState::new(Box::new(SilentObserver));
+        for text in ["VS. Wild Pidgeot", "VS. Wild Pidgey", "VS. Wild Pidgey"] {
+            assert!(state.update(text, &species, &config).is_none());
+        }
+        // The misread restarted detection, so two reads aren't enough yet
+        assert_eq!(state.phase, BattlePhase::PokemonDetected { name: "Pidgey".to_string(), reads: 2 });
+
+        let mut counted = Vec::new();
+        for text in ["VS. Wild Pidgey", "", ""] {
+            counted.extend(state.update(text, &species, &config));
+        }
+        let names: Vec<&str> = counted.iter().map(|battle| battle.name.as_str()).collect();
+        assert_eq!(names, ["Pidgey"]);
+    }
+
+    #[test]
+    fn species_that_starts_the_last_name_is_a_new_encounter() {
+        let script = [
+            "VS. Wild Pidgeotto", "VS. Wild Pidgeotto", "",
+            "VS. Wild Pidgeot", "VS. Wild Pidgeot", "", "",
+        ];
+        let counted = run_script(&script, &Config::preset());
+        let names: Vec<&str> = counted.iter().map(|battle| battle.name.as_str()).collect();
+        assert_eq!(names, ["Pidgeotto", "Pidgeot"]);
+    }
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn manual_pause_is_left_out_of_active_time() {
+        let mut pauses = PauseManager::new(Duration::ZERO, clock::MockClock::new());
+        let start = pauses.clock.now();
+        pauses.clock.advance(secs(10));
+        pauses.toggle_manual_pause();
+        pauses.clock.advance(secs(5));
+        assert_eq!(pauses.active_duration(start), secs(10));
+
+        pauses.toggle_manual_pause();
+        pauses.clock.advance(secs(3));
+        assert_eq!(pauses.active_duration(start), secs(13));
+    }
+
+    #[test]
+    fn window_pause_is_backdated_to_when_focus_was_lost() {
+        let mut pauses = PauseManager::new(secs(2), clock::MockClock::new());
+        let start = pauses.clock.now();
+        pauses.clock.advance(secs(10));
+        pauses.set_window_pause(true);
+        pauses.clock.advance(secs(1));
+        pauses.set_window_pause(true);
+        assert!(!pauses.is_paused(), "paused within the grace period");
+
+        pauses.clock.advance(secs(2));
+        pauses.set_window_pause(true);
+        assert!(pauses.is_paused());
+        pauses.clock.advance(secs(4));
+        pauses.set_window_pause(false);
+        pauses.clock.advance(secs(1));
+        // 18s passed, 7s of them unfocused
+        assert_eq!(pauses.active_duration(start), secs(11));
+    }
+
+    #[test]
+    fn focus_lost_within_the_grace_period_is_active_time() {
+        let mut pauses = PauseManager::new(secs(2), clock::MockClock::new());
+        let start = pauses.clock.now();
+        pauses.set_window_pause(true);
+        pauses.clock.advance(secs(1));
+        pauses.set_window_pause(false);
+        pauses.clock.advance(secs(4));
+        assert!(!pauses.is_paused());
+        assert_eq!(pauses.active_duration(start), secs(5));
+    }
+
+    #[test]
+    fn overlapping_manual_and_window_pauses_count_once() {
+        let mut pauses = PauseManager::new(Duration::ZERO, clock::MockClock::new());
+        let start = pauses.clock.now();
+        pauses.clock.advance(secs(10));
+        pauses.toggle_manual_pause();
+        pauses.clock.advance(secs(2));
+        pauses.set_window_pause(true);
+        pauses.clock.advance(secs(3));
+        pauses.toggle_manual_pause();
+        assert!(pauses.is_paused(), "window pause still holds");
+        pauses.clock.advance(secs(4));
+        assert_eq!(pauses.active_duration(start), secs(10));
+
+        pauses.set_window_pause(false);
+        pauses.clock.advance(secs(1));
+        // 20s passed, paused from 10s to 19s
+        assert_eq!(pauses.active_duration(start), secs(11));
+    }
+
+    #[test]
+    fn resumed_time_is_added_to_active_time() {
+        let mut pauses = PauseManager::new(Duration::ZERO, clock::MockClock::new());
+        pauses.resume_from(secs(60));
+        let start = pauses.clock.now();
+        pauses.clock.advance(secs(5));
+        assert_eq!(pauses.active_duration(start), secs(65));
+    }
+}
+
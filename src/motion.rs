@@ -0,0 +1,66 @@
+use image::imageops::FilterType;
+use image::{DynamicImage, GrayImage};
+
+/// Width frames are downscaled to before comparing; enough to see text
+/// appear or change while keeping the comparison far cheaper than OCR
+const SAMPLE_WIDTH: u32 = 64;
+
+/// Downscaled grayscale copy of a frame, compared with `mean_change`
+pub fn change_sample(image: &DynamicImage) -> GrayImage {
+    let height = (image.height() * SAMPLE_WIDTH / image.width().max(1)).max(1);
+    image.resize_exact(SAMPLE_WIDTH, height, FilterType::Triangle).to_luma8()
+}
+
+/// Mean absolute difference of two samples, from 0 to 1
+/// 
+/// Returns `None` when the samples differ in size (the region was resized),
+/// which callers treat as a change.
+pub fn mean_change(a: &GrayImage, b: &GrayImage) -> Option<f32> {
+    if a.dimensions() != b.dimensions() {
+        return None;
+    }
+    let total: u64 = a
+        .as_raw()
+        .iter()
+        .zip(b.as_raw())
+        .map(|(&x, &y)| u64::from(x.abs_diff(y)))
+        .sum();
+    Some(total as f32 / (a.as_raw().len().max(1) as f32 * f32::from(u8::MAX)))
+}
+
+/// Skips OCR on frames that barely differ from the last frame OCR read,
+/// reusing that frame's text instead
+/// 
+/// Frames are compared with the last one read rather than the previous
+/// one, so a slow fade still triggers a read once it adds up.
+pub struct OcrGate {
+    threshold: f32,
+    /// Sample and text of the last frame OCR read
+    reference: Option<(GrayImage, String)>,
+}
+
+impl OcrGate {
+    pub fn new(threshold: f32) -> Self {
+        Self { threshold, reference: None }
+    }
+
+    /// The text of the last read frame, if `sample` changed less than the
+    /// threshold since then (never for the first frame)
+    pub fn unchanged_text(&self, sample: &GrayImage) -> Option<&str> {
+        let (reference, text) = self.reference.as_ref()?;
+        mean_change(reference, sample)
+            .filter(|change| *change < self.threshold)
+            .map(|_| text.as_str())
+    }
+
+    /// Read the next frame whatever it looks like, e.g. after its text was
+    /// replaced by a better read
+    pub fn forget(&mut self) {
+        self.reference = None;
+    }
+
+    /// Remember a frame OCR just read
+    pub fn remember(&mut self, sample: GrayImage, text: &str) {
+        self.reference = Some((sample, text.to_string()));
+    }
+}
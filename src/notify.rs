@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Terminal bell character used as the alert sound
+const ALERT_SOUND: &str = "\x07";
+/// A single bell for routine counts; target alerts ring twice so the two are
+/// told apart by ear
+const COUNT_SOUND: &str = "\x07";
+
+/// Alerts the user when a target species is encountered
+/// 
+/// Each species has its own cooldown so a target that shows up repeatedly
+/// (or flickers in and out of the OCR read) doesn't flood the user.
+pub struct Notifier {
+    targets: Vec<String>,
+    cooldown: Duration,
+    last_notified: HashMap<String, Instant>,
+}
+
+impl Notifier {
+    pub fn new(targets: &[String], cooldown: Duration) -> Self {
+        Self {
+            targets: targets.iter().map(|t| t.to_lowercase()).collect(),
+            cooldown,
+            last_notified: HashMap::new(),
+        }
+    }
+
+    /// Whether the pokemon is on the target list
+    pub fn is_target(&self, name: &str) -> bool {
+        self.targets.contains(&name.to_lowercase())
+    }
+
+    /// Alert for a detected pokemon if it is a target and not on cooldown
+    /// 
+    /// # Returns
+    /// * `true` if a notification was sent
+    pub fn notify(&mut self, name: &str) -> bool {
+        if !self.is_target(name) {
+            return false;
+        }
+
+        let key = name.to_lowercase();
+        let now = Instant::now();
+        if let Some(last) = self.last_notified.get(&key)
+            && now.duration_since(*last) < self.cooldown
+        {
+            return false;
+        }
+        self.last_notified.insert(key, now);
+
+        print!("{}{}", ALERT_SOUND, ALERT_SOUND);
+        println!("★ TARGET ENCOUNTER: {} ★", name);
+        let _ = io::stdout().flush();
+        send_desktop_notification(name);
+        true
+    }
+}
+
+/// Ring the terminal bell once as audible feedback that a count registered
+pub fn beep_count() {
+    print!("{}", COUNT_SOUND);
+    let _ = io::stdout().flush();
+}
+
+/// Send a desktop notification via `notify-send`, ignoring failures
+/// (e.g. no notification daemon running)
+fn send_desktop_notification(name: &str) {
+    let _ = Command::new("notify-send")
+        .args(["--app-name=protean", "Target encounter!", &format!("Wild {} appeared", name)])
+        .spawn();
+}
@@ -1,35 +1,358 @@
-use anyhow::{Context, Result};
-use image::{DynamicImage, GrayImage};
-use ocrs::{ImageSource, OcrEngine};
+use anyhow::{bail, Context, Result};
+use image::imageops::FilterType;
+use image::{DynamicImage, GrayImage, RgbImage};
+use ocrs::{ImageSource, OcrEngine, OcrInput, TextItem, TextLine};
+use rten_imageproc::{find_contours, min_area_rect, simplify_polygon, Rect, RetrievalMode, RotatedRect};
+use rten_tensor::prelude::*;
+use rten_tensor::NdTensor;
 use screenshots::Screen;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
 
-use crate::config::Region;
+use crate::config::{ConfidenceFloor, Region, TextPolarity, ThresholdMode};
+use crate::debug::confidence_heat_map;
+use crate::error::ProteanError;
 
 // Image preprocessing constants
 const GRAYSCALE_LEVELS: usize = 256;
 const MAX_PIXEL_VALUE: u8 = 255;
 const MIN_PIXEL_VALUE: u8 = 0;
 
+// Tesseract backend constants
+const TESSERACT_PROGRAM: &str = "tesseract";
+/// Page segmentation mode for a uniform block of text
+const TESSERACT_BLOCK_MODE: &str = "6";
+/// Page segmentation mode for a single line of text
+const TESSERACT_SINGLE_LINE_MODE: &str = "7";
+
+// Word detection constants, matching what `OcrEngine::detect_words` uses
+/// Pixels each word box is grown by on every side
+const WORD_EXPAND_DISTANCE: f32 = 3.0;
+/// Smallest word box area kept, in pixels
+const WORD_MIN_AREA: f32 = 100.0;
+/// Tolerance in pixels when simplifying a word's outline
+const WORD_OUTLINE_EPSILON: f32 = 2.0;
+
+/// Placed between the words the model recognizes on a line
+pub const DEFAULT_WORD_SEPARATOR: &str = " ";
+
 /// Trait for OCR operations to allow for testing and different implementations
 pub trait OcrProvider {
     /// Extract text from an image
-    fn extract_text(&self, image: &DynamicImage, preprocess: bool) -> Result<String>;
+    fn extract_text(&self, image: &DynamicImage, preprocess: bool) -> Result<String, ProteanError>;
+
+    /// Render the detected words colored by confidence onto the image OCR sees
+    fn confidence_heat_map(&self, _image: &DynamicImage, _preprocess: bool) -> Result<RgbImage, ProteanError> {
+        Err(ProteanError::Ocr("confidence heat maps need the OCR engine".into()))
+    }
+}
+
+/// Tuning parameters for image preprocessing
+#[derive(Debug, Clone, Copy)]
+pub struct PreprocessOptions {
+    /// Percentile of darkest pixels clipped before histogram stretching (0-100)
+    pub clip_low_percentile: f32,
+    /// Percentile above which bright pixels are clipped before stretching (0-100)
+    pub clip_high_percentile: f32,
+    /// Text polarity, used to make the thresholded text dark on white
+    pub text_polarity: TextPolarity,
+    /// Global or local thresholding
+    pub threshold_mode: ThresholdMode,
+}
+
+impl Default for PreprocessOptions {
+    fn default() -> Self {
+        Self {
+            clip_low_percentile: 0.0,
+            clip_high_percentile: 100.0,
+            text_polarity: TextPolarity::Auto,
+            threshold_mode: ThresholdMode::Otsu,
+        }
+    }
+}
+
+/// Which low-confidence reads are removed from OCR output
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConfidenceFilter {
+    /// Whether words or whole lines are dropped (or nothing)
+    pub floor: ConfidenceFloor,
+    /// Lowest confidence kept (0-1)
+    pub min_confidence: f32,
+}
+
+impl ConfidenceFilter {
+    /// Whether any read can be dropped; a minimum of 0 keeps everything, so
+    /// it skips the confidence pass just like `ConfidenceFloor::Off`
+    pub fn is_active(&self) -> bool {
+        self.floor != ConfidenceFloor::Off && self.min_confidence > 0.0
+    }
+
+    /// The words of a line that pass the filter
+    /// 
+    /// # Arguments
+    /// * `words` - Each word of the line with its confidence
+    fn keep(&self, words: Vec<(String, f32)>) -> Vec<String> {
+        if !self.is_active() {
+            return words.into_iter().map(|(word, _)| word).collect();
+        }
+        match self.floor {
+            ConfidenceFloor::Off => words.into_iter().map(|(word, _)| word).collect(),
+            ConfidenceFloor::PerWord => words
+                .into_iter()
+                .filter(|(_, confidence)| *confidence >= self.min_confidence)
+                .map(|(word, _)| word)
+                .collect(),
+            ConfidenceFloor::PerLine => {
+                let average = words.iter().map(|(_, confidence)| confidence).sum::<f32>() / words.len().max(1) as f32;
+                if average >= self.min_confidence {
+                    words.into_iter().map(|(word, _)| word).collect()
+                } else {
+                    Vec::new()
+                }
+            }
+        }
+    }
 }
 
 /// Standard OCR provider using the ocrs library
 pub struct StandardOcrProvider<'a> {
     engine: &'a OcrEngine,
+    preprocess_options: PreprocessOptions,
+    word_separator: String,
+    single_line: bool,
+    confidence_filter: ConfidenceFilter,
+    /// Retry a frame without preprocessing when OCR of the preprocessed frame fails
+    preprocess_fallback: bool,
+    /// Whether the preprocessing fallback has been reported
+    fallback_warned: Cell<bool>,
+    /// Factor images are enlarged by before OCR (1.0 reads them as they are)
+    upscale_factor: f32,
 }
 
 impl<'a> StandardOcrProvider<'a> {
     pub fn new(engine: &'a OcrEngine) -> Self {
-        Self { engine }
+        Self {
+            engine,
+            preprocess_options: PreprocessOptions::default(),
+            word_separator: DEFAULT_WORD_SEPARATOR.to_string(),
+            single_line: false,
+            confidence_filter: ConfidenceFilter::default(),
+            preprocess_fallback: false,
+            fallback_warned: Cell::new(false),
+            upscale_factor: 1.0,
+        }
+    }
+
+    /// Enlarge images by `upscale_factor` before OCR, for small text
+    pub fn with_upscale_factor(mut self, upscale_factor: f32) -> Self {
+        self.upscale_factor = upscale_factor;
+        self
+    }
+
+    /// Use custom preprocessing parameters
+    pub fn with_preprocess_options(mut self, preprocess_options: PreprocessOptions) -> Self {
+        self.preprocess_options = preprocess_options;
+        self
+    }
+
+    /// Join the words of a line with `word_separator` instead of a space
+    pub fn with_word_separator(mut self, word_separator: &str) -> Self {
+        self.word_separator = word_separator.to_string();
+        self
+    }
+
+    /// Retry frames whose preprocessed OCR fails on the raw image instead of failing
+    pub fn with_preprocess_fallback(mut self, preprocess_fallback: bool) -> Self {
+        self.preprocess_fallback = preprocess_fallback;
+        self
+    }
+
+    /// Drop words or lines the detector wasn't confident were text
+    pub fn with_confidence_filter(mut self, confidence_filter: ConfidenceFilter) -> Self {
+        self.confidence_filter = confidence_filter;
+        self
+    }
+
+    /// Skip word detection and recognize the whole image as one line of text
+    pub fn with_single_line(mut self, single_line: bool) -> Self {
+        self.single_line = single_line;
+        self
     }
 }
 
 impl<'a> OcrProvider for StandardOcrProvider<'a> {
-    fn extract_text(&self, image: &DynamicImage, preprocess: bool) -> Result<String> {
-        extract_text(self.engine, image, preprocess)
+    fn extract_text(&self, image: &DynamicImage, preprocess: bool) -> Result<String, ProteanError> {
+        let image = upscale(image, self.upscale_factor);
+        let preprocess_options = preprocess.then_some(&self.preprocess_options);
+        let extract = |preprocess_options| extract_text_from_image(
+            self.engine, &image, preprocess_options, &self.word_separator, self.single_line, &self.confidence_filter);
+        let result = extract(preprocess_options);
+        let result = match result {
+            Err(e) if preprocess && self.preprocess_fallback => {
+                if !self.fallback_warned.replace(true) {
+                    eprintln!("⚠ OCR failed on a preprocessed frame ({:#}); retrying such frames without preprocessing", e);
+                }
+                extract(None)
+            }
+            result => result,
+        };
+        result.map_err(|e| ProteanError::Ocr(e.into()))
+    }
+
+    fn confidence_heat_map(&self, image: &DynamicImage, preprocess: bool) -> Result<RgbImage, ProteanError> {
+        // Draw on the same pixels extract_text would read
+        let image = upscale(image, self.upscale_factor);
+        let ocr_image = if preprocess {
+            DynamicImage::ImageLuma8(preprocess_image(&image, &self.preprocess_options))
+        } else {
+            image.into_owned()
+        };
+        confidence_heat_map(self.engine, &ocr_image).map_err(|e| ProteanError::Ocr(e.into()))
+    }
+}
+
+/// OCR provider that runs the `tesseract` command line program, for setups
+/// without the ocrs models
+/// 
+/// Each frame is piped to tesseract as a PNG, preprocessed the same way as
+/// for ocrs when preprocessing is on.
+pub struct TesseractOcrProvider {
+    preprocess_options: PreprocessOptions,
+    word_separator: String,
+    single_line: bool,
+    upscale_factor: f32,
+}
+
+impl TesseractOcrProvider {
+    /// Check that tesseract can be run, so a missing install is reported at
+    /// startup instead of on the first frame
+    pub fn new() -> Result<Self> {
+        let output = Command::new(TESSERACT_PROGRAM)
+            .arg("--version")
+            .stdin(Stdio::null())
+            .output()
+            .context("ocr_backend = \"tesseract\" but tesseract could not be run; install it \
+                      (e.g. the tesseract-ocr package) or set ocr_backend = \"ocrs\"")?;
+        if !output.status.success() {
+            bail!("tesseract --version failed ({})", output.status);
+        }
+        Ok(Self {
+            preprocess_options: PreprocessOptions::default(),
+            word_separator: DEFAULT_WORD_SEPARATOR.to_string(),
+            single_line: false,
+            upscale_factor: 1.0,
+        })
+    }
+
+    /// Enlarge images by `upscale_factor` before OCR, for small text
+    pub fn with_upscale_factor(mut self, upscale_factor: f32) -> Self {
+        self.upscale_factor = upscale_factor;
+        self
+    }
+
+    /// Use custom preprocessing parameters
+    pub fn with_preprocess_options(mut self, preprocess_options: PreprocessOptions) -> Self {
+        self.preprocess_options = preprocess_options;
+        self
+    }
+
+    /// Join the words of a line with `word_separator` instead of a space
+    pub fn with_word_separator(mut self, word_separator: &str) -> Self {
+        self.word_separator = word_separator.to_string();
+        self
+    }
+
+    /// Treat the image as a single line of text instead of a block
+    pub fn with_single_line(mut self, single_line: bool) -> Self {
+        self.single_line = single_line;
+        self
+    }
+
+    fn run(&self, image: &DynamicImage, preprocess: bool) -> Result<String> {
+        let image = upscale(image, self.upscale_factor);
+        let mut png = Vec::new();
+        if preprocess {
+            preprocess_image(&image, &self.preprocess_options)
+                .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)?;
+        } else {
+            image.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)?;
+        }
+
+        let page_mode = if self.single_line { TESSERACT_SINGLE_LINE_MODE } else { TESSERACT_BLOCK_MODE };
+        let mut child = Command::new(TESSERACT_PROGRAM)
+            .args(["stdin", "stdout", "--psm", page_mode])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to start tesseract")?;
+        child.stdin.take().context("tesseract stdin unavailable")?.write_all(&png)?;
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!("tesseract failed ({}): {}", output.status, String::from_utf8_lossy(&output.stderr).trim());
+        }
+
+        let text = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(&self.word_separator))
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        Ok(text)
+    }
+}
+
+impl OcrProvider for TesseractOcrProvider {
+    fn extract_text(&self, image: &DynamicImage, preprocess: bool) -> Result<String, ProteanError> {
+        self.run(image, preprocess).map_err(|e| ProteanError::Ocr(e.into()))
+    }
+}
+
+/// Scripted OCR provider that returns queued text instead of reading images
+/// 
+/// Lets the detection state machine run end-to-end without a screen or
+/// OCR models, e.g. to replay text captured from a problematic session.
+pub struct MockOcrProvider {
+    texts: RefCell<VecDeque<String>>,
+}
+
+impl MockOcrProvider {
+    pub fn new(texts: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            texts: RefCell::new(texts.into_iter().collect()),
+        }
+    }
+
+    /// Load a script with one OCR frame per line (blank lines are empty frames)
+    /// 
+    /// A literal `\n` splits a frame into separately recognized lines.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        Ok(Self::new(read_replay_script(path)?))
+    }
+
+    /// Number of scripted frames not yet returned
+    pub fn remaining(&self) -> usize {
+        self.texts.borrow().len()
+    }
+}
+
+/// Read a replay script into one OCR text per frame
+/// 
+/// A literal `\n` in a line splits the frame into separately recognized lines.
+pub fn read_replay_script(path: &Path) -> Result<Vec<String>> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read replay script {}", path.display()))?;
+    Ok(contents.lines().map(|line| line.trim().replace("\\n", "\n")).collect())
+}
+
+impl OcrProvider for MockOcrProvider {
+    fn extract_text(&self, _image: &DynamicImage, _preprocess: bool) -> Result<String, ProteanError> {
+        self.texts.borrow_mut().pop_front().ok_or_else(|| ProteanError::Ocr("replay script exhausted".into()))
     }
 }
 
@@ -42,51 +365,356 @@ impl<'a> OcrProvider for StandardOcrProvider<'a> {
 /// # Returns
 /// * `Ok(DynamicImage)` containing the captured region
 /// * `Err` if capture fails
-pub fn capture_region(screen: &Screen, region: &Region) -> Result<DynamicImage> {
+pub fn capture_region(screen: &Screen, region: &Region) -> Result<DynamicImage, ProteanError> {
     let image = screen
         .capture_area(region.x, region.y, region.width, region.height)
-        .context("Failed to capture screen region")?;
+        .map_err(|e| ProteanError::Capture {
+            what: format!("region {}x{} at ({}, {})", region.width, region.height, region.x, region.y),
+            cause: e.into(),
+        })?;
     Ok(DynamicImage::ImageRgba8(image))
 }
 
+/// Capture the entire screen
+/// 
+/// # Arguments
+/// * `screen` - The screen to capture
+/// 
+/// # Returns
+/// * `Ok(DynamicImage)` containing the full screen (in physical pixels)
+/// * `Err` if capture fails
+pub fn capture_screen(screen: &Screen) -> Result<DynamicImage, ProteanError> {
+    let image = screen
+        .capture()
+        .map_err(|e| ProteanError::Capture { what: "screen".to_string(), cause: e.into() })?;
+    Ok(DynamicImage::ImageRgba8(image))
+}
+
+/// Cut a region out of a full-screen capture
+/// 
+/// The capture may be in physical pixels while regions use screen
+/// coordinates, so the region is scaled to the capture first.
+/// 
+/// # Arguments
+/// * `screenshot` - A capture of the whole screen (see `capture_screen`)
+/// * `screen` - The screen it was captured from
+/// * `region` - The region, relative to the screen
+pub fn crop_screen_region(screenshot: &DynamicImage, screen: &Screen, region: &Region) -> DynamicImage {
+    let scale = screenshot.width() as f32 / screen.display_info.width.max(1) as f32;
+    let to_pixels = |value: f32| (value * scale).max(0.0) as u32;
+    screenshot.crop_imm(
+        to_pixels(region.x as f32),
+        to_pixels(region.y as f32),
+        to_pixels(region.width as f32).max(1),
+        to_pixels(region.height as f32).max(1),
+    )
+}
+
+/// Detect the words in an image along with the probability map they were
+/// found in, running the detection model once
+/// 
+/// `OcrEngine::detect_words` thresholds the same map but doesn't return it,
+/// so the words are found here the same way it does.
+/// 
+/// # Returns
+/// * `Ok((words, probabilities))` with the unordered word boxes and the
+///   probability of each input pixel being text
+/// * `Err` if the detection model fails
+pub fn detect_words_with_probabilities(engine: &OcrEngine, input: &OcrInput) -> Result<(Vec<RotatedRect>, NdTensor<f32, 2>)> {
+    let probabilities = engine.detect_text_pixels(input)?;
+    let threshold = engine.detection_threshold();
+    let text_mask = probabilities.map(|probability| *probability > threshold);
+    let words = find_contours(text_mask.view(), RetrievalMode::External)
+        .iter()
+        .filter_map(|outline| {
+            let points: Vec<_> = outline.iter().map(|point| point.to_f32()).collect();
+            min_area_rect(&simplify_polygon(&points, WORD_OUTLINE_EPSILON)).map(|mut rect| {
+                rect.resize(rect.width() + 2.0 * WORD_EXPAND_DISTANCE, rect.height() + 2.0 * WORD_EXPAND_DISTANCE);
+                rect
+            })
+        })
+        .filter(|rect| rect.area() >= WORD_MIN_AREA)
+        .collect();
+    Ok((words, probabilities))
+}
+
+/// Mean probability the detection model gave the text pixels from (`left`,
+/// `top`) to (`right`, `bottom`), used as the confidence of a word
+/// 
+/// Only pixels above `threshold` count, so the background around a word
+/// doesn't drag its score down. Pixels outside the probability map are
+/// ignored; a box with no text pixels scores 0.
+pub fn mean_text_probability(probabilities: &NdTensor<f32, 2>, threshold: f32, left: u32, top: u32, right: u32, bottom: u32) -> f32 {
+    let mut total = 0.0;
+    let mut pixels = 0;
+    for y in top..=bottom {
+        for x in left..=right {
+            if let Some(&probability) = probabilities.get([y as usize, x as usize])
+                && probability > threshold
+            {
+                total += probability;
+                pixels += 1;
+            }
+        }
+    }
+    if pixels > 0 { total / pixels as f32 } else { 0.0 }
+}
+
+/// Recognize the full image as a single line, skipping word detection
+fn recognize_whole_image(engine: &OcrEngine, input: &OcrInput, width: u32, height: u32) -> Result<Vec<Option<TextLine>>> {
+    let bounds = Rect::from_tlhw(0.0, 0.0, height as f32, width as f32);
+    engine.recognize_text(input, &[vec![RotatedRect::from_rect(bounds)]])
+}
+
+/// Find the bounding box of the first recognized text line accepted by `matches`
+/// 
+/// Runs the full detection and recognition pipeline and checks each line in
+/// reading order, so a large image such as a full-screen capture can be searched.
+/// 
+/// # Arguments
+/// * `engine` - The OCR engine to use
+/// * `image` - The image to search
+/// * `matches` - Predicate applied to each recognized line of text
+/// 
+/// # Returns
+/// * `Ok(Some((Region, String)))` with the line bounds in image pixels and its text
+/// * `Ok(None)` if no line matched
+/// * `Err` if OCR processing fails
+pub fn find_text_line(
+    engine: &OcrEngine,
+    image: &DynamicImage,
+    matches: impl Fn(&str) -> bool,
+) -> Result<Option<(Region, String)>> {
+    let rgb = image.to_rgb8();
+    let img_source = ImageSource::from_bytes(rgb.as_raw(), rgb.dimensions())?;
+    let ocr_input = engine.prepare_input(img_source)?;
+
+    let word_rects = engine.detect_words(&ocr_input)?;
+    let line_rects = engine.find_text_lines(&ocr_input, &word_rects);
+    let line_texts = engine.recognize_text(&ocr_input, &line_rects)?;
+
+    let found = line_texts
+        .iter()
+        .flatten()
+        .map(|line| (line, line.to_string()))
+        .find(|(_, text)| matches(text))
+        .map(|(line, text)| {
+            let rect = line.bounding_rect();
+            let region = Region {
+                x: rect.left(),
+                y: rect.top(),
+                width: rect.width().max(0) as u32,
+                height: rect.height().max(0) as u32,
+            };
+            (region, text)
+        });
+
+    Ok(found)
+}
+
+/// Enlarge an image by `factor` with Lanczos resampling
+/// 
+/// Small text is recognized better at a larger size, at the cost of OCR
+/// time growing with the pixel count. A factor of 1.0 or less borrows the
+/// image unchanged.
+pub fn upscale(image: &DynamicImage, factor: f32) -> Cow<'_, DynamicImage> {
+    if factor <= 1.0 {
+        return Cow::Borrowed(image);
+    }
+    let scaled = |length: u32| ((length as f32 * factor).round() as u32).max(1);
+    Cow::Owned(image.resize_exact(scaled(image.width()), scaled(image.height()), FilterType::Lanczos3))
+}
+
 /// Preprocess image for better OCR accuracy
 /// 
-/// Applies three transformations:
+/// Applies four transformations:
 /// 1. Grayscale conversion - simplifies processing
-/// 2. Contrast enhancement - histogram stretching for better dynamic range
-/// 3. Binary thresholding - Otsu's method for optimal black/white separation
+/// 2. Contrast enhancement - histogram stretching for better dynamic range,
+///    optionally clipping outlier pixels at the configured percentiles
+/// 3. Binary thresholding - Otsu's method for optimal black/white separation,
+///    or a local mean threshold per pixel in adaptive mode
+/// 4. Polarity correction - inverts light-on-dark text to dark-on-light
 /// 
 /// # Arguments
 /// * `image` - The input image to preprocess
+/// * `options` - Preprocessing parameters (clipping percentiles, text
+///   polarity, threshold mode)
 /// 
 /// # Returns
-/// * A binary (black and white) grayscale image optimized for OCR
-fn preprocess_image(image: &DynamicImage) -> GrayImage {
+/// * A binary (black and white) grayscale image optimized for OCR, or the
+///   unmodified grayscale image if it is empty or has a single color
+fn preprocess_image(image: &DynamicImage, options: &PreprocessOptions) -> GrayImage {
     // Convert to grayscale
     let mut grayscale = image.to_luma8();
     
-    // Apply contrast enhancement using histogram stretching
-    let (min_value, max_value) = grayscale.pixels().fold((MAX_PIXEL_VALUE, MIN_PIXEL_VALUE), |(min_val, max_val), pixel| {
-        let pixel_value = pixel.0[0];
-        (min_val.min(pixel_value), max_val.max(pixel_value))
-    });
+    // Find the stretch range, ignoring outliers beyond the clipping percentiles
+    let (min_value, max_value) = stretch_range(&grayscale, options);
     
-    // Stretch histogram only if there's meaningful contrast
-    if max_value > min_value {
-        let scale_factor = MAX_PIXEL_VALUE as f32 / (max_value - min_value) as f32;
-        for pixel in grayscale.pixels_mut() {
-            let original_value = pixel.0[0];
-            pixel.0[0] = (original_value.saturating_sub(min_value) as f32 * scale_factor) as u8;
-        }
+    // Empty or single-color images have nothing to stretch or separate,
+    // so return them unchanged instead of thresholding them to one color
+    if max_value <= min_value {
+        return grayscale;
     }
     
-    // Apply simple binary thresholding using Otsu's method approximation
-    let threshold = calculate_otsu_threshold(&grayscale);
+    // Apply contrast enhancement using histogram stretching
+    let scale_factor = MAX_PIXEL_VALUE as f32 / (max_value - min_value) as f32;
     for pixel in grayscale.pixels_mut() {
+        let original_value = pixel.0[0];
+        let stretched = original_value.saturating_sub(min_value) as f32 * scale_factor;
+        pixel.0[0] = stretched.min(MAX_PIXEL_VALUE as f32) as u8;
+    }
+    
+    match options.threshold_mode {
+        ThresholdMode::Otsu => {
+            let mut binary = binarize(&grayscale, calculate_otsu_threshold(&grayscale));
+            // The model expects dark text on a light background
+            if is_light_on_dark(&binary, options.text_polarity) {
+                invert(&mut binary);
+            }
+            binary
+        }
+        ThresholdMode::Adaptive { block_size, c } => {
+            // The local threshold keeps pixels darker than their surroundings
+            // as text, so light text is thresholded on the inverted image
+            let dark_text = || adaptive_threshold(&grayscale, block_size, c);
+            let light_text = || {
+                let mut inverted = grayscale.clone();
+                invert(&mut inverted);
+                adaptive_threshold(&inverted, block_size, c)
+            };
+            match options.text_polarity {
+                TextPolarity::DarkOnLight => dark_text(),
+                TextPolarity::LightOnDark => light_text(),
+                // A gradient defeats the global pixel count, so compare the
+                // two readings instead: the text is the one with fewer pixels
+                TextPolarity::Auto => {
+                    let (dark, light) = (dark_text(), light_text());
+                    if count_dark(&light) < count_dark(&dark) { light } else { dark }
+                }
+            }
+        }
+    }
+}
+
+fn count_dark(binary: &GrayImage) -> usize {
+    binary.pixels().filter(|pixel| pixel.0[0] == MIN_PIXEL_VALUE).count()
+}
+
+/// Turn pixels brighter than `threshold` white and the rest black
+fn binarize(grayscale: &GrayImage, threshold: u8) -> GrayImage {
+    let mut binary = grayscale.clone();
+    for pixel in binary.pixels_mut() {
         pixel.0[0] = if pixel.0[0] > threshold { MAX_PIXEL_VALUE } else { MIN_PIXEL_VALUE };
     }
+    binary
+}
+
+fn invert(image: &mut GrayImage) {
+    for pixel in image.pixels_mut() {
+        pixel.0[0] = MAX_PIXEL_VALUE - pixel.0[0];
+    }
+}
+
+/// Threshold each pixel against the mean of the window around it
+/// 
+/// A pixel turns black when it's darker than its window's mean minus `c`,
+/// so text stays separated from a background whose brightness changes
+/// across the image. Windows are cut off at the image edges. Window sums
+/// come from an integral image, so the cost doesn't grow with the window.
+/// 
+/// # Arguments
+/// * `grayscale` - The contrast-stretched image, text darker than background
+/// * `block_size` - Side of the square window in pixels
+/// * `c` - Subtracted from the window mean before comparing
+/// 
+/// # Returns
+/// * A binary image with the text black on white
+fn adaptive_threshold(grayscale: &GrayImage, block_size: u32, c: i32) -> GrayImage {
+    let (width, height) = grayscale.dimensions();
+    let (w, h) = (width as usize, height as usize);
+
+    // integral[y * (w + 1) + x] holds the sum of all pixels above and left of (x, y)
+    let mut integral = vec![0u64; (w + 1) * (h + 1)];
+    for y in 0..h {
+        let mut row_sum = 0u64;
+        for x in 0..w {
+            row_sum += u64::from(grayscale.get_pixel(x as u32, y as u32).0[0]);
+            integral[(y + 1) * (w + 1) + x + 1] = integral[y * (w + 1) + x + 1] + row_sum;
+        }
+    }
+
+    let radius = (block_size / 2) as usize;
+    let mut binary = GrayImage::new(width, height);
+    for y in 0..h {
+        let (top, bottom) = (y.saturating_sub(radius), (y + radius + 1).min(h));
+        for x in 0..w {
+            let (left, right) = (x.saturating_sub(radius), (x + radius + 1).min(w));
+            let sum = integral[bottom * (w + 1) + right] + integral[top * (w + 1) + left]
+                - integral[top * (w + 1) + right] - integral[bottom * (w + 1) + left];
+            let mean = sum as f32 / ((bottom - top) * (right - left)) as f32;
+            let value = grayscale.get_pixel(x as u32, y as u32).0[0];
+            let text = f32::from(value) < mean - c as f32;
+            binary.put_pixel(x as u32, y as u32, image::Luma([if text { MIN_PIXEL_VALUE } else { MAX_PIXEL_VALUE }]));
+        }
+    }
+    binary
+}
+
+/// Whether a thresholded image holds light text on a dark background
+/// 
+/// In `Auto` mode the text is assumed to be the minority of pixels, so
+/// more white than black pixels means the text is dark.
+fn is_light_on_dark(binary: &GrayImage, polarity: TextPolarity) -> bool {
+    match polarity {
+        TextPolarity::DarkOnLight => false,
+        TextPolarity::LightOnDark => true,
+        TextPolarity::Auto => {
+            let light_pixels = binary.pixels().filter(|pixel| pixel.0[0] == MAX_PIXEL_VALUE).count();
+            let total_pixels = (binary.width() * binary.height()) as usize;
+            light_pixels * 2 < total_pixels
+        }
+    }
+}
+
+/// Find the intensity range used for histogram stretching
+/// 
+/// With 0/100 percentiles this is the absolute min/max. Higher low or lower
+/// high percentiles ignore that fraction of the darkest/brightest pixels, so
+/// a stray highlight doesn't ruin the contrast expansion.
+/// 
+/// # Returns
+/// * `(low, high)` intensities; `low >= high` for empty or single-color images
+fn stretch_range(grayscale: &GrayImage, options: &PreprocessOptions) -> (u8, u8) {
+    let mut histogram = [0u32; GRAYSCALE_LEVELS];
+    for pixel in grayscale.pixels() {
+        histogram[pixel.0[0] as usize] += 1;
+    }
+    
+    let total_pixels = grayscale.width() as f64 * grayscale.height() as f64;
+    if total_pixels == 0.0 {
+        return (MAX_PIXEL_VALUE, MIN_PIXEL_VALUE);
+    }
+    
+    let low_percentile = options.clip_low_percentile.clamp(0.0, 100.0) as f64;
+    let high_percentile = options.clip_high_percentile.clamp(low_percentile as f32, 100.0) as f64;
+    let low_target = total_pixels * low_percentile / 100.0;
+    let high_target = total_pixels * high_percentile / 100.0;
+    
+    let mut low = None;
+    let mut high = MAX_PIXEL_VALUE;
+    let mut cumulative = 0.0;
+    for (intensity, &count) in histogram.iter().enumerate() {
+        cumulative += count as f64;
+        if low.is_none() && cumulative > low_target {
+            low = Some(intensity as u8);
+        }
+        if cumulative >= high_target && count > 0 {
+            high = intensity as u8;
+            break;
+        }
+    }
     
-    grayscale
+    (low.unwrap_or(MAX_PIXEL_VALUE), high)
 }
 
 /// Calculate optimal threshold using Otsu's method
@@ -108,6 +736,10 @@ fn calculate_otsu_threshold(grayscale: &GrayImage) -> u8 {
     }
     
     let total_pixels = grayscale.width() * grayscale.height();
+    if total_pixels == 0 {
+        return MIN_PIXEL_VALUE;
+    }
+    
     let mut weighted_sum = 0.0;
     for (intensity, &count) in histogram.iter().enumerate() {
         weighted_sum += intensity as f32 * count as f32;
@@ -148,48 +780,205 @@ fn calculate_otsu_threshold(grayscale: &GrayImage) -> u8 {
 
 /// Extract text from an image using OCR with optional preprocessing
 /// 
+/// Works on any image, not just live captures, so tools can OCR saved
+/// frames. `StandardOcrProvider` delegates here.
+/// 
 /// # Arguments
 /// * `engine` - The OCR engine to use
 /// * `image` - The image to extract text from
-/// * `preprocess` - Whether to apply preprocessing (grayscale, contrast, threshold)
+/// * `preprocess_options` - Preprocessing parameters, or `None` to skip preprocessing
+/// * `word_separator` - Placed between the words recognized on a line
+/// * `single_line` - Skip word detection and recognize the whole image as one
+///   line, faster for a small region holding a single line of text
+/// * `confidence_filter` - Low-confidence words or lines to drop (not applied
+///   in single line mode, which has no word boxes to score)
 /// 
 /// # Returns
-/// * `Ok(String)` containing the extracted text
+/// * `Ok(String)` containing the extracted text, one recognized line per text line
 /// * `Err` if OCR processing fails
-fn extract_text(engine: &OcrEngine, image: &DynamicImage, preprocess: bool) -> Result<String> {
+pub fn extract_text_from_image(
+    engine: &OcrEngine,
+    image: &DynamicImage,
+    preprocess_options: Option<&PreprocessOptions>,
+    word_separator: &str,
+    single_line: bool,
+    confidence_filter: &ConfidenceFilter,
+) -> Result<String> {
     // Create the appropriate image format based on preprocessing flag
     let preprocessed_grayscale;
-    let original_rgb;
+    let converted;
     
-    let img_source = if preprocess {
+    let img_source = if let Some(options) = preprocess_options {
         // Preprocess the image for better OCR accuracy
-        preprocessed_grayscale = preprocess_image(image);
+        preprocessed_grayscale = preprocess_image(image, options);
         let (width, height) = preprocessed_grayscale.dimensions();
         ImageSource::from_bytes(preprocessed_grayscale.as_raw(), (width, height))?
     } else {
-        // Use original image without preprocessing
-        original_rgb = image.to_rgb8();
-        let (width, height) = original_rgb.dimensions();
-        ImageSource::from_bytes(original_rgb.as_raw(), (width, height))?
+        // Hand the captured pixels to the engine as-is; it does its own
+        // grayscale conversion, so only exotic formats need converting first
+        let dimensions = (image.width(), image.height());
+        match image {
+            DynamicImage::ImageLuma8(pixels) => ImageSource::from_bytes(pixels.as_raw(), dimensions)?,
+            DynamicImage::ImageRgb8(pixels) => ImageSource::from_bytes(pixels.as_raw(), dimensions)?,
+            DynamicImage::ImageRgba8(pixels) => ImageSource::from_bytes(pixels.as_raw(), dimensions)?,
+            _ => {
+                converted = image.to_rgb8();
+                ImageSource::from_bytes(converted.as_raw(), dimensions)?
+            }
+        }
     };
     
     let ocr_input = engine.prepare_input(img_source)?;
-    
-    let word_rects = engine.detect_words(&ocr_input)?;
-    let line_rects = engine.find_text_lines(&ocr_input, &word_rects);
-    let line_texts = engine.recognize_text(&ocr_input, &line_rects)?;
+    // Confidence comes from the same probability map the words are found in,
+    // so the filter doesn't run the detection model a second time
+    let (line_texts, probabilities) = if single_line {
+        (recognize_whole_image(engine, &ocr_input, image.width(), image.height())?, None)
+    } else {
+        let (word_rects, probabilities) = detect_words_with_probabilities(engine, &ocr_input)?;
+        let line_rects = engine.find_text_lines(&ocr_input, &word_rects);
+        (engine.recognize_text(&ocr_input, &line_rects)?, confidence_filter.is_active().then_some(probabilities))
+    };
+    let threshold = engine.detection_threshold();
     
     let text = line_texts
         .iter()
         .filter_map(|opt_line| opt_line.as_ref())
-        .map(|line| {
-            line.words()
-                .map(|word| word.to_string())
-                .collect::<Vec<_>>()
-                .join(" ")
+        .filter_map(|line| {
+            let words: Vec<(String, f32)> = line
+                .words()
+                .map(|word| {
+                    let confidence = probabilities.as_ref().map_or(1.0, |probabilities| {
+                        let rect = word.bounding_rect();
+                        let clamp = |value: i32| value.max(0) as u32;
+                        mean_text_probability(probabilities, threshold, clamp(rect.left()),
+                                              clamp(rect.top()), clamp(rect.right()), clamp(rect.bottom()))
+                    });
+                    (word.to_string(), confidence)
+                })
+                .collect();
+            let read_anything = !words.is_empty();
+            let kept = confidence_filter.keep(words);
+            // Lines whose every word was filtered out are dropped entirely
+            (!kept.is_empty() || !read_anything).then(|| kept.join(word_separator))
         })
         .collect::<Vec<_>>()
-        .join(" ");
+        .join("\n");
     
     Ok(text.trim().to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    #[test]
+    fn preprocessing_an_empty_image_returns_it_unchanged() {
+        let empty = GrayImage::new(0, 0);
+        assert_eq!(calculate_otsu_threshold(&empty), MIN_PIXEL_VALUE);
+
+        let preprocessed = preprocess_image(&DynamicImage::ImageLuma8(empty), &PreprocessOptions::default());
+        assert_eq!(preprocessed.dimensions(), (0, 0));
+    }
+
+    #[test]
+    fn preprocessing_a_uniform_image_returns_it_unchanged() {
+        let gray = GrayImage::from_pixel(8, 4, Luma([128]));
+        // A single intensity has no second class to separate
+        assert_eq!(calculate_otsu_threshold(&gray), MIN_PIXEL_VALUE);
+
+        for threshold_mode in [ThresholdMode::Otsu, ThresholdMode::Adaptive { block_size: 3, c: 0 }] {
+            let options = PreprocessOptions { threshold_mode, ..PreprocessOptions::default() };
+            let preprocessed = preprocess_image(&DynamicImage::ImageLuma8(gray.clone()), &options);
+            assert_eq!(preprocessed, gray);
+        }
+    }
+
+    /// A 20x10 image of `background` with a 6x4 block of `text` in the middle
+    fn text_block(text: u8, background: u8) -> DynamicImage {
+        let mut image = GrayImage::from_pixel(20, 10, Luma([background]));
+        for y in 3..7 {
+            for x in 7..13 {
+                image.put_pixel(x, y, Luma([text]));
+            }
+        }
+        DynamicImage::ImageLuma8(image)
+    }
+
+    /// Whether the block is black on a white background
+    fn is_dark_on_light(binary: &GrayImage) -> bool {
+        binary.get_pixel(10, 5).0[0] == MIN_PIXEL_VALUE && binary.get_pixel(1, 1).0[0] == MAX_PIXEL_VALUE
+    }
+
+    #[test]
+    fn auto_polarity_makes_text_dark_on_light() {
+        let options = PreprocessOptions::default();
+        assert!(is_dark_on_light(&preprocess_image(&text_block(30, 220), &options)));
+        assert!(is_dark_on_light(&preprocess_image(&text_block(220, 30), &options)));
+    }
+
+    #[test]
+    fn explicit_polarity_overrides_the_pixel_count() {
+        let light_on_dark = PreprocessOptions { text_polarity: TextPolarity::LightOnDark, ..PreprocessOptions::default() };
+        assert!(is_dark_on_light(&preprocess_image(&text_block(220, 30), &light_on_dark)));
+
+        let dark_on_light = PreprocessOptions { text_polarity: TextPolarity::DarkOnLight, ..PreprocessOptions::default() };
+        assert!(is_dark_on_light(&preprocess_image(&text_block(30, 220), &dark_on_light)));
+        // Trusted as configured, so light text stays light
+        assert!(!is_dark_on_light(&preprocess_image(&text_block(220, 30), &dark_on_light)));
+    }
+
+    /// "VS. Wild Pidgey" with a confidently read name and a shaky "VS."
+    fn mixed_confidence_line() -> Vec<(String, f32)> {
+        vec![("VS.".to_string(), 0.2), ("Wild".to_string(), 0.6), ("Pidgey".to_string(), 0.9)]
+    }
+
+    #[test]
+    fn per_word_floor_drops_only_the_low_confidence_words() {
+        let filter = ConfidenceFilter { floor: ConfidenceFloor::PerWord, min_confidence: 0.5 };
+        assert_eq!(filter.keep(mixed_confidence_line()), ["Wild", "Pidgey"]);
+    }
+
+    #[test]
+    fn per_line_floor_keeps_or_drops_the_whole_line_by_its_average() {
+        // The line averages about 0.57
+        let lenient = ConfidenceFilter { floor: ConfidenceFloor::PerLine, min_confidence: 0.5 };
+        assert_eq!(lenient.keep(mixed_confidence_line()), ["VS.", "Wild", "Pidgey"]);
+        let strict = ConfidenceFilter { floor: ConfidenceFloor::PerLine, min_confidence: 0.6 };
+        assert!(strict.keep(mixed_confidence_line()).is_empty());
+    }
+
+    #[test]
+    fn zero_minimum_confidence_keeps_every_word() {
+        for floor in [ConfidenceFloor::PerWord, ConfidenceFloor::PerLine] {
+            let filter = ConfidenceFilter { floor, min_confidence: 0.0 };
+            assert!(!filter.is_active());
+            assert_eq!(filter.keep(mixed_confidence_line()), ["VS.", "Wild", "Pidgey"]);
+        }
+    }
+
+    #[test]
+    fn word_confidence_ignores_the_background_around_it() {
+        // A word's pixels in the middle of a 2x4 box of background
+        let probabilities = NdTensor::from_data([2, 4], vec![0.0, 0.8, 1.0, 0.1, 0.0, 0.9, 0.9, 0.0]);
+        let confidence = mean_text_probability(&probabilities, 0.2, 0, 0, 3, 1);
+        assert!((confidence - 0.9).abs() < 1e-6);
+        assert_eq!(mean_text_probability(&probabilities, 0.2, 0, 0, 0, 1), 0.0);
+    }
+
+    #[test]
+    fn off_floor_keeps_every_word_at_any_minimum() {
+        let floor: ConfidenceFloor = serde_json::from_str("\"off\"").unwrap();
+        let filter = ConfidenceFilter { floor, min_confidence: 1.0 };
+        assert!(!filter.is_active());
+        assert_eq!(filter.keep(mixed_confidence_line()), ["VS.", "Wild", "Pidgey"]);
+    }
+
+    #[test]
+    fn preset_filters_per_line() {
+        let filter = crate::config::Config::preset().confidence_filter();
+        assert!(filter.is_active());
+        assert_eq!(filter.floor, ConfidenceFloor::PerLine);
+    }
+}
+
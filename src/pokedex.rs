@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use crate::pokemon::name_key;
+
+/// National dex bundled with the binary: dex number, display name, then
+/// any other names (other languages) the species is read as, tab-separated
+const EMBEDDED_DEX: &str = include_str!("../data/dex.tsv");
+
+static BUNDLED: LazyLock<Pokedex> = LazyLock::new(|| Pokedex::parse(EMBEDDED_DEX));
+
+/// The dex entry closest to a misread name, by Levenshtein distance
+/// (case-insensitive)
+/// 
+/// Ties go to the entry listed first.
+/// 
+/// # Arguments
+/// * `name` - The name as read, e.g. "Pidqey"
+/// * `dex` - Known names, e.g. the species list
+/// * `max_distance` - Most single-character edits the name may be away
+/// 
+/// # Returns
+/// * `Some(String)` with the entry as written in `dex`
+/// * `None` if no entry is within `max_distance`
+pub fn snap_to_pokedex(name: &str, dex: &[String], max_distance: usize) -> Option<String> {
+    let key: Vec<char> = name_key(name).chars().collect();
+    dex.iter()
+        .filter_map(|entry| {
+            let entry_key: Vec<char> = name_key(entry).chars().collect();
+            // Lengths alone already put some entries out of range
+            if key.len().abs_diff(entry_key.len()) > max_distance {
+                return None;
+            }
+            let distance = levenshtein(&key, &entry_key);
+            (distance <= max_distance).then_some((distance, entry))
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, entry)| entry.clone())
+}
+
+/// Fewest single-character insertions, deletions and substitutions that
+/// turn `a` into `b`
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, a_char) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Maps recognized names in any bundled language to national dex numbers
+pub struct Pokedex {
+    /// Lowercased name -> dex number
+    numbers: HashMap<String, u16>,
+    /// Dex number -> display name
+    display_names: HashMap<u16, String>,
+}
+
+impl Pokedex {
+    /// The dex bundled with the binary
+    pub fn bundled() -> &'static Self {
+        &BUNDLED
+    }
+
+    fn parse(contents: &str) -> Self {
+        let mut numbers = HashMap::new();
+        let mut display_names = HashMap::new();
+        for line in contents.lines() {
+            let mut fields = line.split('\t').map(str::trim);
+            let Some(number) = fields.next().and_then(|field| field.parse().ok()) else {
+                continue;
+            };
+            for (index, name) in fields.filter(|name| !name.is_empty()).enumerate() {
+                if index == 0 {
+                    display_names.insert(number, name.to_string());
+                }
+                numbers.insert(name_key(name), number);
+            }
+        }
+        Self { numbers, display_names }
+    }
+
+    /// National dex number of a recognized name (case-insensitive)
+    pub fn number(&self, name: &str) -> Option<u16> {
+        self.numbers.get(&name_key(name)).copied()
+    }
+
+    /// Display name for a recognized name, e.g. "Pidgey" for "ポッポ"
+    ///
+    /// Names not in the dex give `None` and keep their raw string.
+    pub fn display_name(&self, name: &str) -> Option<&str> {
+        self.number(name)
+            .and_then(|number| self.display_names.get(&number))
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_in_any_language_map_to_their_dex_number() {
+        let dex = Pokedex::bundled();
+        assert_eq!(dex.number("Pidgey"), Some(16));
+        assert_eq!(dex.number("pidgey"), Some(16));
+        assert_eq!(dex.number("ポッポ"), Some(16));
+        assert_eq!(dex.number("Nidoran♀"), Some(29));
+        assert_eq!(dex.number("Nidoran♂"), Some(32));
+        assert_eq!(dex.number("Ho-Oh"), Some(250));
+    }
+
+    #[test]
+    fn display_name_is_the_first_listed_name() {
+        let dex = Pokedex::bundled();
+        assert_eq!(dex.display_name("ポッポ"), Some("Pidgey"));
+        assert_eq!(dex.display_name("PIDGEY"), Some("Pidgey"));
+    }
+
+    #[test]
+    fn unknown_names_have_no_dex_entry() {
+        let dex = Pokedex::bundled();
+        assert_eq!(dex.number("Pidqey"), None);
+        assert_eq!(dex.display_name("Pidqey"), None);
+    }
+
+    #[test]
+    fn parse_skips_lines_without_a_number() {
+        let dex = Pokedex::parse("# comment\n16\tPidgey\tRoucool\n\nbad\tName\n");
+        assert_eq!(dex.number("Roucool"), Some(16));
+        assert_eq!(dex.number("Name"), None);
+    }
+}
@@ -1,11 +1,137 @@
-use std::collections::HashMap;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::config::{Config, DetectionStrategy, OutcomeConfig, PatternAnchor};
+use crate::pokedex::Pokedex;
 
 /// The pattern to search for in OCR text
 const VS_WILD_PATTERN: &str = "VS. WILD";
 
+/// Marker preceding the pokemon's level on the banner ("Lv.5")
+const LEVEL_MARKER: &str = "Lv";
+
+/// Symbols that are part of a species name (Nidoran♀ and Nidoran♂ are
+/// different species)
+const GENDER_SYMBOLS: [char; 2] = ['♀', '♂'];
+
+/// Placeholder marking where the name appears in a battle log pattern
+pub const NAME_PLACEHOLDER: &str = "{name}";
+
+/// Extract the encountered pokemon name using the configured detection strategy
+/// 
+/// A configured `name_regex` takes the place of the built-in pattern
+/// matching; battle log end phrases still apply. With the `line_start`
+/// pattern anchor only lines beginning with the pattern are considered;
+/// otherwise the lines are searched as one text. Names that are only a
+/// word of the pattern or a configured stop word are rejected (see
+/// `is_stop_word`). Names are returned in composed form (see `compose_name`).
+/// 
+/// # Arguments
+/// * `text` - The OCR text read from the capture region, one recognized line per text line
+/// * `config` - Configuration selecting banner or battle log detection
+/// 
+/// # Returns
+/// * `Some(String)` containing the pokemon name if an encounter is found
+/// * `None` otherwise
+pub fn extract_encounter_name(text: &str, config: &Config) -> Option<String> {
+    let name = match config.pattern_anchor {
+        PatternAnchor::Anywhere => extract_name_from_line(&text.replace('\n', " "), config),
+        PatternAnchor::LineStart => {
+            let battle_over = config.detection_strategy == DetectionStrategy::BattleLog
+                && contains_any_phrase(&text.replace('\n', " "), &config.battle_log_end_phrases);
+            if battle_over {
+                return None;
+            }
+            text.lines()
+                .map(str::trim_start)
+                .filter(|line| starts_with_pattern(line, config))
+                .find_map(|line| extract_name_from_line(line, config))
+        }
+    }?;
+    let name = compose_name(&name);
+    (!is_stop_word(&name, config)).then_some(name)
+}
+
+/// Compose a name's characters (Unicode NFC), so a name always compares
+/// equal to itself whether an accent was read as part of its letter or as
+/// a separate combining mark ("Flabébé")
+pub fn compose_name(name: &str) -> String {
+    name.nfc().collect()
+}
+
+/// Case- and composition-insensitive key a name is looked up by
+pub fn name_key(name: &str) -> String {
+    compose_name(name).to_lowercase()
+}
+
+/// Whether a candidate name is really a word of the pattern (or part of one,
+/// e.g. "WIL") or a configured stop word, compared ignoring case and punctuation
+/// 
+/// The pattern's words end up read as the name when OCR misses the actual
+/// name, e.g. "VS. Wild" read as "Wild" after the first "VS." is garbled.
+fn is_stop_word(name: &str, config: &Config) -> bool {
+    let letters = |word: &str| -> String {
+        word.nfc().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+    };
+    let candidate = letters(name);
+    if candidate.is_empty() {
+        return true;
+    }
+
+    let pattern = match config.detection_strategy {
+        DetectionStrategy::Banner => VS_WILD_PATTERN,
+        DetectionStrategy::BattleLog => config.battle_log_pattern.as_str(),
+    };
+    let is_pattern_word = pattern
+        .split_whitespace()
+        .filter(|word| *word != NAME_PLACEHOLDER)
+        .any(|word| letters(word).contains(&candidate));
+    is_pattern_word || config.name_stop_words.iter().any(|word| letters(word) == candidate)
+}
+
+/// Apply the configured detection to a single line of text
+fn extract_name_from_line(text: &str, config: &Config) -> Option<String> {
+    if let Some(name_regex) = &config.name_regex {
+        let battle_over = config.detection_strategy == DetectionStrategy::BattleLog
+            && contains_any_phrase(text, &config.battle_log_end_phrases);
+        return if battle_over { None } else { name_regex.extract(text) };
+    }
+
+    match config.detection_strategy {
+        DetectionStrategy::Banner => extract_pokemon_name(text),
+        DetectionStrategy::BattleLog => {
+            extract_battle_log_name(text, &config.battle_log_pattern, &config.battle_log_end_phrases)
+        }
+    }
+}
+
+/// Whether a line begins with the text before the name in the configured pattern
+/// 
+/// A `name_regex` carries its own anchoring, so every line qualifies.
+fn starts_with_pattern(line: &str, config: &Config) -> bool {
+    if config.name_regex.is_some() {
+        return true;
+    }
+
+    let prefix = match config.detection_strategy {
+        DetectionStrategy::Banner => VS_WILD_PATTERN,
+        DetectionStrategy::BattleLog => {
+            let pattern = &config.battle_log_pattern;
+            pattern.split_once(NAME_PLACEHOLDER).map_or(pattern.as_str(), |(prefix, _)| prefix).trim()
+        }
+    };
+    find_ignore_ascii_case(line, prefix) == Some(0)
+}
+
 /// Extract pokemon name from text containing "VS. Wild [Pokemon Name]"
 /// Uses case-insensitive matching without allocating uppercase string
 /// 
+/// A gender symbol read as a separate word is kept with the name
+/// ("Nidoran ♀" gives "Nidoran♀").
+/// 
 /// # Arguments
 /// * `text` - The OCR text to search for the pattern
 /// 
@@ -13,64 +139,415 @@ const VS_WILD_PATTERN: &str = "VS. WILD";
 /// * `Some(String)` containing the pokemon name if pattern is found
 /// * `None` if pattern is not found or no name follows the pattern
 pub fn extract_pokemon_name(text: &str) -> Option<String> {
-    // Find "VS. WILD" using case-insensitive byte-by-byte comparison
-    let vs_pos = text
-        .char_indices()
-        .position(|(i, _)| {
-            text[i..]
-                .chars()
-                .zip(VS_WILD_PATTERN.chars())
-                .take(VS_WILD_PATTERN.len())
-                .all(|(a, b)| a.eq_ignore_ascii_case(&b))
-        })?;
+    let vs_pos = find_ignore_ascii_case(text, VS_WILD_PATTERN)?;
     
     let after_wild = text[vs_pos..]
         .char_indices()
         .nth(VS_WILD_PATTERN.chars().count())
         .map(|(i, _)| vs_pos + i)?;
     
-    let remaining = text[after_wild..].trim_start();
-    
-    remaining
-        .split_whitespace()
-        .next()
-        .filter(|s| !s.is_empty())
-        .map(|s| s.to_string())
+    let mut words = text[after_wild..].split_whitespace();
+    let name = words.next()?;
+    match words.next() {
+        Some(symbol) if symbol.chars().count() == 1 && symbol.chars().all(|c| GENDER_SYMBOLS.contains(&c)) => {
+            Some(format!("{}{}", name, symbol))
+        }
+        _ => Some(name.to_string()),
+    }
+}
+
+/// Extract the level from a "Lv.N" token, e.g. "VS. Wild Pidgey Lv.5"
+/// 
+/// Accepts the common OCR variants "Lv.5", "LV5", "Lv. 5" and "Lv:5".
+/// 
+/// # Arguments
+/// * `text` - The OCR text to search for a level token
+/// 
+/// # Returns
+/// * `Some(u32)` containing the level if a level token is found
+/// * `None` otherwise
+pub fn extract_level(text: &str) -> Option<u32> {
+    let mut tokens = text.split_whitespace().peekable();
+    while let Some(token) = tokens.next() {
+        let Some(rest) = token
+            .get(..LEVEL_MARKER.len())
+            .filter(|prefix| prefix.eq_ignore_ascii_case(LEVEL_MARKER))
+            .map(|_| token[LEVEL_MARKER.len()..].trim_start_matches(['.', ':']))
+        else {
+            continue;
+        };
+
+        // The number may be split from the marker ("Lv. 5")
+        let number = if rest.is_empty() { tokens.peek().copied().unwrap_or_default() } else { rest };
+        let digits: String = number.chars().take_while(|c| c.is_ascii_digit()).collect();
+        if let Ok(level) = digits.parse() {
+            return Some(level);
+        }
+    }
+    None
+}
+
+/// Extract pokemon name from a battle log message such as "A wild Pidgey appeared!"
+/// 
+/// The pattern contains a `{name}` placeholder; the text before it must be
+/// present in the OCR text and the name is read up to the text after it.
+/// Messages containing any of the end phrases are treated as the battle
+/// being over, so the lingering "appeared" line is not detected again.
+/// 
+/// # Arguments
+/// * `text` - The OCR text read from the battle log region
+/// * `pattern` - Message pattern, e.g. "A wild {name} appeared"
+/// * `end_phrases` - Phrases that mark the end of a battle ("fled", "fainted", ...)
+/// 
+/// # Returns
+/// * `Some(String)` containing the pokemon name if the message is found
+/// * `None` if the message is missing or the battle has already ended
+pub fn extract_battle_log_name(text: &str, pattern: &str, end_phrases: &[String]) -> Option<String> {
+    if contains_any_phrase(text, end_phrases) {
+        return None;
+    }
+
+    let (prefix, suffix) = pattern.split_once(NAME_PLACEHOLDER).unwrap_or((pattern, ""));
+    let prefix = prefix.trim();
+    let suffix = suffix.trim();
+
+    let prefix_pos = find_ignore_ascii_case(text, prefix)?;
+    let remaining = text[prefix_pos + prefix.len()..].trim_start();
+
+    let name = if suffix.is_empty() {
+        remaining.split_whitespace().next()?
+    } else {
+        let suffix_pos = find_ignore_ascii_case(remaining, suffix)?;
+        remaining[..suffix_pos].trim()
+    };
+
+    let name = name.trim_end_matches(['!', '.']);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Check whether the text contains any of the phrases (ASCII case-insensitive)
+pub fn contains_any_phrase(text: &str, phrases: &[String]) -> bool {
+    phrases
+        .iter()
+        .filter(|phrase| !phrase.is_empty())
+        .any(|phrase| find_ignore_ascii_case(text, phrase).is_some())
+}
+
+/// How a completed battle ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BattleOutcome {
+    Fled,
+    Caught,
+    Defeated,
+}
+
+/// Whether an encounter was announced with the shiny banner
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShinyStatus {
+    #[default]
+    Normal,
+    Shiny,
+}
+
+/// Check the OCR text for the configured shiny pattern (ASCII case-insensitive)
+pub fn detect_shiny(text: &str, pattern: &str) -> ShinyStatus {
+    if !pattern.is_empty() && find_ignore_ascii_case(text, pattern).is_some() {
+        ShinyStatus::Shiny
+    } else {
+        ShinyStatus::Normal
+    }
+}
+
+/// Classify an outcome message using the configured phrase lists
+/// 
+/// Caught is checked first, then defeated, then fled, so a message
+/// matching several lists resolves to the most significant outcome.
+pub fn classify_outcome(text: &str, outcome_config: &OutcomeConfig) -> Option<BattleOutcome> {
+    if contains_any_phrase(text, &outcome_config.caught_phrases) {
+        Some(BattleOutcome::Caught)
+    } else if contains_any_phrase(text, &outcome_config.defeated_phrases) {
+        Some(BattleOutcome::Defeated)
+    } else if contains_any_phrase(text, &outcome_config.fled_phrases) {
+        Some(BattleOutcome::Fled)
+    } else {
+        None
+    }
+}
+
+/// Find the byte offset of `needle` in `text` using ASCII case-insensitive
+/// comparison without allocating an uppercase copy
+fn find_ignore_ascii_case(text: &str, needle: &str) -> Option<usize> {
+    let needle_len = needle.chars().count();
+    text.char_indices()
+        .find(|(i, _)| {
+            text[*i..].chars().count() >= needle_len
+                && text[*i..]
+                    .chars()
+                    .zip(needle.chars())
+                    .all(|(a, b)| a.eq_ignore_ascii_case(&b))
+        })
+        .map(|(i, _)| i)
 }
 
 /// Normalize Pokemon names by merging superstrings into substrings
 /// 
 /// This is useful when OCR occasionally captures extra characters.
-/// For example, "Pidgey!" would be merged into "Pidgey".
+/// For example, "Pidgey!" would be merged into "Pidgey". Names that only
+/// differ by confusable glyphs ("Pidgey" / "Pidqey") are merged as well.
 /// 
 /// # Arguments
 /// * `text_counts` - HashMap of pokemon names to encounter counts
+/// * `confusables` - Glyph sequences OCR mixes up, mapped to a canonical replacement
 /// 
 /// # Returns
 /// * A new HashMap with normalized names and merged counts
-pub fn normalize_pokemon_names(text_counts: &HashMap<String, usize>) -> HashMap<String, usize> {
+pub fn normalize_pokemon_names(
+    text_counts: &HashMap<String, usize>,
+    confusables: &BTreeMap<String, String>,
+) -> HashMap<String, usize> {
+    let name_map = normalized_name_map(text_counts, confusables);
     let mut normalized: HashMap<String, usize> = HashMap::new();
     let mut keys: Vec<_> = text_counts.keys().collect();
-    keys.sort_by_key(|k| k.len()); // Process shorter strings first
+    keys.sort_by_key(|k| k.len()); // Report merges shortest first
     
     for key in keys {
         let count = text_counts[key];
-        let mut merged = false;
-        
-        // Check if this key is a superstring of any existing normalized key
-        for (norm_key, norm_count) in normalized.iter_mut() {
-            if key.contains(norm_key.as_str()) && key != norm_key {
-                *norm_count += count;
-                merged = true;
-                println!("  Merged \"{}\" ({}) into \"{}\"", key, count, norm_key);
-                break;
-            }
+        let norm_key = &name_map[key];
+        if norm_key != key {
+            println!("  Merged \"{}\" ({}) into \"{}\"", key, count, norm_key);
         }
+        *normalized.entry(norm_key.clone()).or_insert(0) += count;
+    }
+    
+    normalized
+}
+
+/// Map every recorded name to the name it normalizes into
+/// 
+/// Superstrings first merge into a shorter recorded name. The remaining
+/// names are then grouped by their canonical form (see `canonical_form`)
+/// and each group merges into its most frequently read name. Names in the
+/// bundled dex finally map to their species' display name, so reads in
+/// different languages merge. Used to keep per-species data in step with
+/// normalized counts.
+/// 
+/// # Arguments
+/// * `text_counts` - HashMap of pokemon names to encounter counts
+/// * `confusables` - Glyph sequences OCR mixes up, mapped to a canonical replacement
+/// 
+/// # Returns
+/// * A HashMap from each recorded name to its normalized name
+pub fn normalized_name_map(
+    text_counts: &HashMap<String, usize>,
+    confusables: &BTreeMap<String, String>,
+) -> HashMap<String, String> {
+    let mut name_map = superstring_name_map(text_counts);
+
+    let mut merged_counts: HashMap<String, usize> = HashMap::new();
+    for (name, target) in &name_map {
+        *merged_counts.entry(target.clone()).or_insert(0) += text_counts[name];
+    }
+
+    // Most frequent read wins; ties go to the shorter, then alphabetical name
+    let mut targets: Vec<_> = merged_counts.into_iter().collect();
+    targets.sort_by(|a, b| {
+        Reverse(a.1).cmp(&Reverse(b.1))
+            .then_with(|| a.0.len().cmp(&b.0.len()))
+            .then_with(|| a.0.cmp(&b.0))
+    });
+    let mut representatives: HashMap<String, String> = HashMap::new();
+    for (target, _) in targets {
+        representatives.entry(canonical_form(&target, confusables)).or_insert(target);
+    }
+
+    // Names of the same species in another language merge under its display name
+    let pokedex = Pokedex::bundled();
+    for target in name_map.values_mut() {
+        let representative = &representatives[&canonical_form(target, confusables)];
+        *target = pokedex.display_name(representative).unwrap_or(representative).to_string();
+    }
+    name_map
+}
+
+/// Reduce a name to a form shared by its common OCR misreads
+/// 
+/// Confusable sequences are replaced (longest first), accents are dropped,
+/// the result is lowercased and runs of the same character collapse into
+/// one, so "Rattata", "Ratata" and "RattaIa" all compare equal, as do
+/// "Flabébé" and "Flabebe". Gender symbols are kept.
+pub fn canonical_form(name: &str, confusables: &BTreeMap<String, String>) -> String {
+    let mut sequences: Vec<_> = confusables.iter().collect();
+    sequences.sort_by_key(|(from, _)| Reverse(from.chars().count()));
+
+    let mut replaced = name.to_string();
+    for (from, to) in sequences {
+        replaced = replaced.replace(from.as_str(), to);
+    }
+
+    let mut canonical = String::with_capacity(replaced.len());
+    for ch in replaced.nfd().filter(|c| !is_combining_mark(*c)).flat_map(char::to_lowercase) {
+        if !canonical.ends_with(ch) {
+            canonical.push(ch);
+        }
+    }
+    canonical
+}
+
+/// Map every recorded name to the shorter recorded name it is a superstring of
+/// 
+/// A name whose extra characters include a gender symbol is a different
+/// species ("Nidoran♀" is not a misread of "Nidoran") and isn't merged.
+fn superstring_name_map(text_counts: &HashMap<String, usize>) -> HashMap<String, String> {
+    let mut canonical: Vec<&String> = Vec::new();
+    let mut name_map = HashMap::new();
+    let mut keys: Vec<_> = text_counts.keys().collect();
+    keys.sort_by_key(|k| k.len()); // Process shorter strings first
+    
+    for key in keys {
+        // Check if this key is a superstring of any existing normalized key
+        let target = canonical
+            .iter()
+            .find(|norm_key| {
+                key.contains(norm_key.as_str())
+                    && key != **norm_key
+                    && !key.replacen(norm_key.as_str(), "", 1).contains(GENDER_SYMBOLS)
+            })
+            .copied();
         
-        if !merged {
-            normalized.insert(key.clone(), count);
+        match target {
+            Some(norm_key) => {
+                name_map.insert(key.clone(), norm_key.clone());
+            }
+            None => {
+                canonical.push(key);
+                name_map.insert(key.clone(), key.clone());
+            }
         }
     }
     
-    normalized
+    name_map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::default_confusables;
+
+    fn same_canonical_form(a: &str, b: &str) -> bool {
+        let confusables = default_confusables();
+        canonical_form(a, &confusables) == canonical_form(b, &confusables)
+    }
+
+    #[test]
+    fn l_capital_i_and_one_are_confusable() {
+        assert!(same_canonical_form("Bellsprout", "BeIIsprout"));
+        assert!(same_canonical_form("Bellsprout", "Be11sprout"));
+        assert!(same_canonical_form("Bellsprout", "Be1Isprout"));
+    }
+
+    #[test]
+    fn capital_o_and_zero_are_confusable() {
+        assert!(same_canonical_form("Metapod", "Metap0d"));
+        assert!(same_canonical_form("ONIX", "0NIX"));
+    }
+
+    #[test]
+    fn doubled_letters_are_confusable() {
+        assert!(same_canonical_form("Rattata", "Ratata"));
+        assert!(same_canonical_form("Rattata", "Rattatta"));
+    }
+
+    #[test]
+    fn different_species_stay_apart() {
+        assert!(!same_canonical_form("Pidgey", "Pidgeot"));
+        assert!(!same_canonical_form("Nidoran♀", "Nidoran♂"));
+    }
+
+    #[test]
+    fn confusable_reads_merge_into_the_most_frequent_name() {
+        let counts = HashMap::from([
+            ("Bellsprout".to_string(), 3),
+            ("Be11sprout".to_string(), 1),
+            ("Metap0d".to_string(), 1),
+            ("Metapod".to_string(), 2),
+        ]);
+        let normalized = normalize_pokemon_names(&counts, &default_confusables());
+        assert_eq!(normalized, HashMap::from([("Bellsprout".to_string(), 4), ("Metapod".to_string(), 3)]));
+    }
+
+    fn anchored(pattern_anchor: PatternAnchor) -> Config {
+        Config { pattern_anchor, ..Config::preset() }
+    }
+
+    #[test]
+    fn anywhere_anchor_matches_mid_line() {
+        let config = anchored(PatternAnchor::Anywhere);
+        assert_eq!(extract_encounter_name("Bag VS. Wild Pidgey", &config).as_deref(), Some("Pidgey"));
+        assert_eq!(extract_encounter_name("Bag\nVS. Wild Pidgey", &config).as_deref(), Some("Pidgey"));
+    }
+
+    #[test]
+    fn line_start_anchor_needs_the_pattern_to_begin_a_line() {
+        let config = anchored(PatternAnchor::LineStart);
+        assert_eq!(extract_encounter_name("Bag VS. Wild Pidgey", &config), None);
+        assert_eq!(extract_encounter_name("Bag\n  VS. Wild Pidgey", &config).as_deref(), Some("Pidgey"));
+    }
+
+    #[test]
+    fn line_start_anchor_applies_to_the_battle_log_pattern() {
+        let config = Config { detection_strategy: DetectionStrategy::BattleLog, ..anchored(PatternAnchor::LineStart) };
+        assert_eq!(extract_encounter_name("You see: A wild Pidgey appeared!", &config), None);
+        assert_eq!(extract_encounter_name("You see:\nA wild Pidgey appeared!", &config).as_deref(), Some("Pidgey"));
+    }
+
+    #[test]
+    fn missing_name_is_not_read_from_the_pattern() {
+        let config = Config::preset();
+        for text in ["VS. Wild", "VS. Wild WILD", "VS. Wild Wi", "VS. VS. Wild", "VS. Wild ???"] {
+            assert_eq!(extract_encounter_name(text, &config), None, "{:?}", text);
+        }
+        assert_eq!(extract_encounter_name("VS. Wild Wigglytuff", &config).as_deref(), Some("Wigglytuff"));
+    }
+
+    #[test]
+    fn missing_name_is_not_read_from_the_battle_log_pattern() {
+        let config = Config { detection_strategy: DetectionStrategy::BattleLog, ..Config::preset() };
+        assert_eq!(extract_encounter_name("A wild wild appeared!", &config), None);
+        assert_eq!(extract_encounter_name("A wild appeared!", &config), None);
+    }
+
+    #[test]
+    fn configured_stop_words_are_not_names() {
+        let config = Config { name_stop_words: vec!["Trainer".to_string()], ..Config::preset() };
+        assert_eq!(extract_encounter_name("VS. Wild TRAINER", &config), None);
+    }
+
+    #[test]
+    fn accented_names_are_composed() {
+        let config = Config::preset();
+        let decomposed = "VS. Wild Flabe\u{301}be\u{301}";
+        assert_eq!(extract_encounter_name(decomposed, &config).as_deref(), Some("Flabébé"));
+        assert!(crate::species::SpeciesList::load(None).unwrap().contains("Flabe\u{301}be\u{301}"));
+        assert!(same_canonical_form("Flabébé", "Flabebe"));
+    }
+
+    #[test]
+    fn gender_symbol_read_as_its_own_word_stays_with_the_name() {
+        let config = Config::preset();
+        assert_eq!(extract_encounter_name("VS. Wild Nidoran ♀", &config).as_deref(), Some("Nidoran♀"));
+        assert_eq!(extract_encounter_name("VS. Wild Nidoran♂ Lv. 5", &config).as_deref(), Some("Nidoran♂"));
+    }
+
+    #[test]
+    fn nidoran_genders_are_not_merged() {
+        let counts = HashMap::from([
+            ("Nidoran♀".to_string(), 2),
+            ("Nidoran♂".to_string(), 1),
+            ("Nidoran".to_string(), 3),
+        ]);
+        assert_eq!(normalize_pokemon_names(&counts, &default_confusables()), counts);
+    }
 }
+
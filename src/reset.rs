@@ -0,0 +1,47 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+
+use crate::config::Config;
+use crate::lock::running_instance;
+use crate::ui::ask_yes_no;
+
+/// Delete the saved session and daemon snapshot and, with `lifetime`, the
+/// all-time statistics
+/// 
+/// Refuses while another instance holds the lock, since it would write the
+/// files straight back.
+/// 
+/// # Arguments
+/// * `config` - Locates a snapshot written to a custom `snapshot_path`
+/// * `lifetime` - Also delete the lifetime statistics
+/// * `confirm` - Ask before deleting anything
+pub fn reset(config: &Config, lifetime: bool, confirm: bool) -> Result<()> {
+    if let Some(pid) = running_instance()? {
+        bail!("Another protean instance (PID {}) is running; stop it before resetting", pid);
+    }
+
+    let mut paths = vec![config.snapshot_file()?, Config::session_path()?];
+    if lifetime {
+        paths.push(Config::lifetime_path()?);
+    }
+    paths.retain(|path| path.exists());
+    if paths.is_empty() {
+        println!("Nothing to reset");
+        return Ok(());
+    }
+
+    println!("This will delete:");
+    for path in &paths {
+        println!("  {}", path.display());
+    }
+    if confirm && !ask_yes_no("Delete these files?")? {
+        println!("Nothing was deleted");
+        return Ok(());
+    }
+
+    for path in &paths {
+        fs::remove_file(path).with_context(|| format!("Failed to delete {}", path.display()))?;
+        println!("✓ Removed {}", path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::config::{Config, ConfigFormat};
+use crate::species::SpeciesList;
+
+/// The name-handling rules tuned for a game or client, shareable as one file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleSet {
+    /// Words never counted as a name (see `name_stop_words`)
+    #[serde(default)]
+    pub name_stop_words: Vec<String>,
+    /// Glyph sequences OCR confuses, mapped to their replacement
+    #[serde(default)]
+    pub confusables: BTreeMap<String, String>,
+    /// Species list replacing the embedded one; empty keeps the embedded list
+    #[serde(default)]
+    pub species: Vec<String>,
+}
+
+impl RuleSet {
+    /// The rules currently in effect
+    /// 
+    /// The species list is only included when the config replaces the
+    /// embedded one.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let species = match &config.species_list_path {
+            Some(path) => SpeciesList::load(Some(path))?.names().to_vec(),
+            None => Vec::new(),
+        };
+        Ok(Self {
+            name_stop_words: config.name_stop_words.clone(),
+            confusables: config.confusables.clone(),
+            species,
+        })
+    }
+
+    /// Reject empty entries and mappings that contradict each other
+    pub fn validate(&self) -> Result<()> {
+        if self.name_stop_words.iter().any(|word| word.trim().is_empty()) {
+            bail!("name_stop_words contains an empty word");
+        }
+        if self.species.iter().any(|name| name.trim().is_empty()) {
+            bail!("species contains an empty name");
+        }
+        for (from, to) in &self.confusables {
+            if from.is_empty() {
+                bail!("confusables contains an empty sequence");
+            }
+            if from == to {
+                bail!("confusable \"{}\" maps to itself", from);
+            }
+            if self.confusables.get(to) == Some(from) {
+                bail!("confusables \"{}\" -> \"{}\" and \"{}\" -> \"{}\" undo each other", from, to, to, from);
+            }
+        }
+
+        let mut seen = HashSet::new();
+        for name in &self.species {
+            if !seen.insert(name.trim().to_lowercase()) {
+                bail!("species lists \"{}\" more than once", name.trim());
+            }
+        }
+        if let Some(word) = self.name_stop_words.iter().find(|word| seen.contains(&word.trim().to_lowercase())) {
+            bail!("\"{}\" is both a stop word and a species", word.trim());
+        }
+        Ok(())
+    }
+}
+
+/// Write the rules in effect to `path` (TOML, YAML or JSON by extension)
+pub fn export_rules(config: &Config, path: &Path) -> Result<()> {
+    let rules = RuleSet::from_config(config)?;
+    let contents = ConfigFormat::from_path(path)?.serialize(&rules)?;
+    fs::write(path, contents).with_context(|| format!("Failed to write rules {}", path.display()))?;
+
+    println!("✓ Exported {} to {}", describe(&rules), path.display());
+    Ok(())
+}
+
+/// Replace the config's rules with the ones in `path` and save the config
+/// 
+/// An imported species list is written to the config folder and replaces
+/// the configured one.
+pub fn import_rules(config: &mut Config, path: &Path) -> Result<()> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read rules {}", path.display()))?;
+    let rules: RuleSet = ConfigFormat::from_path(path)?.parse(&contents)
+        .with_context(|| format!("Failed to parse rules {}", path.display()))?;
+    rules.validate().with_context(|| format!("Invalid rules in {}", path.display()))?;
+
+    if !rules.species.is_empty() {
+        let species_path = Config::imported_species_path()?;
+        let list: String = rules.species.iter().map(|name| format!("{}\n", name.trim())).collect();
+        fs::write(&species_path, list)
+            .with_context(|| format!("Failed to write species list {}", species_path.display()))?;
+        config.species_list_path = Some(species_path);
+    }
+    config.name_stop_words = rules.name_stop_words.iter().map(|word| word.trim().to_string()).collect();
+    config.confusables = rules.confusables.clone();
+    config.save()?;
+
+    println!("✓ Imported {} from {}", describe(&rules), path.display());
+    if rules.species.is_empty() {
+        println!("  (no species list - keeping the current one)");
+    }
+    Ok(())
+}
+
+/// Summary such as "3 stop words, 5 confusables and 151 species"
+fn describe(rules: &RuleSet) -> String {
+    format!("{} stop words, {} confusables and {} species",
+            rules.name_stop_words.len(), rules.confusables.len(), rules.species.len())
+}
@@ -0,0 +1,187 @@
+use anyhow::{bail, Context, Result};
+use screenshots::display_info::DisplayInfo;
+use screenshots::Screen;
+use std::env;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::card::coordinate_grid;
+use crate::config::{Config, DetectionStrategy, Region};
+use crate::ocr::{capture_screen, crop_screen_region};
+use crate::ui::{ask_yes_no, print_region_preview};
+
+/// Tools that let a rectangle be dragged out on screen, tried in order:
+/// slurp (Wayland) and slop (X11)
+const SELECTION_TOOLS: [&str; 2] = ["slurp", "slop"];
+/// Output format both tools accept, in virtual desktop coordinates
+const SELECTION_FORMAT: &str = "%x %y %w %h";
+/// Distance between the grid lines of the fallback screenshot, in screen units
+const GRID_SPACING: u32 = 100;
+
+/// Result of asking a selection tool for a rectangle
+enum Dragged {
+    Region(Region),
+    /// The selection was cancelled (e.g. with Escape)
+    Cancelled,
+    /// None of the selection tools is installed
+    NoTool,
+}
+
+/// Let the user pick the capture region on screen
+///
+/// With slurp or slop installed the region is dragged out directly;
+/// otherwise a screenshot with a coordinate grid is saved and the corners
+/// are typed in. The selection is previewed before it replaces the region
+/// of the configured detection strategy, and is saved on request.
+///
+/// # Arguments
+/// * `screen` - The screen the region is captured from
+/// * `config` - Configuration to update with the selected region
+///
+/// # Returns
+/// * `Ok(true)` if a region was selected and accepted
+/// * `Ok(false)` if the selection was cancelled
+pub fn select_region(screen: &Screen, config: &mut Config) -> Result<bool> {
+    println!("\n=== Region Selection ===");
+    println!("Start a wild battle so '{}' is visible on screen.", config.pattern_label());
+    print!("Press Enter to select the region...");
+    io::stdout().flush()?;
+    io::stdin().read_line(&mut String::new())?;
+
+    let display = screen.display_info;
+    let region = loop {
+        let selected = match drag_region(&display)? {
+            Dragged::Region(region) => Some(region),
+            Dragged::Cancelled => None,
+            Dragged::NoTool => {
+                println!("(install slurp or slop to drag the region out instead)");
+                enter_corners(screen)?
+            }
+        };
+        let Some(region) = selected else {
+            println!("✗ Region selection cancelled");
+            return Ok(false);
+        };
+
+        if !fits_on_screen(&region, &display) {
+            println!("✗ {}x{} at ({}, {}) is not within the {}x{} screen {} - select it again",
+                     region.width, region.height, region.x, region.y,
+                     display.width, display.height, config.monitor_index);
+            continue;
+        }
+        let preview = crop_screen_region(&capture_screen(screen)?, screen, &region);
+        print_region_preview(&preview, &region, "Selected region");
+        if ask_yes_no("Use this region?")? {
+            break region;
+        }
+    };
+
+    match config.detection_strategy {
+        DetectionStrategy::Banner => config.region = region,
+        DetectionStrategy::BattleLog => config.battle_log_region = region,
+    }
+    if ask_yes_no("Save this configuration for future use?")? {
+        config.save()?;
+    }
+    Ok(true)
+}
+
+/// Drag out a rectangle with the first selection tool that is installed,
+/// converting it to coordinates relative to the screen
+fn drag_region(display: &DisplayInfo) -> Result<Dragged> {
+    let Some(tool) = SELECTION_TOOLS.into_iter().find(|tool| is_installed(tool)) else {
+        return Ok(Dragged::NoTool);
+    };
+    println!("Drag a rectangle around the text to read (Escape cancels)");
+    let output = Command::new(tool)
+        .args(["-f", SELECTION_FORMAT])
+        .output()
+        .with_context(|| format!("Failed to run {}", tool))?;
+    if !output.status.success() {
+        return Ok(Dragged::Cancelled);
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let values: Vec<i64> = stdout
+        .split_whitespace()
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .with_context(|| format!("Unexpected {} output: {}", tool, stdout.trim()))?;
+    let [x, y, width, height] = values[..] else {
+        bail!("Unexpected {} output: {}", tool, stdout.trim());
+    };
+    if width <= 0 || height <= 0 {
+        return Ok(Dragged::Cancelled);
+    }
+    Ok(Dragged::Region(Region {
+        x: (x - i64::from(display.x)) as i32,
+        y: (y - i64::from(display.y)) as i32,
+        width: width as u32,
+        height: height as u32,
+    }))
+}
+
+/// Whether an executable of this name is on the `PATH`
+fn is_installed(tool: &str) -> bool {
+    env::var_os("PATH").is_some_and(|paths| env::split_paths(&paths).any(|dir| dir.join(tool).is_file()))
+}
+
+/// Save a screenshot with a coordinate grid and ask for two corners
+///
+/// # Returns
+/// * `Ok(None)` if a corner was left blank
+fn enter_corners(screen: &Screen) -> Result<Option<Region>> {
+    let screenshot = capture_screen(screen)?.to_rgb8();
+    let scale = screenshot.width() as f32 / screen.display_info.width.max(1) as f32;
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let path = PathBuf::from(format!("protean-screen-{}.png", timestamp));
+    coordinate_grid(&screenshot, GRID_SPACING, scale)
+        .save(&path)
+        .with_context(|| format!("Failed to write screenshot to {}", path.display()))?;
+    println!("✓ Screenshot saved to: {} (grid lines every {} pixels)", path.display(), GRID_SPACING);
+    println!("Open it and read off two opposite corners of the region.");
+
+    let Some((x1, y1)) = read_corner("First corner (x,y, blank to cancel): ")? else {
+        return Ok(None);
+    };
+    let Some((x2, y2)) = read_corner("Opposite corner (x,y, blank to cancel): ")? else {
+        return Ok(None);
+    };
+    Ok(Some(Region {
+        x: x1.min(x2),
+        y: y1.min(y2),
+        width: x1.abs_diff(x2).max(1),
+        height: y1.abs_diff(y2).max(1),
+    }))
+}
+
+/// Read an `x,y` point, asking again until it parses
+fn read_corner(prompt: &str) -> Result<Option<(i32, i32)>> {
+    loop {
+        print!("{}", prompt);
+        io::stdout().flush()?;
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        let entered = input.trim();
+        if entered.is_empty() {
+            return Ok(None);
+        }
+        let point = entered
+            .split_once(',')
+            .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)));
+        match point {
+            Some(point) => return Ok(Some(point)),
+            None => println!("✗ Enter the corner as x,y (e.g. 640,120)"),
+        }
+    }
+}
+
+/// Whether the region lies entirely on the screen
+fn fits_on_screen(region: &Region, display: &DisplayInfo) -> bool {
+    region.x >= 0
+        && region.y >= 0
+        && i64::from(region.x) + i64::from(region.width) <= i64::from(display.width)
+        && i64::from(region.y) + i64::from(region.height) <= i64::from(display.height)
+}
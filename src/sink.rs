@@ -0,0 +1,260 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::binlog::BinaryLogSink;
+use crate::config::{Config, OverlayFormat, OverlayMetric, SinkConfig};
+use crate::pokemon::{BattleOutcome, ShinyStatus};
+
+/// Counts waiting to be posted before new ones are dropped
+const WEBHOOK_QUEUE_CAPACITY: usize = 32;
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+/// A counted encounter, as reported to every sink
+#[derive(Debug, Serialize)]
+pub struct CountEvent<'a> {
+    pub name: &'a str,
+    /// National dex number, or null for names not in the bundled dex
+    pub dex: Option<u16>,
+    /// Encounters of this name so far
+    pub count: usize,
+    /// Encounters of every name so far on this target
+    pub total: usize,
+    /// Monitored client the encounter was counted on
+    pub target: &'a str,
+    pub outcome: Option<BattleOutcome>,
+    pub level: Option<u32>,
+    /// Whether the banner showed the shiny pattern (normal without one configured)
+    pub shiny: ShinyStatus,
+    pub area: Option<&'a str>,
+    /// Whether the species is one of the notify targets
+    pub is_target: bool,
+    /// Encounters since the previous target or shiny, including this one
+    pub since_last_target: Option<usize>,
+    /// Whether the name is in the species list
+    pub known_species: bool,
+    /// Active hunting time in seconds when the encounter was counted
+    pub active_secs: u64,
+}
+
+/// Destination for counted encounters
+pub trait CountSink {
+    /// Report one counted encounter
+    fn record(&mut self, event: &CountEvent);
+}
+
+/// Build the sinks listed in the config
+/// 
+/// # Arguments
+/// * `monitored_targets` - Number of clients being monitored; counts name
+///   their client when there is more than one
+pub fn build_sinks(config: &Config, monitored_targets: usize) -> Result<Vec<Box<dyn CountSink>>> {
+    config
+        .sinks
+        .iter()
+        .map(|sink| -> Result<Box<dyn CountSink>> {
+            Ok(match sink {
+                SinkConfig::Stdout => Box::new(StdoutSink {
+                    show_target: monitored_targets > 1,
+                    show_outcome: config.outcome.is_some(),
+                }),
+                SinkConfig::File { path } => Box::new(FileSink::open(path)?),
+                SinkConfig::Webhook { url, names } => Box::new(WebhookSink::spawn(url.clone(), names)),
+                SinkConfig::BinaryLog { path } => Box::new(BinaryLogSink::open(path)?),
+                SinkConfig::Overlay { path, metric, format } => {
+                    Box::new(OverlaySink::create(path.clone(), metric.clone(), *format)?)
+                }
+            })
+        })
+        .collect()
+}
+
+/// Prints counts to the terminal
+pub struct StdoutSink {
+    /// Name the client each count came from (several targets monitored)
+    show_target: bool,
+    /// Print the battle outcome (outcome tracking enabled)
+    show_outcome: bool,
+}
+
+impl CountSink for StdoutSink {
+    fn record(&mut self, event: &CountEvent) {
+        if self.show_target {
+            println!("✓ Counted: \"{}\" (Total: {}) [{}]", event.name, event.count, event.target);
+        } else {
+            println!("✓ Counted: \"{}\" (Total: {})", event.name, event.count);
+        }
+        match event.since_last_target {
+            Some(since) if event.is_target => println!("  ★ Target found after {} encounters", since),
+            Some(since) if event.shiny == ShinyStatus::Shiny => println!("  ★ Shiny found after {} encounters", since),
+            Some(since) => println!("  {} encounters since last target", since),
+            None => {}
+        }
+        if event.shiny == ShinyStatus::Shiny {
+            println!("  ✨ Shiny encounter!");
+        }
+        if !event.known_species {
+            println!("  ⚠ \"{}\" is not a known species (possible misread)", event.name);
+        }
+        if self.show_outcome {
+            match event.outcome {
+                Some(outcome) => println!("  Outcome: {:?}", outcome),
+                None => println!("  Outcome: unknown"),
+            }
+        }
+    }
+}
+
+/// Appends one JSON line per count to a file
+pub struct FileSink {
+    file: File,
+}
+
+impl FileSink {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open count log {}", path.display()))?;
+        Ok(Self { file })
+    }
+}
+
+impl CountSink for FileSink {
+    fn record(&mut self, event: &CountEvent) {
+        let written = serde_json::to_string(event)
+            .map_err(anyhow::Error::from)
+            .and_then(|line| Ok(writeln!(self.file, "{}", line)?));
+        if let Err(e) = written {
+            eprintln!("Failed to write count log: {:#}", e);
+        }
+    }
+}
+
+/// Rewrites a file with one headline number after every count
+///
+/// The file is replaced atomically so a text source polling it never
+/// reads a partial write. It starts at zero when monitoring starts.
+pub struct OverlaySink {
+    path: PathBuf,
+    metric: OverlayMetric,
+    format: OverlayFormat,
+    /// Latest value per monitored client, summed when several are monitored
+    values: BTreeMap<String, usize>,
+}
+
+impl OverlaySink {
+    pub fn create(path: PathBuf, metric: OverlayMetric, format: OverlayFormat) -> Result<Self> {
+        let sink = Self { path, metric, format, values: BTreeMap::new() };
+        sink.write()?;
+        Ok(sink)
+    }
+
+    fn write(&self) -> Result<()> {
+        let value: usize = self.values.values().sum();
+        let contents = match self.format {
+            OverlayFormat::Text => value.to_string(),
+            OverlayFormat::Json => serde_json::json!({ "metric": self.metric, "value": value }).to_string(),
+        };
+        let temp_path = self.path.with_extension("tmp");
+        fs::write(&temp_path, contents)
+            .with_context(|| format!("Failed to write overlay {}", temp_path.display()))?;
+        fs::rename(&temp_path, &self.path)
+            .with_context(|| format!("Failed to replace overlay {}", self.path.display()))
+    }
+}
+
+impl CountSink for OverlaySink {
+    fn record(&mut self, event: &CountEvent) {
+        let value = match &self.metric {
+            OverlayMetric::Total => event.total,
+            OverlayMetric::Species(species) if species.eq_ignore_ascii_case(event.name) => event.count,
+            OverlayMetric::Species(_) => return,
+            OverlayMetric::DryStreak if event.is_target || event.shiny == ShinyStatus::Shiny => 0,
+            OverlayMetric::DryStreak => event.since_last_target.unwrap_or(event.total),
+        };
+        self.values.insert(event.target.to_string(), value);
+        if let Err(e) = self.write() {
+            eprintln!("{:#}", e);
+        }
+    }
+}
+
+/// POSTs each count as JSON from a background thread
+///
+/// The payload carries a human-readable `content` field (what Discord
+/// webhooks display) alongside the event fields. Posting never blocks the
+/// capture loop: when the queue is full the count is dropped with a warning.
+/// Queued counts are still delivered when the sink is dropped on exit.
+/// With a name filter only counts of those species are posted, e.g. to be
+/// pinged only for the target of a hunt.
+pub struct WebhookSink {
+    sender: Option<SyncSender<String>>,
+    worker: Option<JoinHandle<()>>,
+    /// Lowercased species to post; empty posts every count
+    names: Vec<String>,
+}
+
+impl WebhookSink {
+    pub fn spawn(url: String, names: &[String]) -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<String>(WEBHOOK_QUEUE_CAPACITY);
+        let worker = thread::spawn(move || {
+            for body in receiver {
+                let posted = ureq::post(&url)
+                    .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+                    .set("Content-Type", "application/json")
+                    .send_string(&body);
+                if let Err(e) = posted {
+                    eprintln!("Webhook error: {}", e);
+                }
+            }
+        });
+        Self {
+            sender: Some(sender),
+            worker: Some(worker),
+            names: names.iter().map(|name| name.to_lowercase()).collect(),
+        }
+    }
+}
+
+impl CountSink for WebhookSink {
+    fn record(&mut self, event: &CountEvent) {
+        if !self.names.is_empty() && !self.names.contains(&event.name.to_lowercase()) {
+            return;
+        }
+        let mut payload = match serde_json::to_value(event) {
+            Ok(payload) => payload,
+            Err(e) => {
+                eprintln!("Failed to serialize webhook payload: {}", e);
+                return;
+            }
+        };
+        payload["content"] = format!("Counted {} (total {})", event.name, event.count).into();
+
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        match sender.try_send(payload.to_string()) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => eprintln!("⚠ Webhook queue full - dropped count for \"{}\"", event.name),
+            Err(TrySendError::Disconnected(_)) => eprintln!("⚠ Webhook thread stopped - dropped count for \"{}\"", event.name),
+        }
+    }
+}
+
+impl Drop for WebhookSink {
+    fn drop(&mut self) {
+        // Closing the queue lets the worker finish what's queued and exit
+        self.sender.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
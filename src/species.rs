@@ -0,0 +1,162 @@
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::pokemon::{compose_name, name_key};
+
+/// Species list bundled with the binary (one name per line)
+const EMBEDDED_SPECIES_LIST: &str = include_str!("../data/species.txt");
+
+/// Most words a split name is rejoined from
+const MAX_SPLIT_NAME_PARTS: usize = 3;
+
+/// Known species names used to validate detected names
+pub struct SpeciesList {
+    /// Names as listed, keyed by lowercased name for case-insensitive lookups
+    names: HashMap<String, String>,
+    /// The same names, sorted
+    sorted: Vec<String>,
+}
+
+impl SpeciesList {
+    /// Load the species list from a user file, or the embedded list when no path is given
+    ///
+    /// The file is newline-delimited; blank lines and surrounding whitespace
+    /// are ignored. An unreadable or empty file is an error so a typo in the
+    /// config doesn't silently disable validation.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let Some(path) = path else {
+            return Ok(Self::parse(EMBEDDED_SPECIES_LIST));
+        };
+
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read species list {}", path.display()))?;
+        let list = Self::parse(&contents);
+        if list.names.is_empty() {
+            bail!("Species list {} contains no names", path.display());
+        }
+        Ok(list)
+    }
+
+    fn parse(contents: &str) -> Self {
+        let names: HashMap<String, String> = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|name| (name_key(name), compose_name(name)))
+            .collect();
+        let mut sorted: Vec<String> = names.values().cloned().collect();
+        sorted.sort_unstable();
+        Self { names, sorted }
+    }
+
+    /// Whether the name is a known species (case-insensitive)
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains_key(&name_key(name))
+    }
+
+    /// The name as written in the list (case-insensitive lookup)
+    pub fn canonical(&self, name: &str) -> Option<&str> {
+        self.names.get(&name_key(name)).map(String::as_str)
+    }
+
+    /// Every listed name, sorted
+    pub fn names(&self) -> &[String] {
+        &self.sorted
+    }
+
+    /// Listed names starting with `prefix` (case-insensitive), sorted
+    pub fn completions(&self, prefix: &str) -> Vec<&str> {
+        let prefix = name_key(prefix);
+        let mut matches: Vec<&str> = self
+            .names
+            .iter()
+            .filter(|(lowercase, _)| lowercase.starts_with(&prefix))
+            .map(|(_, name)| name.as_str())
+            .collect();
+        matches.sort_unstable();
+        matches
+    }
+
+    /// Merge runs of adjacent words that only form a known species together
+    ///
+    /// Recovers names OCR splits into several words ("VS. Wild Pid gey"
+    /// becomes "VS. Wild Pidgey"). A word that is already a species is never
+    /// merged with its neighbours. Line breaks are kept.
+    pub fn rejoin_split_names(&self, text: &str) -> String {
+        text.lines()
+            .map(|line| {
+                let words: Vec<&str> = line.split_whitespace().collect();
+                let mut joined = Vec::with_capacity(words.len());
+                let mut index = 0;
+                while index < words.len() {
+                    let parts = (2..=MAX_SPLIT_NAME_PARTS.min(words.len() - index))
+                        .rev()
+                        .find(|&parts| {
+                            !self.contains(words[index]) && self.contains(&words[index..index + parts].concat())
+                        })
+                        .unwrap_or(1);
+                    joined.push(words[index..index + parts].concat());
+                    index += parts;
+                }
+                joined.join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pokedex::{snap_to_pokedex, Pokedex};
+
+    #[test]
+    fn embedded_list_covers_the_national_dex() {
+        let species = SpeciesList::load(None).unwrap();
+        for name in ["Bulbasaur", "Mew", "Chikorita", "Ho-Oh", "Lucario", "Flabébé", "Sirfetch'd", "Pecharunt"] {
+            assert!(species.contains(name), "{} missing", name);
+        }
+        assert_eq!(Pokedex::bundled().number("Pecharunt"), Some(1025));
+    }
+
+    #[test]
+    fn later_generation_names_are_not_snapped_to_earlier_ones() {
+        let species = SpeciesList::load(None).unwrap();
+        // Each is close to a Gen 1 name ("Pichu" / "Pikachu") but listed itself
+        for name in ["Pichu", "Togepi", "Marill", "Snom"] {
+            assert_eq!(snap_to_pokedex(name, species.names(), 2).as_deref(), Some(name));
+        }
+    }
+
+    #[test]
+    fn empty_species_file_is_an_error() {
+        let path = std::env::temp_dir().join(format!("protean-empty-species-{}.txt", std::process::id()));
+        fs::write(&path, "\n  \n").unwrap();
+        let loaded = SpeciesList::load(Some(&path));
+        fs::remove_file(&path).unwrap();
+        assert!(loaded.is_err());
+    }
+
+    #[test]
+    fn over_segmented_names_are_rejoined_into_the_species() {
+        let species = SpeciesList::load(None).unwrap();
+        assert_eq!(species.rejoin_split_names("VS. Wild Pid gey"), "VS. Wild Pidgey");
+        assert_eq!(species.rejoin_split_names("VS. Wild Bul ba saur"), "VS. Wild Bulbasaur");
+        assert_eq!(species.rejoin_split_names("Bag\nVS. Wild Pid gey Lv. 5"), "Bag\nVS. Wild Pidgey Lv. 5");
+
+        let config = crate::config::Config::preset();
+        let rejoined = species.rejoin_split_names("VS. Wild Rat tata");
+        assert_eq!(crate::pokemon::extract_encounter_name(&rejoined, &config).as_deref(), Some("Rattata"));
+    }
+
+    #[test]
+    fn species_are_not_merged_with_their_neighbours() {
+        let species = SpeciesList::load(None).unwrap();
+        // "Abra" is a species, so it isn't joined even though the rest may be
+        assert_eq!(species.rejoin_split_names("VS. Wild Abra Kadabra"), "VS. Wild Abra Kadabra");
+        assert_eq!(species.rejoin_split_names("VS. Wild Qwerty uiop"), "VS. Wild Qwerty uiop");
+    }
+}
+
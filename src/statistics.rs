@@ -1,29 +1,938 @@
-use std::collections::HashMap;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
 use std::time::Duration;
 
+use crate::config::{Config, OutputStyle};
+use crate::export::csv_field;
+use crate::pokemon::{normalize_pokemon_names, normalized_name_map, BattleOutcome, ShinyStatus};
+use crate::ui::print_banner;
+
 // Time conversion constants
 const SECONDS_PER_HOUR: u64 = 3600;
 const SECONDS_PER_MINUTE: u64 = 60;
+/// Digits of `Duration::subsec_nanos`, the finest fraction of a second
+const SUBSEC_NANOS_DIGITS: u32 = 9;
 
 // Statistics display constants
 const COLUMN_WIDTH_POKEMON: usize = 50;
 const COLUMN_WIDTH_COUNT: usize = 5;
 const COLUMN_WIDTH_RATE: usize = 6;
+const COLUMN_WIDTH_OUTCOME_NAME: usize = 20;
+const COLUMN_WIDTH_RECENT_COUNT: usize = 8;
 const TABLE_WIDTH: usize = 70;
 const PERCENTAGE_MULTIPLIER: f64 = 100.0;
+/// Decimal places of the share of encounters shown in the count table
+const PERCENTAGE_DECIMALS: usize = 1;
+const SECONDS_PER_HOUR_F64: f64 = 3600.0;
+
+// Encounter pace sparkline constants
+const SPARKLINE_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const SPARKLINE_MAX_BUCKETS: u64 = 40;
+const SPARKLINE_MIN_BUCKET_SECS: u64 = 60;
+
+/// z-score for a 95% confidence level
+const CONFIDENCE_Z_95: f64 = 1.96;
+
+/// A single counted encounter
+#[derive(Debug, Clone)]
+pub struct Encounter {
+    /// Name the encounter was counted under
+    pub name: String,
+    /// Active hunting time (excluding pauses) at which the encounter was counted
+    pub active_time: Duration,
+    /// Area label active when the encounter was counted
+    pub area: Option<String>,
+    /// Whether the species is one of the hunt's targets
+    pub is_target: bool,
+    /// Whether the battle showed the shiny pattern
+    pub shiny: bool,
+}
+
+/// Encounters counted in one bucket of a timeline
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineBucket {
+    /// Active time at which the bucket starts
+    pub start_secs: u64,
+    pub total: usize,
+    /// Encounters per name (names without encounters in the bucket are omitted)
+    pub counts: BTreeMap<String, usize>,
+}
+
+/// Timestamped log of every counted encounter in the session
+/// 
+/// Alongside the raw entries it maintains an exponentially-weighted moving
+/// average of the interval between encounters, which reflects the recent
+/// pace better than the session average.
+#[derive(Debug, Clone)]
+pub struct EncounterLog {
+    encounters: Vec<Encounter>,
+    /// Weight given to the newest interval (0.0-1.0)
+    smoothing: f64,
+    /// Smoothed interval between encounters in seconds
+    ewma_interval_secs: Option<f64>,
+}
+
+impl EncounterLog {
+    pub fn new(smoothing: f64) -> Self {
+        Self {
+            encounters: Vec::new(),
+            smoothing: smoothing.clamp(0.0, 1.0),
+            ewma_interval_secs: None,
+        }
+    }
+
+    /// Record a counted encounter
+    pub fn record(&mut self, encounter: Encounter) {
+        let previous = self.encounters.last().map_or(Duration::ZERO, |e| e.active_time);
+        let interval = encounter.active_time.saturating_sub(previous).as_secs_f64();
+
+        self.ewma_interval_secs = Some(match self.ewma_interval_secs {
+            Some(ewma) => self.smoothing * interval + (1.0 - self.smoothing) * ewma,
+            None => interval,
+        });
+
+        self.encounters.push(encounter);
+    }
+
+    /// Combine several logs into one, ordered by encounter time
+    pub fn merged(logs: &[&EncounterLog], smoothing: f64) -> Self {
+        let mut encounters: Vec<Encounter> = logs
+            .iter()
+            .flat_map(|log| log.encounters.iter().cloned())
+            .collect();
+        encounters.sort_by_key(|e| e.active_time);
+
+        let mut merged = Self::new(smoothing);
+        for encounter in encounters {
+            merged.record(encounter);
+        }
+        merged
+    }
+
+    /// Every counted encounter, oldest first
+    pub fn encounters(&self) -> &[Encounter] {
+        &self.encounters
+    }
+
+    /// Remove all recorded encounters
+    pub fn clear(&mut self) {
+        self.encounters.clear();
+        self.ewma_interval_secs = None;
+    }
+
+    /// The same log with encounter names mapped through `name_map`
+    pub fn remapped(&self, name_map: &HashMap<String, String>) -> Self {
+        let mut remapped = self.clone();
+        for encounter in &mut remapped.encounters {
+            if let Some(norm_name) = name_map.get(&encounter.name) {
+                encounter.name = norm_name.clone();
+            }
+        }
+        remapped
+    }
+
+    /// Per-name counts of encounters at or after `start` (active time)
+    pub fn counts_since(&self, start: Duration) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for encounter in self.encounters.iter().filter(|e| e.active_time >= start) {
+            *counts.entry(encounter.name.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Encounters counted while no area was set
+    pub fn untagged_count(&self) -> usize {
+        self.encounters.iter().filter(|e| e.area.is_none()).count()
+    }
+
+    /// Encounters counted after the most recent target or shiny (all of them
+    /// if neither has been seen yet)
+    pub fn since_last_target(&self) -> usize {
+        self.encounters.iter().rev().take_while(|e| !e.is_target && !e.shiny).count()
+    }
+
+    /// Encounters per hour averaged over the whole session
+    pub fn session_rate_per_hour(&self, hunt_duration: Duration) -> Option<f64> {
+        let hours = hunt_duration.as_secs_f64() / SECONDS_PER_HOUR_F64;
+        (hours > 0.0 && !self.encounters.is_empty()).then(|| self.encounters.len() as f64 / hours)
+    }
+
+    /// Unicode block sparkline of encounters per time bucket over the session
+    /// 
+    /// Buckets are whole minutes, widened so the line never exceeds
+    /// `SPARKLINE_MAX_BUCKETS` characters. Sessions without encounters give
+    /// a flat line.
+    /// 
+    /// # Returns
+    /// * The sparkline and the bucket width in minutes
+    pub fn sparkline(&self, hunt_duration: Duration) -> (String, u64) {
+        let total_secs = hunt_duration.as_secs().max(1);
+        let min_bucket_secs = total_secs.div_ceil(SPARKLINE_MAX_BUCKETS);
+        let bucket_secs = min_bucket_secs.div_ceil(SPARKLINE_MIN_BUCKET_SECS).max(1) * SPARKLINE_MIN_BUCKET_SECS;
+        let bucket_count = total_secs.div_ceil(bucket_secs) as usize;
+
+        let mut buckets = vec![0usize; bucket_count];
+        for encounter in &self.encounters {
+            let index = (encounter.active_time.as_secs() / bucket_secs) as usize;
+            buckets[index.min(bucket_count - 1)] += 1;
+        }
+
+        let peak = buckets.iter().copied().max().unwrap_or(0);
+        let top_level = SPARKLINE_LEVELS.len() - 1;
+        let line = buckets
+            .iter()
+            .map(|&count| match peak {
+                0 => SPARKLINE_LEVELS[0],
+                _ => SPARKLINE_LEVELS[count * top_level / peak],
+            })
+            .collect();
+        (line, bucket_secs / SPARKLINE_MIN_BUCKET_SECS)
+    }
+
+    /// Encounters per name in consecutive buckets of active time covering the
+    /// session, including empty buckets so the timeline is continuous
+    /// 
+    /// # Arguments
+    /// * `bucket` - Length of each bucket (at least a second)
+    /// * `hunt_duration` - Active time the timeline covers
+    pub fn timeline(&self, bucket: Duration, hunt_duration: Duration) -> Vec<TimelineBucket> {
+        let bucket_secs = bucket.as_secs().max(1);
+        let bucket_count = hunt_duration.as_secs().div_ceil(bucket_secs).max(1);
+
+        let mut timeline: Vec<TimelineBucket> = (0..bucket_count)
+            .map(|index| TimelineBucket {
+                start_secs: index * bucket_secs,
+                total: 0,
+                counts: BTreeMap::new(),
+            })
+            .collect();
+        for encounter in &self.encounters {
+            let index = (encounter.active_time.as_secs() / bucket_secs).min(bucket_count - 1) as usize;
+            let bucket = &mut timeline[index];
+            bucket.total += 1;
+            *bucket.counts.entry(encounter.name.clone()).or_insert(0) += 1;
+        }
+        timeline
+    }
+
+    /// Encounters per hour derived from the smoothed encounter interval
+    pub fn ewma_rate_per_hour(&self) -> Option<f64> {
+        self.ewma_interval_secs
+            .filter(|interval| *interval > 0.0)
+            .map(|interval| SECONDS_PER_HOUR_F64 / interval)
+    }
+}
+
+/// Per-species data that can be combined when names are merged
+pub trait Tally: Default + Clone {
+    /// Add another tally into this one
+    fn merge(&mut self, other: &Self);
+}
+
+impl Tally for usize {
+    fn merge(&mut self, other: &Self) {
+        *self += other;
+    }
+}
+
+/// Re-key per-species data according to a name normalization map,
+/// merging entries that end up with the same name
+/// 
+/// # Arguments
+/// * `species_data` - HashMap of pokemon names to per-species data
+/// * `name_map` - Map from recorded names to normalized names
+/// 
+/// # Returns
+/// * A new HashMap keyed by normalized names
+pub fn remap_species<T: Tally>(
+    species_data: &HashMap<String, T>,
+    name_map: &HashMap<String, String>,
+) -> HashMap<String, T> {
+    let mut remapped: HashMap<String, T> = HashMap::new();
+    for (name, tally) in species_data {
+        let norm_name = name_map.get(name).unwrap_or(name);
+        remapped.entry(norm_name.clone()).or_default().merge(tally);
+    }
+    remapped
+}
+
+/// Merge all entries of `from` into `into`
+fn merge_species<T: Tally>(into: &mut HashMap<String, T>, from: &HashMap<String, T>) {
+    for (name, tally) in from {
+        into.entry(name.clone()).or_default().merge(tally);
+    }
+}
+
+/// All statistics collected during a hunt session
+/// 
+/// Counts are kept exactly as OCR read them. Normalization is applied as a
+/// view when displaying, unless explicitly committed with `commit_normalization`.
+#[derive(Clone)]
+pub struct HuntStats {
+    /// Raw encounter counts keyed by the name OCR read
+    pub text_counts: HashMap<String, usize>,
+    /// Per-species battle outcomes (empty when outcome tracking is disabled)
+    pub outcome_counts: HashMap<String, OutcomeTally>,
+    /// Per-species time spent in battle
+    pub battle_times: HashMap<String, BattleTimeTally>,
+    /// Per-species level distribution (empty when level tracking is disabled)
+    pub levels: HashMap<String, LevelTally>,
+    /// Per-species shiny and normal encounters (empty without a shiny pattern)
+    pub shiny_counts: HashMap<String, ShinyTally>,
+    /// Per-species encounters by area (only encounters counted with an area set)
+    pub area_counts: HashMap<String, AreaTally>,
+    /// Area labels cycled through by `cycle_area`
+    pub areas: Vec<String>,
+    /// Area new encounters are tagged with
+    pub current_area: Option<String>,
+    /// Target species (lowercase) that reset the "since last target" counter
+    pub targets: Vec<String>,
+    /// Timestamped encounters used for rate estimates
+    pub encounter_log: EncounterLog,
+    /// What OCR read that didn't become a count
+    pub diagnostics: DetectionDiagnostics,
+    /// Whether statistics are displayed with normalized names
+    pub normalized_view: bool,
+    /// Whether the average battle time table is shown
+    pub show_battle_times: bool,
+    /// Whether per-species confidence intervals are shown
+    pub show_confidence_intervals: bool,
+    /// Confusable glyph sequences used when normalizing names
+    pub confusables: BTreeMap<String, String>,
+    /// Number of species listed before the rest are grouped into "Other"
+    pub top_n: Option<usize>,
+    /// Minutes compared against the whole session (0 disables)
+    pub recent_window_mins: u64,
+    /// Expected encounter rate in percent per species (empty when not configured)
+    pub spawn_table: BTreeMap<String, f64>,
+    /// Decimal places of seconds shown in the hunt duration
+    pub duration_decimals: u32,
+    /// Border style of the statistics banner
+    pub output_style: OutputStyle,
+}
+
+impl HuntStats {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            text_counts: HashMap::new(),
+            outcome_counts: HashMap::new(),
+            battle_times: HashMap::new(),
+            levels: HashMap::new(),
+            shiny_counts: HashMap::new(),
+            area_counts: HashMap::new(),
+            areas: config.areas.clone(),
+            current_area: None,
+            targets: config.notify_targets.iter().map(|t| t.to_lowercase()).collect(),
+            encounter_log: EncounterLog::new(config.rate_smoothing),
+            diagnostics: DetectionDiagnostics::default(),
+            normalized_view: false,
+            show_battle_times: config.show_battle_times,
+            show_confidence_intervals: config.show_confidence_intervals,
+            confusables: config.confusables.clone(),
+            top_n: config.stats_top_n,
+            recent_window_mins: config.recent_window_mins,
+            spawn_table: config.spawn_table.clone(),
+            duration_decimals: config.duration_decimals,
+            output_style: config.output_style,
+        }
+    }
+
+    /// Combine the statistics of several monitored targets
+    pub fn combined(all_stats: &[&HuntStats]) -> Self {
+        let first = all_stats.first();
+        let smoothing = first.map_or(0.0, |stats| stats.encounter_log.smoothing);
+        let logs: Vec<&EncounterLog> = all_stats.iter().map(|stats| &stats.encounter_log).collect();
+        let mut combined = Self {
+            text_counts: HashMap::new(),
+            outcome_counts: HashMap::new(),
+            battle_times: HashMap::new(),
+            levels: HashMap::new(),
+            shiny_counts: HashMap::new(),
+            area_counts: HashMap::new(),
+            areas: first.map(|stats| stats.areas.clone()).unwrap_or_default(),
+            current_area: first.and_then(|stats| stats.current_area.clone()),
+            targets: first.map(|stats| stats.targets.clone()).unwrap_or_default(),
+            encounter_log: EncounterLog::merged(&logs, smoothing),
+            diagnostics: DetectionDiagnostics::default(),
+            normalized_view: first.is_some_and(|stats| stats.normalized_view),
+            show_battle_times: first.is_some_and(|stats| stats.show_battle_times),
+            show_confidence_intervals: first.is_some_and(|stats| stats.show_confidence_intervals),
+            confusables: first.map(|stats| stats.confusables.clone()).unwrap_or_default(),
+            top_n: first.and_then(|stats| stats.top_n),
+            recent_window_mins: first.map_or(0, |stats| stats.recent_window_mins),
+            spawn_table: first.map(|stats| stats.spawn_table.clone()).unwrap_or_default(),
+            duration_decimals: first.map_or(0, |stats| stats.duration_decimals),
+            output_style: first.map(|stats| stats.output_style).unwrap_or_default(),
+        };
+
+        for stats in all_stats {
+            merge_species(&mut combined.text_counts, &stats.text_counts);
+            merge_species(&mut combined.outcome_counts, &stats.outcome_counts);
+            merge_species(&mut combined.battle_times, &stats.battle_times);
+            merge_species(&mut combined.levels, &stats.levels);
+            merge_species(&mut combined.shiny_counts, &stats.shiny_counts);
+            merge_species(&mut combined.area_counts, &stats.area_counts);
+            combined.diagnostics.merge(&stats.diagnostics);
+        }
+        combined
+    }
+
+    /// Clear all session statistics
+    pub fn clear(&mut self) {
+        self.text_counts.clear();
+        self.outcome_counts.clear();
+        self.battle_times.clear();
+        self.levels.clear();
+        self.shiny_counts.clear();
+        self.area_counts.clear();
+        self.encounter_log.clear();
+        self.diagnostics = DetectionDiagnostics::default();
+    }
+
+    /// Switch to the next configured area (after the last, back to no area)
+    pub fn cycle_area(&mut self) -> Option<&str> {
+        let next_index = match &self.current_area {
+            Some(current) => self.areas.iter().position(|area| area == current).map_or(0, |index| index + 1),
+            None => 0,
+        };
+        self.current_area = self.areas.get(next_index).cloned();
+        self.current_area.as_deref()
+    }
+
+    /// Record a counted encounter and return the new count for that name
+    pub fn record(&mut self, name: &str, active_time: Duration, shiny: ShinyStatus) -> usize {
+        self.encounter_log.record(Encounter {
+            name: name.to_string(),
+            active_time,
+            area: self.current_area.clone(),
+            is_target: self.targets.contains(&name.to_lowercase()),
+            shiny: shiny == ShinyStatus::Shiny,
+        });
+        if let Some(area) = &self.current_area {
+            self.area_counts.entry(name.to_string()).or_default().record(area);
+        }
+        let count = self.text_counts.entry(name.to_string()).or_insert(0);
+        *count += 1;
+        *count
+    }
+
+    /// Encounters since the last target species or shiny, if any targets are configured
+    pub fn since_last_target(&self) -> Option<usize> {
+        (!self.targets.is_empty()).then(|| self.encounter_log.since_last_target())
+    }
+
+    /// Record the outcome of a counted battle
+    pub fn record_outcome(&mut self, name: &str, outcome: Option<BattleOutcome>) {
+        self.outcome_counts.entry(name.to_string()).or_default().record(outcome);
+    }
+
+    /// Record how long a counted battle lasted
+    pub fn record_battle_time(&mut self, name: &str, battle_time: Duration) {
+        self.battle_times.entry(name.to_string()).or_default().record(battle_time);
+    }
+
+    /// Record the level read for a counted battle
+    pub fn record_level(&mut self, name: &str, level: u32) {
+        self.levels.entry(name.to_string()).or_default().record(level);
+    }
+
+    /// Record whether a counted encounter was shiny
+    pub fn record_shiny(&mut self, name: &str, shiny: ShinyStatus) {
+        self.shiny_counts.entry(name.to_string()).or_default().record(shiny);
+    }
+
+    /// The statistics as they should be displayed (raw or normalized view)
+    pub fn view(&self) -> HuntStats {
+        if !self.normalized_view {
+            return self.clone();
+        }
+
+        let name_map = normalized_name_map(&self.text_counts, &self.confusables);
+        Self {
+            text_counts: remap_species(&self.text_counts, &name_map),
+            outcome_counts: remap_species(&self.outcome_counts, &name_map),
+            battle_times: remap_species(&self.battle_times, &name_map),
+            levels: remap_species(&self.levels, &name_map),
+            shiny_counts: remap_species(&self.shiny_counts, &name_map),
+            area_counts: remap_species(&self.area_counts, &name_map),
+            encounter_log: self.encounter_log.remapped(&name_map),
+            ..self.clone()
+        }
+    }
+
+    /// Permanently merge raw names into their normalized names
+    pub fn commit_normalization(&mut self) {
+        let name_map = normalized_name_map(&self.text_counts, &self.confusables);
+        self.text_counts = normalize_pokemon_names(&self.text_counts, &self.confusables);
+        self.outcome_counts = remap_species(&self.outcome_counts, &name_map);
+        self.battle_times = remap_species(&self.battle_times, &name_map);
+        self.levels = remap_species(&self.levels, &name_map);
+        self.shiny_counts = remap_species(&self.shiny_counts, &name_map);
+        self.area_counts = remap_species(&self.area_counts, &name_map);
+        self.encounter_log = self.encounter_log.remapped(&name_map);
+    }
+
+    /// Print the statistics table using the current view
+    pub fn print(&self, hunt_duration: Duration) {
+        let view = self.view();
+        print_banner("FINAL STATISTICS", self.output_style);
+        println!();
+        print_statistics(
+            &view.text_counts,
+            &view.encounter_log,
+            hunt_duration,
+            self.duration_decimals,
+            self.top_n,
+            self.since_last_target(),
+            &self.diagnostics,
+        );
+        if !view.text_counts.is_empty() && !view.outcome_counts.is_empty() {
+            print_outcome_breakdown(&view.outcome_counts);
+        }
+        let recent_window = Duration::from_secs(self.recent_window_mins * SECONDS_PER_MINUTE);
+        if !recent_window.is_zero() && hunt_duration > recent_window && !view.text_counts.is_empty() {
+            print_recent_split(&view.text_counts, &view.encounter_log, hunt_duration, recent_window);
+        }
+        if self.show_confidence_intervals && !view.text_counts.is_empty() {
+            print_confidence_intervals(&view.text_counts);
+        }
+        if !self.spawn_table.is_empty() && !view.text_counts.is_empty() {
+            print_spawn_comparison(&view.text_counts, &self.spawn_table);
+        }
+        if !view.area_counts.is_empty() {
+            print_area_breakdown(&view.area_counts, view.encounter_log.untagged_count());
+        }
+        if !view.levels.is_empty() {
+            print_level_distribution(&view.levels);
+        }
+        if !view.shiny_counts.is_empty() {
+            print_shiny_breakdown(&view.shiny_counts);
+        }
+        if self.show_battle_times && !view.battle_times.is_empty() {
+            print_battle_times(&view.battle_times);
+        }
+        if self.normalized_view {
+            println!("(normalized view - press [V] for raw OCR names)");
+        }
+    }
+
+    /// The counts and encounter log to save for resuming the session
+    pub fn saved(&self) -> SavedStats {
+        SavedStats {
+            text_counts: self.text_counts.clone(),
+            encounters: self.encounter_log.encounters()
+                .iter()
+                .map(|encounter| SavedEncounter {
+                    name: encounter.name.clone(),
+                    active_ms: encounter.active_time.as_millis() as u64,
+                    area: encounter.area.clone(),
+                    shiny: encounter.shiny,
+                })
+                .collect(),
+        }
+    }
+
+    /// Continue from saved counts, replaying the saved encounters so rates
+    /// and the "since last target" counter carry over
+    pub fn restore(&mut self, saved: SavedStats) {
+        self.text_counts = saved.text_counts;
+        for encounter in saved.encounters {
+            let is_target = self.targets.contains(&encounter.name.to_lowercase());
+            self.encounter_log.record(Encounter {
+                name: encounter.name,
+                active_time: Duration::from_millis(encounter.active_ms),
+                area: encounter.area,
+                is_target,
+                shiny: encounter.shiny,
+            });
+        }
+    }
+}
+
+/// An unfinished session, saved periodically so a restart can continue it
+/// 
+/// Only counts, encounter times and active time are kept; outcomes, battle
+/// times, levels and shiny counts start over when the session is resumed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SavedSession {
+    /// Active hunting time in milliseconds (excluding pauses)
+    pub active_ms: u64,
+    /// Statistics per monitored target, keyed by target name
+    pub targets: BTreeMap<String, SavedStats>,
+}
+
+/// One target's saved statistics
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SavedStats {
+    /// Encounter counts keyed by recorded name
+    pub text_counts: HashMap<String, usize>,
+    /// Counted encounters, oldest first
+    #[serde(default)]
+    pub encounters: Vec<SavedEncounter>,
+}
+
+/// A counted encounter in a saved session
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedEncounter {
+    pub name: String,
+    /// Active time at which the encounter was counted, in milliseconds
+    pub active_ms: u64,
+    #[serde(default)]
+    pub area: Option<String>,
+    #[serde(default)]
+    pub shiny: bool,
+}
+
+/// Save the running session, replacing any earlier save
+/// 
+/// # Arguments
+/// * `targets` - Each monitored target's name and statistics
+/// * `active_duration` - Active hunting time so far, including any resumed time
+pub fn save_session(path: &Path, targets: &[(&str, &HuntStats)], active_duration: Duration) -> Result<()> {
+    let session = SavedSession {
+        active_ms: active_duration.as_millis() as u64,
+        targets: targets.iter().map(|(name, stats)| (name.to_string(), stats.saved())).collect(),
+    };
+    let json = serde_json::to_string_pretty(&session)
+        .context("Failed to serialize session")?;
+
+    // Write beside the target and rename so a crash mid-save keeps the last good save
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, json)
+        .with_context(|| format!("Failed to write session {}", temp_path.display()))?;
+    fs::rename(&temp_path, path)
+        .with_context(|| format!("Failed to replace session {}", path.display()))
+}
+
+/// File format of a statistics export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsFormat {
+    /// `pokemon,count,percentage` rows
+    Csv,
+    /// The rows plus the total and hunt duration
+    Json,
+}
+
+impl StatsFormat {
+    /// Pick the format from a file extension (`.csv` or `.json`)
+    pub fn from_path(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|ext| ext.to_str()).map(str::to_lowercase).as_deref() {
+            Some("csv") => Ok(Self::Csv),
+            Some("json") => Ok(Self::Json),
+            _ => bail!("Unrecognized export extension in {} (expected .csv or .json)", path.display()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct StatsExport {
+    total: usize,
+    /// Active hunting time in seconds (excluding pauses)
+    hunt_duration_secs: u64,
+    /// Hunting time as shown under the count table
+    hunt_duration: String,
+    counts: Vec<ExportedCount>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedCount {
+    pokemon: String,
+    count: usize,
+    /// Share of all encounters, rounded as the count table shows it
+    percentage: f64,
+}
+
+/// Write the count table to a file, every species listed (no "Other" row)
+/// in the table's order with the percentages it shows
+/// 
+/// # Arguments
+/// * `text_counts` - Pokemon names to encounter counts
+/// * `hunt_duration` - Total active hunting time (excluding pauses)
+/// * `format` - CSV rows or a JSON document with the total and duration
+/// * `path` - File to write
+pub fn export_statistics(
+    text_counts: &HashMap<String, usize>,
+    hunt_duration: Duration,
+    format: StatsFormat,
+    path: &Path,
+) -> Result<()> {
+    let total: usize = text_counts.values().sum();
+    let mut sorted: Vec<_> = text_counts.iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+    let contents = match format {
+        StatsFormat::Csv => {
+            let mut csv = String::from("pokemon,count,percentage\n");
+            for (name, count) in sorted {
+                csv.push_str(&format!("{},{},{}\n", csv_field(name), count, format_percentage(*count, total)));
+            }
+            csv
+        }
+        StatsFormat::Json => {
+            let export = StatsExport {
+                total,
+                hunt_duration_secs: hunt_duration.as_secs(),
+                hunt_duration: format_duration(hunt_duration, 0),
+                counts: sorted
+                    .into_iter()
+                    .map(|(name, count)| ExportedCount {
+                        pokemon: name.clone(),
+                        count: *count,
+                        // Parsed from the table's text so the rounding can't differ
+                        percentage: format_percentage(*count, total).parse().unwrap_or_default(),
+                    })
+                    .collect(),
+            };
+            serde_json::to_string_pretty(&export).context("Failed to serialize statistics")?
+        }
+    };
+    fs::write(path, contents)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Load a saved session, if there is one
+pub fn load_session(path: &Path) -> Result<Option<SavedSession>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read session {}", path.display()))?;
+    serde_json::from_str(&contents)
+        .map(Some)
+        .with_context(|| format!("Failed to parse session {}", path.display()))
+}
+
+/// What OCR read that didn't become a count
+/// 
+/// Tells "nothing readable on screen" apart from "text was read but never
+/// matched" and "encounters were detected but never confirmed", which point
+/// at the region, the pattern and the end-of-battle thresholds respectively.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DetectionDiagnostics {
+    /// Frames with text that didn't match the pattern outside a battle
+    pub ignored_frames: usize,
+    /// Frames discarded by `suppress_if_contains`
+    pub suppressed_frames: usize,
+    /// Frames not read again because they barely changed (`ocr_change_threshold`)
+    pub unchanged_frames: usize,
+    /// Encounters detected (each newly read name)
+    pub detections: usize,
+    /// Detected encounters that were confirmed into counts
+    pub confirmed: usize,
+}
+
+impl DetectionDiagnostics {
+    /// Detections that never became counts
+    pub fn unconfirmed(&self) -> usize {
+        self.detections.saturating_sub(self.confirmed)
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.ignored_frames += other.ignored_frames;
+        self.suppressed_frames += other.suppressed_frames;
+        self.unchanged_frames += other.unchanged_frames;
+        self.detections += other.detections;
+        self.confirmed += other.confirmed;
+    }
+
+    /// Explain why nothing was counted
+    fn print_empty_report(&self) {
+        println!("{:<width_name$} | {}", "Frames Ignored (no pattern)", self.ignored_frames,
+                 width_name = COLUMN_WIDTH_POKEMON);
+        if self.suppressed_frames > 0 {
+            println!("{:<width_name$} | {}", "Frames Suppressed", self.suppressed_frames,
+                     width_name = COLUMN_WIDTH_POKEMON);
+        }
+        if self.unchanged_frames > 0 {
+            println!("{:<width_name$} | {}", "OCR Skipped (unchanged)", self.unchanged_frames,
+                     width_name = COLUMN_WIDTH_POKEMON);
+        }
+        println!("{:<width_name$} | {}", "Detections Never Confirmed", self.unconfirmed(),
+                 width_name = COLUMN_WIDTH_POKEMON);
+        if self.unconfirmed() > 0 {
+            println!("\nEncounters were detected but never counted: check empty_threshold and");
+            println!("empty_detection, which decide when a battle has ended.");
+        } else if self.ignored_frames > 0 {
+            println!("\nText was read but never matched the pattern: check the pattern and");
+            println!("that the region covers the banner (see --replay and --ocr-image).");
+        } else {
+            println!("\nNo text was read: check the capture region and OCR setup.");
+        }
+    }
+}
+
+/// Per-species tally of how battles ended
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OutcomeTally {
+    pub fled: usize,
+    pub caught: usize,
+    pub defeated: usize,
+    /// Battles where no outcome phrase was read
+    pub unknown: usize,
+}
+
+impl OutcomeTally {
+    /// Record a single battle outcome
+    pub fn record(&mut self, outcome: Option<BattleOutcome>) {
+        match outcome {
+            Some(BattleOutcome::Fled) => self.fled += 1,
+            Some(BattleOutcome::Caught) => self.caught += 1,
+            Some(BattleOutcome::Defeated) => self.defeated += 1,
+            None => self.unknown += 1,
+        }
+    }
+
+    fn total(&self) -> usize {
+        self.fled + self.caught + self.defeated + self.unknown
+    }
+}
+
+impl Tally for OutcomeTally {
+    fn merge(&mut self, other: &Self) {
+        self.fled += other.fled;
+        self.caught += other.caught;
+        self.defeated += other.defeated;
+        self.unknown += other.unknown;
+    }
+}
+
+/// Per-species total time spent in battle
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BattleTimeTally {
+    pub total: Duration,
+    pub battles: u32,
+}
+
+impl BattleTimeTally {
+    /// Record a single battle's duration
+    pub fn record(&mut self, battle_time: Duration) {
+        self.total += battle_time;
+        self.battles += 1;
+    }
+
+    /// Average battle duration
+    pub fn average(&self) -> Duration {
+        if self.battles == 0 {
+            Duration::ZERO
+        } else {
+            self.total / self.battles
+        }
+    }
+}
+
+impl Tally for BattleTimeTally {
+    fn merge(&mut self, other: &Self) {
+        self.total += other.total;
+        self.battles += other.battles;
+    }
+}
+
+/// Per-species count of encounters at each level
+#[derive(Debug, Clone, Default)]
+pub struct LevelTally {
+    pub counts: BTreeMap<u32, usize>,
+}
+
+impl LevelTally {
+    /// Record a single encounter at the given level
+    pub fn record(&mut self, level: u32) {
+        *self.counts.entry(level).or_insert(0) += 1;
+    }
+
+    /// Number of encounters with a known level
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+}
+
+impl Tally for LevelTally {
+    fn merge(&mut self, other: &Self) {
+        for (level, count) in &other.counts {
+            *self.counts.entry(*level).or_insert(0) += count;
+        }
+    }
+}
+
+/// Per-species count of shiny and normal encounters
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShinyTally {
+    pub normal: usize,
+    pub shiny: usize,
+}
+
+impl ShinyTally {
+    /// Record a single encounter
+    pub fn record(&mut self, shiny: ShinyStatus) {
+        match shiny {
+            ShinyStatus::Normal => self.normal += 1,
+            ShinyStatus::Shiny => self.shiny += 1,
+        }
+    }
+}
+
+impl Tally for ShinyTally {
+    fn merge(&mut self, other: &Self) {
+        self.normal += other.normal;
+        self.shiny += other.shiny;
+    }
+}
+
+/// Per-species count of encounters in each area
+#[derive(Debug, Clone, Default)]
+pub struct AreaTally {
+    pub counts: BTreeMap<String, usize>,
+}
+
+impl AreaTally {
+    /// Record a single encounter in the given area
+    pub fn record(&mut self, area: &str) {
+        *self.counts.entry(area.to_string()).or_insert(0) += 1;
+    }
+}
+
+impl Tally for AreaTally {
+    fn merge(&mut self, other: &Self) {
+        for (area, count) in &other.counts {
+            *self.counts.entry(area.clone()).or_insert(0) += count;
+        }
+    }
+}
+
+/// Share of `total` taken by `count`, formatted as the count table shows it
+/// (without the percent sign)
+pub fn format_percentage(count: usize, total: usize) -> String {
+    let percentage = (count as f64 / total as f64) * PERCENTAGE_MULTIPLIER;
+    format!("{:.*}", PERCENTAGE_DECIMALS, percentage)
+}
 
 /// Format duration into human-readable string (e.g., "1h 23m 45s")
 /// 
 /// # Arguments
 /// * `duration` - The duration to format
+/// * `decimals` - Decimal places of seconds to show (e.g. 1 for "1m 23.4s"),
+///   truncated rather than rounded so a duration never reads longer than it was
 /// 
 /// # Returns
 /// * A human-readable string representation of the duration
-pub fn format_duration(duration: Duration) -> String {
+pub fn format_duration(duration: Duration, decimals: u32) -> String {
     let total_secs = duration.as_secs();
     let hours = total_secs / SECONDS_PER_HOUR;
     let minutes = (total_secs % SECONDS_PER_HOUR) / SECONDS_PER_MINUTE;
-    let seconds = total_secs % SECONDS_PER_MINUTE;
+    let whole_seconds = total_secs % SECONDS_PER_MINUTE;
+    let seconds = if decimals == 0 {
+        whole_seconds.to_string()
+    } else {
+        let fraction = duration.subsec_nanos() / 10u32.pow(SUBSEC_NANOS_DIGITS - decimals);
+        format!("{}.{:0width$}", whole_seconds, fraction, width = decimals as usize)
+    };
     
     if hours > 0 {
         format!("{}h {}m {}s", hours, minutes, seconds)
@@ -41,21 +950,78 @@ pub fn format_duration(duration: Duration) -> String {
 /// 
 /// # Arguments
 /// * `text_counts` - HashMap of pokemon names to encounter counts
+/// * `encounter_log` - Timestamped encounters used for the rate estimates
 /// * `hunt_duration` - Total active hunting time (excluding pauses)
-pub fn print_statistics(text_counts: &HashMap<String, usize>, hunt_duration: Duration) {
-    println!("\n╔════════════════════════════════════════════════════════╗");
-    println!("║                    FINAL STATISTICS                    ║");
-    println!("╚════════════════════════════════════════════════════════╝\n");
+/// * `duration_decimals` - Decimal places of seconds shown in the hunt duration
+/// * `top_n` - Number of species listed before the rest are grouped into "Other"
+/// * `since_last_target` - Encounters since the last target, if targets are configured
+/// * `diagnostics` - What was read but not counted, reported when it explains missing counts
+pub fn print_statistics(
+    text_counts: &HashMap<String, usize>,
+    encounter_log: &EncounterLog,
+    hunt_duration: Duration,
+    duration_decimals: u32,
+    top_n: Option<usize>,
+    since_last_target: Option<usize>,
+    diagnostics: &DetectionDiagnostics,
+) {
     
     if text_counts.is_empty() {
         println!("No encounters recorded.");
-        println!("Hunt Duration: {}", format_duration(hunt_duration));
+        println!("Hunt Duration: {}\n", format_duration(hunt_duration, duration_decimals));
+        diagnostics.print_empty_report();
         return;
     }
 
+    print_count_table(text_counts, hunt_duration, duration_decimals, top_n);
+    if let Some(session_rate) = encounter_log.session_rate_per_hour(hunt_duration) {
+        let recent_rate = encounter_log.ewma_rate_per_hour().unwrap_or(session_rate);
+        println!("{:<width_name$} | {:.1} (recent: {:.1})",
+                 "Encounters/Hour", session_rate, recent_rate,
+                 width_name = COLUMN_WIDTH_POKEMON);
+        let (sparkline, bucket_minutes) = encounter_log.sparkline(hunt_duration);
+        println!("{:<width_name$} | {}",
+                 format!("Pace (per {}m)", bucket_minutes), sparkline,
+                 width_name = COLUMN_WIDTH_POKEMON);
+    }
+    if let Some(since) = since_last_target {
+        println!("{:<width_name$} | {}",
+                 "Since Last Target", since,
+                 width_name = COLUMN_WIDTH_POKEMON);
+    }
+    if diagnostics.unconfirmed() > 0 {
+        println!("{:<width_name$} | {}",
+                 "Unconfirmed Detections", diagnostics.unconfirmed(),
+                 width_name = COLUMN_WIDTH_POKEMON);
+    }
+    if diagnostics.unchanged_frames > 0 {
+        println!("{:<width_name$} | {}",
+                 "OCR Skipped (unchanged)", diagnostics.unchanged_frames,
+                 width_name = COLUMN_WIDTH_POKEMON);
+    }
+}
+
+/// Print the per-species count table followed by the total and hunt duration
+/// 
+/// When `top_n` is set, species beyond the first N are summed into a single
+/// "Other" row; the total (and so every percentage) still includes them.
+/// 
+/// # Arguments
+/// * `text_counts` - HashMap of pokemon names to encounter counts (non-empty)
+/// * `hunt_duration` - Total active hunting time (excluding pauses)
+/// * `duration_decimals` - Decimal places of seconds shown in the hunt duration
+/// * `top_n` - Number of species listed before the rest are grouped into "Other"
+pub fn print_count_table(
+    text_counts: &HashMap<String, usize>,
+    hunt_duration: Duration,
+    duration_decimals: u32,
+    top_n: Option<usize>,
+) {
     let total: usize = text_counts.values().sum();
     let mut sorted: Vec<_> = text_counts.iter().collect();
-    sorted.sort_by(|a, b| b.1.cmp(a.1));
+    sorted.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    let shown = top_n.map_or(sorted.len(), |n| n.min(sorted.len()));
+    let rest = sorted.split_off(shown);
 
     println!("{:<width_name$} | {:>width_count$} | {:>width_rate$}", 
              "Pokemon", "Count", "Rate",
@@ -65,9 +1031,17 @@ pub fn print_statistics(text_counts: &HashMap<String, usize>, hunt_duration: Dur
     println!("{}", "-".repeat(TABLE_WIDTH));
     
     for (text, count) in sorted {
-        let percentage = (*count as f64 / total as f64) * PERCENTAGE_MULTIPLIER;
-        println!("{:<width_name$} | {:>width_count$} | {:>width_rate$.1}%", 
-                 text, count, percentage,
+        println!("{:<width_name$} | {:>width_count$} | {:>width_rate$}%", 
+                 text, count, format_percentage(*count, total),
+                 width_name = COLUMN_WIDTH_POKEMON,
+                 width_count = COLUMN_WIDTH_COUNT,
+                 width_rate = COLUMN_WIDTH_RATE);
+    }
+
+    if !rest.is_empty() {
+        let other_count: usize = rest.iter().map(|(_, count)| **count).sum();
+        println!("{:<width_name$} | {:>width_count$} | {:>width_rate$}%",
+                 format!("Other ({} species)", rest.len()), other_count, format_percentage(other_count, total),
                  width_name = COLUMN_WIDTH_POKEMON,
                  width_count = COLUMN_WIDTH_COUNT,
                  width_rate = COLUMN_WIDTH_RATE);
@@ -79,6 +1053,392 @@ pub fn print_statistics(text_counts: &HashMap<String, usize>, hunt_duration: Dur
              width_name = COLUMN_WIDTH_POKEMON,
              width_count = COLUMN_WIDTH_COUNT);
     println!("{:<width_name$} | {}", 
-             "Hunt Duration", format_duration(hunt_duration),
+             "Hunt Duration", format_duration(hunt_duration, duration_decimals),
              width_name = COLUMN_WIDTH_POKEMON);
 }
+
+/// Print each species' count and hourly rate over the whole session beside
+/// the same figures for only the last `recent_window`
+/// 
+/// Shows whether the route's rate is holding up over a long session.
+/// 
+/// # Arguments
+/// * `text_counts` - HashMap of pokemon names to session encounter counts
+/// * `encounter_log` - Timestamped encounters, named as in `text_counts`
+/// * `hunt_duration` - Total active hunting time (longer than the window)
+/// * `recent_window` - Length of the recent period, ending now
+fn print_recent_split(
+    text_counts: &HashMap<String, usize>,
+    encounter_log: &EncounterLog,
+    hunt_duration: Duration,
+    recent_window: Duration,
+) {
+    let recent_counts = encounter_log.counts_since(hunt_duration.saturating_sub(recent_window));
+    let session_hours = hunt_duration.as_secs_f64() / SECONDS_PER_HOUR_F64;
+    let recent_hours = recent_window.as_secs_f64() / SECONDS_PER_HOUR_F64;
+    let mut sorted: Vec<_> = text_counts.iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+    let recent_label = format!("Last {}m", recent_window.as_secs() / SECONDS_PER_MINUTE);
+    println!("\nSession vs {}", recent_label);
+    println!("{:<width_name$} | {:>width_count$} | {:>width_rate$} | {:>width_count$} | {:>width_rate$}",
+             "Pokemon", "Count", "/h", recent_label, "/h",
+             width_name = COLUMN_WIDTH_OUTCOME_NAME,
+             width_count = COLUMN_WIDTH_RECENT_COUNT,
+             width_rate = COLUMN_WIDTH_RATE);
+    println!("{}", "-".repeat(TABLE_WIDTH));
+
+    let print_row = |name: &str, count: usize, recent: usize| {
+        println!("{:<width_name$} | {:>width_count$} | {:>width_rate$.1} | {:>width_count$} | {:>width_rate$.1}",
+                 name, count, count as f64 / session_hours, recent, recent as f64 / recent_hours,
+                 width_name = COLUMN_WIDTH_OUTCOME_NAME,
+                 width_count = COLUMN_WIDTH_RECENT_COUNT,
+                 width_rate = COLUMN_WIDTH_RATE);
+    };
+    for (name, count) in sorted {
+        print_row(name, *count, recent_counts.get(name).copied().unwrap_or(0));
+    }
+    println!("{}", "-".repeat(TABLE_WIDTH));
+    print_row("TOTAL", text_counts.values().sum(), recent_counts.values().sum());
+}
+
+/// Print per-species battle outcomes with the resulting catch rate
+fn print_outcome_breakdown(outcome_counts: &HashMap<String, OutcomeTally>) {
+    let mut sorted: Vec<_> = outcome_counts.iter().collect();
+    sorted.sort_by_key(|(_, tally)| Reverse(tally.total()));
+
+    println!("\nBattle Outcomes");
+    println!("{:<width_name$} | {:>width_count$} | {:>width_count$} | {:>width_count$} | {:>width_count$} | {:>width_rate$}",
+             "Pokemon", "Fled", "Caught", "Fnt", "?", "Catch",
+             width_name = COLUMN_WIDTH_OUTCOME_NAME,
+             width_count = COLUMN_WIDTH_RATE,
+             width_rate = COLUMN_WIDTH_RATE);
+    println!("{}", "-".repeat(TABLE_WIDTH));
+
+    for (text, tally) in sorted {
+        let catch_rate = (tally.caught as f64 / tally.total() as f64) * PERCENTAGE_MULTIPLIER;
+        println!("{:<width_name$} | {:>width_count$} | {:>width_count$} | {:>width_count$} | {:>width_count$} | {:>width_rate$.1}%",
+                 text, tally.fled, tally.caught, tally.defeated, tally.unknown, catch_rate,
+                 width_name = COLUMN_WIDTH_OUTCOME_NAME,
+                 width_count = COLUMN_WIDTH_RATE,
+                 width_rate = COLUMN_WIDTH_RATE);
+    }
+}
+
+/// Wilson score interval for a proportion of `successes` out of `total`
+/// 
+/// Unlike the normal approximation it stays within 0..=1 and behaves well
+/// for small samples and proportions near 0% or 100%.
+/// 
+/// # Arguments
+/// * `successes` - Number of encounters of the species
+/// * `total` - Total number of encounters
+/// * `z` - z-score of the confidence level (1.96 for 95%)
+/// 
+/// # Returns
+/// * `(lower, upper)` bounds as fractions, or `(0.0, 1.0)` when `total` is 0
+pub fn wilson_interval(successes: usize, total: usize, z: f64) -> (f64, f64) {
+    if total == 0 {
+        return (0.0, 1.0);
+    }
+
+    let n = total as f64;
+    let p = successes as f64 / n;
+    let z2 = z * z;
+    let denominator = 1.0 + z2 / n;
+    let center = (p + z2 / (2.0 * n)) / denominator;
+    let margin = z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / denominator;
+    ((center - margin).max(0.0), (center + margin).min(1.0))
+}
+
+/// Print the 95% Wilson confidence interval of each species' share
+fn print_confidence_intervals(text_counts: &HashMap<String, usize>) {
+    let total: usize = text_counts.values().sum();
+    let mut sorted: Vec<_> = text_counts.iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(a.1));
+
+    println!("\nRate Confidence (95%)");
+    println!("{:<width_name$} | {:>width_rate$} | {:>width_rate$}",
+             "Pokemon", "Low", "High",
+             width_name = COLUMN_WIDTH_POKEMON,
+             width_rate = COLUMN_WIDTH_RATE);
+    println!("{}", "-".repeat(TABLE_WIDTH));
+
+    for (text, count) in sorted {
+        let (low, high) = wilson_interval(*count, total, CONFIDENCE_Z_95);
+        println!("{:<width_name$} | {:>width_rate$.1}% | {:>width_rate$.1}%",
+                 text, low * PERCENTAGE_MULTIPLIER, high * PERCENTAGE_MULTIPLIER,
+                 width_name = COLUMN_WIDTH_POKEMON,
+                 width_rate = COLUMN_WIDTH_RATE);
+    }
+}
+
+/// Print observed against expected rates for every species in the spawn
+/// table or observed
+/// 
+/// A rate is flagged when the expected rate lies outside the observed
+/// rate's 95% confidence interval; observed species missing from the
+/// table are flagged as unexpected.
+fn print_spawn_comparison(text_counts: &HashMap<String, usize>, spawn_table: &BTreeMap<String, f64>) {
+    let total: usize = text_counts.values().sum();
+    let observed_count = |species: &str| -> usize {
+        text_counts
+            .iter()
+            .filter(|(name, _)| name.eq_ignore_ascii_case(species))
+            .map(|(_, count)| count)
+            .sum()
+    };
+
+    let mut rows: Vec<(&str, usize, Option<f64>)> = spawn_table
+        .iter()
+        .map(|(species, expected)| (species.as_str(), observed_count(species), Some(*expected)))
+        .collect();
+    for (name, count) in text_counts {
+        if !spawn_table.keys().any(|species| species.eq_ignore_ascii_case(name)) {
+            rows.push((name.as_str(), *count, None));
+        }
+    }
+    rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+    println!("\nSpawn Table (observed vs expected)");
+    println!("{:<width_name$} | {:>width_rate$} | {:>width_rate$} |",
+             "Pokemon", "Seen", "Exp.",
+             width_name = COLUMN_WIDTH_POKEMON,
+             width_rate = COLUMN_WIDTH_RATE + 1);
+    println!("{}", "-".repeat(TABLE_WIDTH));
+
+    for (name, count, expected) in rows {
+        let observed = count as f64 / total as f64 * PERCENTAGE_MULTIPLIER;
+        let (expected, deviation) = match expected {
+            Some(expected) => {
+                let (low, high) = wilson_interval(count, total, CONFIDENCE_Z_95);
+                let deviation = if expected < low * PERCENTAGE_MULTIPLIER {
+                    "▲ more than expected"
+                } else if expected > high * PERCENTAGE_MULTIPLIER {
+                    "▼ less than expected"
+                } else {
+                    "✓"
+                };
+                (format!("{:.1}%", expected), deviation)
+            }
+            None => ("-".to_string(), "⚠ unexpected"),
+        };
+        println!("{:<width_name$} | {:>width_rate$.1}% | {:>width_exp$} | {}",
+                 name, observed, expected, deviation,
+                 width_name = COLUMN_WIDTH_POKEMON,
+                 width_rate = COLUMN_WIDTH_RATE,
+                 width_exp = COLUMN_WIDTH_RATE + 1);
+    }
+}
+
+/// Print encounters per area with the species seen there
+/// 
+/// # Arguments
+/// * `area_counts` - Per-species encounters by area
+/// * `untagged` - Encounters counted while no area was set
+fn print_area_breakdown(area_counts: &HashMap<String, AreaTally>, untagged: usize) {
+    let mut by_area: BTreeMap<&str, Vec<(&str, usize)>> = BTreeMap::new();
+    for (name, tally) in area_counts {
+        for (area, count) in &tally.counts {
+            by_area.entry(area.as_str()).or_default().push((name.as_str(), *count));
+        }
+    }
+    let mut sorted: Vec<_> = by_area
+        .into_iter()
+        .map(|(area, mut species)| {
+            species.sort_by_key(|&(name, count)| (Reverse(count), name));
+            (area, species.iter().map(|(_, count)| count).sum::<usize>(), species)
+        })
+        .collect();
+    sorted.sort_by_key(|&(_, area_total, _)| Reverse(area_total));
+
+    println!("\nArea Breakdown");
+    println!("{:<width_name$} | {:>width_count$} | Species",
+             "Area", "Count",
+             width_name = COLUMN_WIDTH_OUTCOME_NAME,
+             width_count = COLUMN_WIDTH_COUNT);
+    println!("{}", "-".repeat(TABLE_WIDTH));
+
+    for (area, area_total, species) in sorted {
+        let species: Vec<String> = species
+            .iter()
+            .map(|(name, count)| format!("{} ({})", name, count))
+            .collect();
+        println!("{:<width_name$} | {:>width_count$} | {}",
+                 area, area_total, species.join(", "),
+                 width_name = COLUMN_WIDTH_OUTCOME_NAME,
+                 width_count = COLUMN_WIDTH_COUNT);
+    }
+    if untagged > 0 {
+        println!("{:<width_name$} | {:>width_count$} |",
+                 "(no area)", untagged,
+                 width_name = COLUMN_WIDTH_OUTCOME_NAME,
+                 width_count = COLUMN_WIDTH_COUNT);
+    }
+}
+
+/// Print how often each level was encountered per species
+fn print_level_distribution(levels: &HashMap<String, LevelTally>) {
+    let mut sorted: Vec<_> = levels.iter().collect();
+    sorted.sort_by_key(|(_, tally)| Reverse(tally.total()));
+
+    println!("\nLevel Distribution");
+    println!("{:<width_name$} | Levels (count)",
+             "Pokemon",
+             width_name = COLUMN_WIDTH_OUTCOME_NAME);
+    println!("{}", "-".repeat(TABLE_WIDTH));
+
+    for (text, tally) in sorted {
+        let histogram: Vec<String> = tally.counts
+            .iter()
+            .map(|(level, count)| format!("Lv.{} ({})", level, count))
+            .collect();
+        println!("{:<width_name$} | {}",
+                 text, histogram.join("  "),
+                 width_name = COLUMN_WIDTH_OUTCOME_NAME);
+    }
+}
+
+/// Print shiny and normal encounters per species, species with shinies first
+fn print_shiny_breakdown(shiny_counts: &HashMap<String, ShinyTally>) {
+    let mut sorted: Vec<_> = shiny_counts.iter().collect();
+    sorted.sort_by_key(|(name, tally)| (Reverse(tally.shiny), Reverse(tally.normal), *name));
+
+    println!("\nShiny Encounters");
+    println!("{:<width_name$} | {:>width_count$} | {:>width_count$}",
+             "Pokemon", "Normal", "Shiny",
+             width_name = COLUMN_WIDTH_OUTCOME_NAME,
+             width_count = COLUMN_WIDTH_RATE);
+    println!("{}", "-".repeat(TABLE_WIDTH));
+
+    for (text, tally) in sorted {
+        println!("{:<width_name$} | {:>width_count$} | {:>width_count$}",
+                 text, tally.normal, tally.shiny,
+                 width_name = COLUMN_WIDTH_OUTCOME_NAME,
+                 width_count = COLUMN_WIDTH_RATE);
+    }
+}
+
+/// Print the average time spent in battle per species
+fn print_battle_times(battle_times: &HashMap<String, BattleTimeTally>) {
+    let mut sorted: Vec<_> = battle_times.iter().collect();
+    sorted.sort_by_key(|(_, tally)| Reverse(tally.average()));
+
+    println!("\nAverage Battle Time");
+    println!("{:<width_name$} | {:>width_count$} | {:>width_rate$}",
+             "Pokemon", "Count", "Avg",
+             width_name = COLUMN_WIDTH_POKEMON,
+             width_count = COLUMN_WIDTH_COUNT,
+             width_rate = COLUMN_WIDTH_RATE);
+    println!("{}", "-".repeat(TABLE_WIDTH));
+
+    for (text, tally) in sorted {
+        println!("{:<width_name$} | {:>width_count$} | {:>width_rate$.1}s",
+                 text, tally.battles, tally.average().as_secs_f64(),
+                 width_name = COLUMN_WIDTH_POKEMON,
+                 width_count = COLUMN_WIDTH_COUNT,
+                 width_rate = COLUMN_WIDTH_RATE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encounter_at(secs: u64) -> Encounter {
+        Encounter {
+            name: "Pidgey".to_string(),
+            active_time: Duration::from_secs(secs),
+            area: None,
+            is_target: false,
+            shiny: false,
+        }
+    }
+
+    #[test]
+    fn ewma_rate_follows_recent_intervals() {
+        let mut log = EncounterLog::new(0.5);
+        assert_eq!(log.ewma_rate_per_hour(), None);
+
+        // Intervals of 60s, 60s, then 30s: 60 -> 60 -> 0.5 * 30 + 0.5 * 60 = 45s
+        for secs in [60, 120, 150] {
+            log.record(encounter_at(secs));
+        }
+        let rate = log.ewma_rate_per_hour().unwrap();
+        assert!((rate - 80.0).abs() < 1e-9, "rate {}", rate);
+
+        // The session average is unaffected by the smoothing: 3 in 150s
+        let session = log.session_rate_per_hour(Duration::from_secs(150)).unwrap();
+        assert!((session - 72.0).abs() < 1e-9, "session rate {}", session);
+    }
+
+    #[test]
+    fn ewma_rate_with_full_smoothing_is_the_last_interval() {
+        let mut log = EncounterLog::new(1.0);
+        for secs in [10, 100, 112] {
+            log.record(encounter_at(secs));
+        }
+        let rate = log.ewma_rate_per_hour().unwrap();
+        assert!((rate - 300.0).abs() < 1e-9, "rate {}", rate);
+    }
+
+    #[test]
+    fn ewma_rate_resets_on_clear() {
+        let mut log = EncounterLog::new(0.3);
+        log.record(encounter_at(30));
+        log.clear();
+        assert_eq!(log.ewma_rate_per_hour(), None);
+    }
+
+    #[test]
+    fn shiny_resets_the_streak_since_the_last_target() {
+        let mut log = EncounterLog::new(0.3);
+        log.record(encounter_at(10));
+        log.record(Encounter { shiny: true, ..encounter_at(20) });
+        log.record(encounter_at(30));
+        assert_eq!(log.since_last_target(), 1);
+
+        log.record(Encounter { is_target: true, ..encounter_at(40) });
+        assert_eq!(log.since_last_target(), 0);
+        log.record(encounter_at(50));
+        log.record(encounter_at(60));
+        assert_eq!(log.since_last_target(), 2);
+    }
+
+    fn assert_interval(actual: (f64, f64), expected: (f64, f64)) {
+        assert!((actual.0 - expected.0).abs() < 1e-4 && (actual.1 - expected.1).abs() < 1e-4,
+                "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn wilson_interval_matches_known_values() {
+        assert_interval(wilson_interval(10, 100, CONFIDENCE_Z_95), (0.0552, 0.1744));
+        assert_interval(wilson_interval(1, 2, CONFIDENCE_Z_95), (0.0945, 0.9055));
+    }
+
+    #[test]
+    fn wilson_interval_stays_within_bounds_at_the_extremes() {
+        assert_interval(wilson_interval(0, 10, CONFIDENCE_Z_95), (0.0, 0.2775));
+        assert_interval(wilson_interval(5, 5, CONFIDENCE_Z_95), (0.5655, 1.0));
+        assert_eq!(wilson_interval(0, 0, CONFIDENCE_Z_95), (0.0, 1.0));
+    }
+
+    #[test]
+    fn format_duration_in_whole_seconds() {
+        assert_eq!(format_duration(Duration::ZERO, 0), "0s");
+        assert_eq!(format_duration(Duration::from_millis(999), 0), "0s");
+        assert_eq!(format_duration(Duration::from_millis(83_456), 0), "1m 23s");
+        assert_eq!(format_duration(Duration::from_secs(3 * 3600 + 5), 0), "3h 0m 5s");
+    }
+
+    #[test]
+    fn format_duration_truncates_fractions_of_a_second() {
+        assert_eq!(format_duration(Duration::ZERO, 1), "0.0s");
+        assert_eq!(format_duration(Duration::ZERO, 3), "0.000s");
+        assert_eq!(format_duration(Duration::from_millis(999), 1), "0.9s");
+        assert_eq!(format_duration(Duration::from_millis(45), 3), "0.045s");
+        assert_eq!(format_duration(Duration::from_millis(83_456), 1), "1m 23.4s");
+        assert_eq!(format_duration(Duration::from_millis(27 * 3_600_000 + 61_007), 3), "27h 1m 1.007s");
+    }
+}
+
@@ -0,0 +1,277 @@
+use anyhow::{Context, Result};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Layout};
+use image::RgbImage;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table};
+use ratatui::Terminal;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::os::fd::{AsFd, AsRawFd, OwnedFd};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config::{Action, Region};
+use crate::daemon::redirect_output;
+use crate::statistics::{format_duration, format_percentage, HuntStats};
+use crate::ui::supports_true_color;
+
+/// Log lines kept for the log panel
+const LOG_TAIL_LINES: usize = 200;
+/// Height of the status header, including its border
+const HEADER_HEIGHT: u16 = 3;
+/// Width of the count and percentage columns of the encounter table
+const COUNT_COLUMN_WIDTH: u16 = 8;
+/// Widest region thumbnail shown in the preview panel, in terminal columns
+pub const PREVIEW_COLUMNS: u32 = 48;
+
+/// Everything the dashboard shows, gathered by the monitor loop each pass
+pub struct DashboardView<'a> {
+    /// Combined statistics, in the current raw or normalized view
+    pub stats: &'a HuntStats,
+    pub active_duration: Duration,
+    pub paused: bool,
+    /// Target name and a description of its battle phase
+    pub phases: Vec<(&'a str, String)>,
+    pub bindings: &'a [(Action, char)],
+    /// Captured regions to preview; empty while the preview is off
+    pub previews: Vec<RegionPreview<'a>>,
+}
+
+/// One target's region for the preview panel
+pub struct RegionPreview<'a> {
+    pub name: &'a str,
+    pub region: &'a Region,
+    /// Half-block thumbnail of the latest capture (`ui::region_thumbnail`), once there is one
+    pub thumbnail: Option<&'a RgbImage>,
+}
+
+/// Full-screen dashboard drawn in place of the scrolling output
+///
+/// While it runs, stdout and stderr go to a log file whose newest lines are
+/// shown in a panel, so the messages the monitor loop prints don't tear the
+/// screen. Prompts need the plain terminal back, so they're wrapped in
+/// `suspend` and `resume`.
+pub struct Dashboard {
+    terminal: Terminal<CrosstermBackend<File>>,
+    /// The original stdout and stderr, put back while suspended
+    saved_stdout: OwnedFd,
+    saved_stderr: OwnedFd,
+    log_path: PathBuf,
+    log: BufReader<File>,
+    log_tail: VecDeque<String>,
+    active: bool,
+}
+
+impl Dashboard {
+    /// Take over the terminal and send output to `log_path` (truncated first)
+    pub fn start(log_path: &Path) -> Result<Self> {
+        let saved_stdout = io::stdout().as_fd().try_clone_to_owned()
+            .context("Failed to duplicate stdout")?;
+        let saved_stderr = io::stderr().as_fd().try_clone_to_owned()
+            .context("Failed to duplicate stderr")?;
+        let tty = File::from(saved_stdout.try_clone().context("Failed to duplicate stdout")?);
+
+        redirect_output(log_path)?;
+        File::create(log_path)
+            .with_context(|| format!("Failed to truncate {}", log_path.display()))?;
+        let log = File::open(log_path)
+            .with_context(|| format!("Failed to open {}", log_path.display()))?;
+
+        let mut dashboard = Self {
+            terminal: Terminal::new(CrosstermBackend::new(tty))?,
+            saved_stdout,
+            saved_stderr,
+            log_path: log_path.to_path_buf(),
+            log: BufReader::new(log),
+            log_tail: VecDeque::new(),
+            active: false,
+        };
+        dashboard.enter()?;
+        Ok(dashboard)
+    }
+
+    /// Give the terminal back for a prompt; output goes to it again until `resume`
+    pub fn suspend(&mut self) -> Result<()> {
+        if !self.active {
+            return Ok(());
+        }
+        self.active = false;
+        disable_raw_mode()?;
+        execute!(self.terminal.backend_mut(), LeaveAlternateScreen)?;
+        for (saved, fd) in [(&self.saved_stdout, libc::STDOUT_FILENO), (&self.saved_stderr, libc::STDERR_FILENO)] {
+            // SAFETY: both descriptors are valid for the duration of the call
+            if unsafe { libc::dup2(saved.as_raw_fd(), fd) } < 0 {
+                return Err(io::Error::last_os_error()).context("Failed to restore terminal output");
+            }
+        }
+        Ok(())
+    }
+
+    /// Take the terminal over again after `suspend`
+    pub fn resume(&mut self) -> Result<()> {
+        if self.active {
+            return Ok(());
+        }
+        redirect_output(&self.log_path)?;
+        self.enter()
+    }
+
+    fn enter(&mut self) -> Result<()> {
+        enable_raw_mode()?;
+        execute!(self.terminal.backend_mut(), EnterAlternateScreen)?;
+        self.terminal.clear()?;
+        self.active = true;
+        Ok(())
+    }
+
+    /// Redraw the dashboard with the latest statistics and log lines
+    pub fn draw(&mut self, view: &DashboardView) -> Result<()> {
+        if !self.active {
+            return Ok(());
+        }
+        self.read_log();
+
+        let total: usize = view.stats.text_counts.values().sum();
+        let rate = view.stats.encounter_log.session_rate_per_hour(view.active_duration)
+            .map_or_else(|| "-".to_string(), |rate| format!("{:.1}", rate));
+        let status = if view.paused { "PAUSED" } else { "Monitoring" };
+        let header = format!("{}  |  Active: {}  |  Encounters: {}  |  Per hour: {}",
+                             status, format_duration(view.active_duration, view.stats.duration_decimals),
+                             total, rate);
+
+        let mut counts: Vec<(&String, &usize)> = view.stats.text_counts.iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        let rows = counts.into_iter().map(|(name, count)| {
+            Row::new([name.clone(), count.to_string(), format!("{}%", format_percentage(*count, total))])
+        });
+
+        let phases: Vec<Line> = view.phases
+            .iter()
+            .map(|(target, phase)| Line::from(format!("{}: {}", target, phase)))
+            .collect();
+
+        let footer = view.bindings
+            .iter()
+            .map(|(action, key)| format!("[{}] {:?}", key.to_ascii_uppercase(), action))
+            .collect::<Vec<_>>()
+            .join("  ");
+
+        let preview = preview_lines(&view.previews);
+
+        let log_tail = &self.log_tail;
+        self.terminal.draw(|frame| {
+            let [header_area, body_area, footer_area] = Layout::vertical([
+                Constraint::Length(HEADER_HEIGHT),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ]).areas(frame.area());
+            let [table_area, side_area] = Layout::horizontal([
+                Constraint::Percentage(50),
+                Constraint::Percentage(50),
+            ]).areas(body_area);
+            let preview_height = if preview.is_empty() { 0 } else { preview.len() as u16 + 2 };
+            let [phase_area, preview_area, log_area] = Layout::vertical([
+                Constraint::Length(phases.len() as u16 + 2),
+                Constraint::Length(preview_height),
+                Constraint::Min(0),
+            ]).areas(side_area);
+
+            frame.render_widget(
+                Paragraph::new(header).block(Block::default().borders(Borders::ALL).title(" protean ")),
+                header_area,
+            );
+            frame.render_widget(
+                Table::new(rows, [
+                    Constraint::Min(0),
+                    Constraint::Length(COUNT_COLUMN_WIDTH),
+                    Constraint::Length(COUNT_COLUMN_WIDTH),
+                ])
+                .header(Row::new(["Pokemon", "Count", "Share"]).style(Style::default().add_modifier(Modifier::BOLD)))
+                .block(Block::default().borders(Borders::ALL).title(" Encounters ")),
+                table_area,
+            );
+            frame.render_widget(
+                Paragraph::new(phases).block(Block::default().borders(Borders::ALL).title(" Battle ")),
+                phase_area,
+            );
+            if !preview.is_empty() {
+                frame.render_widget(
+                    Paragraph::new(preview).block(Block::default().borders(Borders::ALL).title(" Preview ")),
+                    preview_area,
+                );
+            }
+            // Only the newest lines that fit inside the border
+            let visible = log_area.height.saturating_sub(2) as usize;
+            let lines: Vec<Line> = log_tail
+                .iter()
+                .skip(log_tail.len().saturating_sub(visible))
+                .map(|line| Line::from(line.as_str()))
+                .collect();
+            frame.render_widget(
+                Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" Log ")),
+                log_area,
+            );
+            frame.render_widget(Paragraph::new(footer), footer_area);
+        })?;
+        Ok(())
+    }
+
+    /// Append lines written to the log since the last read to the tail
+    fn read_log(&mut self) {
+        let mut line = String::new();
+        while self.log.read_line(&mut line).is_ok_and(|read| read > 0) {
+            // A partial line is finished by the next write; read it again then
+            if !line.ends_with('\n') {
+                let _ = self.log.seek(SeekFrom::Current(-(line.len() as i64)));
+                break;
+            }
+            let trimmed = line.trim();
+            if !trimmed.is_empty() {
+                self.log_tail.push_back(trimmed.to_string());
+                if self.log_tail.len() > LOG_TAIL_LINES {
+                    self.log_tail.pop_front();
+                }
+            }
+            line.clear();
+        }
+    }
+}
+
+/// Preview panel contents: each region's coordinates, then its thumbnail when
+/// the terminal can show it
+fn preview_lines(previews: &[RegionPreview]) -> Vec<Line<'static>> {
+    let true_color = supports_true_color();
+    let mut lines = Vec::new();
+    for preview in previews {
+        let region = preview.region;
+        lines.push(Line::from(format!("{}: {}x{} at ({}, {})",
+                                      preview.name, region.width, region.height, region.x, region.y)));
+        let Some(thumbnail) = preview.thumbnail.filter(|_| true_color) else {
+            continue;
+        };
+        for row in 0..thumbnail.height() / 2 {
+            let cells: Vec<Span> = (0..thumbnail.width())
+                .map(|column| {
+                    let [tr, tg, tb] = thumbnail.get_pixel(column, row * 2).0;
+                    let [br, bg, bb] = thumbnail.get_pixel(column, row * 2 + 1).0;
+                    Span::styled("▀", Style::default().fg(Color::Rgb(tr, tg, tb)).bg(Color::Rgb(br, bg, bb)))
+                })
+                .collect();
+            lines.push(Line::from(cells));
+        }
+    }
+    lines
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        if let Err(e) = self.suspend() {
+            eprintln!("Failed to restore the terminal: {:#}", e);
+        }
+    }
+}
@@ -1,12 +1,106 @@
+use crossterm::style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor};
+use image::imageops::FilterType;
+use image::{DynamicImage, RgbImage};
+use anyhow::Result;
+use std::io::{self, IsTerminal, Write};
+
+use crate::config::{Action, OutputStyle, Region};
+
+/// Columns between the side borders of a banner
+const BANNER_INNER_WIDTH: usize = 56;
+/// Widest region preview, in terminal columns
+const PREVIEW_MAX_COLUMNS: u32 = 80;
+/// Terminal cells are about twice as tall as wide, so each cell shows two pixel rows
+const PREVIEW_PIXELS_PER_CELL: u32 = 2;
+
 /// Display help menu with keyboard controls
-pub fn show_help() {
-    println!("\n╔════════════════════════════════════════════════════════╗");
-    println!("║                   KEYBOARD CONTROLS                    ║");
-    println!("╚════════════════════════════════════════════════════════╝");
-    println!("  [P] - Pause/Resume monitoring");
-    println!("  [R] - Restart (clear all statistics)");
-    println!("  [S] - Show current statistics");
-    println!("  [N] - Normalize Pokemon names (merge superstrings)");
-    println!("  [?] - Show this help menu");
-    println!("  [Q] - Quit and show final statistics\n");
+/// 
+/// # Arguments
+/// * `bindings` - Effective key for each action
+/// * `style` - Border style of the heading
+pub fn show_help(bindings: &[(Action, char)], style: OutputStyle) {
+    print_banner("KEYBOARD CONTROLS", style);
+    for (action, key) in bindings {
+        println!("  [{}] - {}", key.to_ascii_uppercase(), action.description());
+    }
+    println!();
+}
+
+/// Ask a yes/no question on the terminal
+pub fn ask_yes_no(question: &str) -> Result<bool> {
+    print!("{} (y/n): ", question);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("y"))
+}
+
+/// Print a heading on its own line after a blank one, boxed unless the
+/// style is plain
+pub fn print_banner(title: &str, style: OutputStyle) {
+    let (top, side, bottom) = match style {
+        OutputStyle::Unicode => (
+            format!("╔{}╗", "═".repeat(BANNER_INNER_WIDTH)),
+            '║',
+            format!("╚{}╝", "═".repeat(BANNER_INNER_WIDTH)),
+        ),
+        OutputStyle::Ascii => {
+            let border = format!("+{}+", "-".repeat(BANNER_INNER_WIDTH));
+            (border.clone(), '|', border)
+        }
+        OutputStyle::Plain => {
+            println!("\n{}", title);
+            return;
+        }
+    };
+    println!("\n{}", top);
+    println!("{}{:^width$}{}", side, title, side, width = BANNER_INNER_WIDTH);
+    println!("{}", bottom);
+}
+
+/// Print a downscaled view of a captured region below its coordinates
+/// 
+/// Each terminal cell draws two pixels with a colored upper half block.
+/// Without a true color terminal only the coordinates are printed.
+/// 
+/// # Arguments
+/// * `image` - The captured region
+/// * `region` - Where the region was captured from
+/// * `label` - Name of the monitored client
+pub fn print_region_preview(image: &DynamicImage, region: &Region, label: &str) {
+    println!("\n=> {}: {}x{} at ({}, {})", label, region.width, region.height, region.x, region.y);
+    if !std::io::stdout().is_terminal() || !supports_true_color() {
+        println!("   (preview needs a true color terminal)");
+        return;
+    }
+
+    let thumbnail = region_thumbnail(image, PREVIEW_MAX_COLUMNS);
+    for row in 0..thumbnail.height() / PREVIEW_PIXELS_PER_CELL {
+        let mut line = String::new();
+        for column in 0..thumbnail.width() {
+            let [tr, tg, tb] = thumbnail.get_pixel(column, row * 2).0;
+            let [br, bg, bb] = thumbnail.get_pixel(column, row * 2 + 1).0;
+            line.push_str(&format!(
+                "{}{}▀",
+                SetForegroundColor(Color::Rgb { r: tr, g: tg, b: tb }),
+                SetBackgroundColor(Color::Rgb { r: br, g: bg, b: bb }),
+            ));
+        }
+        println!("{}{}", line, ResetColor);
+    }
+}
+
+/// Downscale a captured region to at most `max_columns` half-block cells wide
+///
+/// The result is two pixel rows per cell: each cell draws its top pixel as
+/// the foreground of '▀' and its bottom pixel as the background.
+pub fn region_thumbnail(image: &DynamicImage, max_columns: u32) -> RgbImage {
+    let columns = image.width().clamp(1, max_columns);
+    let rows = (image.height() * columns / image.width().max(1)).div_ceil(PREVIEW_PIXELS_PER_CELL).max(1);
+    image.resize_exact(columns, rows * PREVIEW_PIXELS_PER_CELL, FilterType::Triangle).to_rgb8()
+}
+
+/// Whether the terminal advertises 24-bit color
+pub fn supports_true_color() -> bool {
+    std::env::var("COLORTERM").is_ok_and(|value| value == "truecolor" || value == "24bit")
 }
@@ -1,32 +1,128 @@
-use anyhow::{Context, Result};
 use serde::Deserialize;
 use std::process::Command;
 
-use crate::config::TARGET_WINDOW_CLASS;
+use crate::config::WindowBackend;
+use crate::error::ProteanError;
+
+/// Set by Hyprland in the environment of programs it starts
+const HYPRLAND_ENV_VAR: &str = "HYPRLAND_INSTANCE_SIGNATURE";
+const SESSION_TYPE_ENV_VAR: &str = "XDG_SESSION_TYPE";
+const X11_SESSION_TYPE: &str = "x11";
 
 #[derive(Deserialize)]
 struct HyprlandWindow {
     class: String,
+    size: (i32, i32),
+}
+
+/// The currently focused window
+pub struct ActiveWindow {
+    pub class: String,
+    /// Width and height in screen coordinates
+    pub size: (i32, i32),
+}
+
+/// Queries the active window from the desktop session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowDetector {
+    /// `hyprctl activewindow`
+    Hyprland,
+    /// `xdotool getactivewindow` (the `_NET_ACTIVE_WINDOW` of the window manager)
+    X11,
+}
+
+impl WindowDetector {
+    /// The detector for `backend`, telling the session apart from the
+    /// environment when it's `Auto`
+    /// 
+    /// Returns `None` when the session isn't one window detection supports.
+    pub fn new(backend: WindowBackend) -> Option<Self> {
+        match backend {
+            WindowBackend::Hyprland => Some(Self::Hyprland),
+            WindowBackend::X11 => Some(Self::X11),
+            WindowBackend::Auto if std::env::var_os(HYPRLAND_ENV_VAR).is_some() => Some(Self::Hyprland),
+            WindowBackend::Auto => std::env::var(SESSION_TYPE_ENV_VAR)
+                .is_ok_and(|session| session.eq_ignore_ascii_case(X11_SESSION_TYPE))
+                .then_some(Self::X11),
+        }
+    }
+
+    /// Get the class and geometry of the active window if it's one of the targets
+    /// Returns Ok(None) if no window is active or it has another class
+    /// Returns Err if the session can't be queried
+    pub fn check_active_window(self, target_window_classes: &[&str]) -> Result<Option<ActiveWindow>, ProteanError> {
+        Ok(self.active_window()?.filter(|window| target_window_classes.contains(&window.class.as_str())))
+    }
+
+    /// Get the class and geometry of the currently active window
+    /// Returns Ok(None) if no window is active
+    /// Returns Err if the session can't be queried
+    fn active_window(self) -> Result<Option<ActiveWindow>, ProteanError> {
+        match self {
+            Self::Hyprland => hyprland_active_window(),
+            Self::X11 => x11_active_window(),
+        }
+    }
 }
 
-/// Check if the target window is currently active (Hyprland specific)
-/// Returns Ok(true) if target window is active, Ok(false) otherwise
-/// Returns Err if unable to query Hyprland
-pub fn check_active_window() -> Result<bool> {
+fn hyprland_active_window() -> Result<Option<ActiveWindow>, ProteanError> {
     let output = Command::new("hyprctl")
         .args(["activewindow", "-j"])
         .output()
-        .context("Failed to execute hyprctl - is Hyprland running?")?;
+        .map_err(|e| window_error("Failed to execute hyprctl - is Hyprland running?", e))?;
 
     if !output.status.success() {
-        return Ok(false);
+        return Ok(None);
     }
 
     let json_str = String::from_utf8(output.stdout)
-        .context("hyprctl returned invalid UTF-8")?;
+        .map_err(|e| window_error("hyprctl returned invalid UTF-8", e))?;
     
     let window: HyprlandWindow = serde_json::from_str(&json_str)
-        .context("Failed to parse hyprctl JSON output")?;
+        .map_err(|e| window_error("Failed to parse hyprctl JSON output", e))?;
     
-    Ok(window.class == TARGET_WINDOW_CLASS)
+    Ok(Some(ActiveWindow {
+        class: window.class,
+        size: window.size,
+    }))
+}
+
+/// Prints the class name, then `KEY=VALUE` geometry lines
+fn x11_active_window() -> Result<Option<ActiveWindow>, ProteanError> {
+    let output = Command::new("xdotool")
+        .args(["getactivewindow", "getwindowclassname", "getwindowgeometry", "--shell"])
+        .output()
+        .map_err(|e| window_error("Failed to execute xdotool - is it installed?", e))?;
+
+    // xdotool fails when no window is active (e.g. the desktop is focused)
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    let stdout = String::from_utf8(output.stdout)
+        .map_err(|e| window_error("xdotool returned invalid UTF-8", e))?;
+    let mut lines = stdout.lines();
+    let Some(class) = lines.next().map(str::trim).filter(|class| !class.is_empty()) else {
+        return Ok(None);
+    };
+    let (mut width, mut height) = (0, 0);
+    for line in lines {
+        match line.split_once('=') {
+            Some(("WIDTH", value)) => width = value.trim().parse().unwrap_or(0),
+            Some(("HEIGHT", value)) => height = value.trim().parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+
+    Ok(Some(ActiveWindow {
+        class: class.to_string(),
+        size: (width, height),
+    }))
+}
+
+fn window_error(message: &str, cause: impl std::error::Error + Send + Sync + 'static) -> ProteanError {
+    ProteanError::Window {
+        message: message.to_string(),
+        cause: Box::new(cause),
+    }
 }